@@ -0,0 +1,60 @@
+//! A baseline for performance work, run with `cargo bench --features test-utils`
+//!
+//! Measures the fixture image, set `AOT_BENCH_BINARY` to the path of a client binary for numbers that matter.
+//! Throughput is reported in elements per second, which are MethodTables for the scan and types for the decode.
+
+use aot_blobs::{
+    binary::{NativeAotBinary, ScanSections},
+    embedded_meta::MetadataReader,
+    test_utils::ida_fixture_image,
+};
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use pelite::pe64::PeFile;
+
+/// Decodes every type and names it, which is what every dump starts with
+fn decode_all_types(metadata: MetadataReader<'_>) -> u64 {
+    let mut types = 0;
+
+    for scope in metadata
+        .header()
+        .scope_definitions()
+        .iter()
+        .unwrap()
+        .flatten()
+        .flat_map(|hdl| hdl.to_data(metadata))
+    {
+        for typ in scope.get_all_types().unwrap() {
+            typ.get_full_name_with_generics().unwrap();
+            types += 1;
+        }
+    }
+
+    types
+}
+
+fn throughput(c: &mut Criterion) {
+    let image = match std::env::var_os("AOT_BENCH_BINARY") {
+        Some(path) => std::fs::read(path).unwrap(),
+        None => ida_fixture_image(),
+    };
+    let pe = PeFile::from_bytes(&image).unwrap();
+    let binary = NativeAotBinary::load_pe(pe, ScanSections::default()).unwrap();
+
+    let tables = binary.scan_method_tables().unwrap().len() as u64;
+    let mut group = c.benchmark_group("scan");
+    group.throughput(Throughput::Elements(tables));
+    group.bench_function("scan_method_tables", |b| {
+        b.iter(|| binary.scan_method_tables().unwrap())
+    });
+    group.finish();
+
+    let metadata = binary.rtr_header().metadata().unwrap();
+    let types = decode_all_types(metadata);
+    let mut group = c.benchmark_group("metadata");
+    group.throughput(Throughput::Elements(types));
+    group.bench_function("get_all_types", |b| b.iter(|| decode_all_types(metadata)));
+    group.finish();
+}
+
+criterion_group!(benches, throughput);
+criterion_main!(benches);
@@ -0,0 +1,108 @@
+# Creates the structures written by `dump-ghidra` in the data type manager of the current program
+# @category NativeAOT
+import json
+
+from ghidra.program.model.data import (
+    BooleanDataType,
+    ByteDataType,
+    CategoryPath,
+    DataType,
+    DataTypeConflictHandler,
+    DoubleDataType,
+    FloatDataType,
+    IntegerDataType,
+    LongLongDataType,
+    PointerDataType,
+    ShortDataType,
+    SignedByteDataType,
+    StructureDataType,
+    UnsignedIntegerDataType,
+    UnsignedLongLongDataType,
+    UnsignedShortDataType,
+)
+
+CATEGORY = "/NativeAOT/Structs"
+
+PRIMITIVES = {
+    "System.Boolean": BooleanDataType.dataType,
+    "System.Char": UnsignedShortDataType.dataType,
+    "System.SByte": SignedByteDataType.dataType,
+    "System.Byte": ByteDataType.dataType,
+    "System.Int16": ShortDataType.dataType,
+    "System.UInt16": UnsignedShortDataType.dataType,
+    "System.Int32": IntegerDataType.dataType,
+    "System.UInt32": UnsignedIntegerDataType.dataType,
+    "System.Int64": LongLongDataType.dataType,
+    "System.UInt64": UnsignedLongLongDataType.dataType,
+    "System.IntPtr": LongLongDataType.dataType,
+    "System.UIntPtr": UnsignedLongLongDataType.dataType,
+    "System.Single": FloatDataType.dataType,
+    "System.Double": DoubleDataType.dataType,
+}
+
+def category_of(name):
+    return CategoryPath("/".join([CATEGORY] + name[:-1]))
+
+def field_type(type_name, structs):
+    if type_name in PRIMITIVES:
+        return PRIMITIVES[type_name]
+
+    # Structs listed before are embedded. References are pointers, and so are the value types without a declared
+    # layout, whose size isn't known here
+    if type_name in structs:
+        return structs[type_name]
+
+    return PointerDataType.dataType
+
+def create_struct(definition, structs):
+    name = definition["name"]
+    struct = StructureDataType(category_of(name), name[-1], 0)
+
+    packing = definition.get("packing")
+    if packing is not None:
+        struct.setExplicitPackingValue(packing)
+
+    for field in definition["fields"]:
+        data_type = field_type(field["type_name"], structs)
+
+        if definition["explicit"]:
+            offset = field["offset"]
+
+            # Overlapping fields (unions) only keep the first one, the others are mentioned in its comment
+            component = struct.getComponentContaining(offset) if offset < struct.getLength() else None
+            if component is not None and component.getDataType() != DataType.DEFAULT:
+                comment = component.getComment() or ""
+                component.setComment((comment + " " + field["name"]).strip())
+                continue
+
+            if offset + data_type.getLength() > struct.getLength():
+                struct.growStructure(offset + data_type.getLength() - struct.getLength())
+
+            struct.replaceAtOffset(offset, data_type, data_type.getLength(), field["name"], None)
+        else:
+            struct.add(data_type, data_type.getLength(), field["name"], None)
+
+    size = definition.get("size")
+    if size is not None and struct.getLength() < size:
+        struct.growStructure(size - struct.getLength())
+
+    return struct
+
+def main():
+    path = askFile("Select the JSON file written by dump-ghidra", "Import")
+
+    with open(path.getAbsolutePath(), "r") as f:
+        data = json.load(f)
+
+    manager = currentProgram.getDataTypeManager()
+    structs = {}
+
+    # Structs are created in the order they're listed, so fields of a later struct fall back to a pointer
+    for definition in data["structs"]:
+        struct = create_struct(definition, structs)
+        struct = manager.addDataType(struct, DataTypeConflictHandler.REPLACE_HANDLER)
+        structs[".".join(definition["name"])] = struct
+
+    print("[+] Created %d structs" % len(structs))
+
+main()
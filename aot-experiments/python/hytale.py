@@ -2,6 +2,7 @@ import json
 import ida_kernwin
 import ida_dirtree
 import ida_bytes
+import ida_name
 import idc
 
 from pydantic import BaseModel
@@ -10,17 +11,40 @@ from typing import List, Optional
 class HytaleDefinition(BaseModel):
     mt_structs: List[MtStruct]
     functions: List[Function]
+    globals: List[Global] = []
 
 class MtStruct(BaseModel):
     name: List[str]
     vtables: int
     ifaces: int
     address: int
+    vtable_slots: Optional[List[str]] = None
 
 class Function(BaseModel):
     name: str
     address: int
 
+class Global(BaseModel):
+    name: str
+    address: int
+
+def expand_interned(data: dict) -> dict:
+    # `--interned` definitions reference their names by index into a shared table
+    strings = data.pop("strings")
+
+    for mt in data["mt_structs"]:
+        mt["name"] = [strings[index] for index in mt["name"]]
+        if mt.get("vtable_slots") is not None:
+            mt["vtable_slots"] = [strings[index] for index in mt["vtable_slots"]]
+
+    for fn in data["functions"]:
+        fn["name"] = strings[fn["name"]]
+
+    for glob in data.get("globals", []):
+        glob["name"] = strings[glob["name"]]
+
+    return data
+
 def load_json() -> Optional[HytaleDefinition]:
     json_path = ida_kernwin.ask_file(0, "*.json", "Select JSON file to parse")
 
@@ -30,11 +54,16 @@ def load_json() -> Optional[HytaleDefinition]:
 
     try:
         with open(json_path, 'r', encoding='utf-8') as f:
-            return HytaleDefinition.model_validate_json(f.read())
+            data = json.load(f)
+
+        if "strings" in data:
+            data = expand_interned(data)
+
+        return HytaleDefinition.model_validate(data)
     except Exception as e:
         print(f"[!] Error parsing JSON: {str(e)}")
 
-def create_mt_struct(struct_name: List[str], num_vtable=0, num_interface=0):
+def create_mt_struct(struct_name: List[str], num_vtable=0, num_interface=0, vtable_slots: Optional[List[str]] = None):
     # Actual type name will be the full name including namespace
     full_name = "_".join(struct_name)
 
@@ -66,7 +95,15 @@ def create_mt_struct(struct_name: List[str], num_vtable=0, num_interface=0):
     idc.add_struc_member(sid, "usNumInterfaceSlots", -1, idc.FF_WORD, -1, 2)
     idc.add_struc_member(sid, "uHashCode", -1, idc.FF_DWORD, -1, 4)
 
-    if num_vtable > 0:
+    if vtable_slots:
+        # Every slot is typed as a function pointer, named after the method it points to
+        for slot_name in vtable_slots:
+            member_name = ida_name.validate_name(slot_name, ida_name.VNT_IDENT)
+            offset = idc.get_struc_size(sid)
+
+            idc.add_struc_member(sid, member_name, -1, idc.FF_QWORD | idc.FF_0OFF, -1, 8)
+            idc.SetType(idc.get_member_id(sid, offset), "void (__fastcall *)()")
+    elif num_vtable > 0:
         idc.add_struc_member(sid, "vtable_slots", -1, idc.FF_QWORD | idc.FF_0OFF, -1, 8 * num_vtable)
 
     if num_interface > 0:
@@ -91,7 +128,7 @@ def main():
     type_dir.mkdir("NativeAOT/Structs")
 
     for mt in parsed_data.mt_structs:
-        sid = create_mt_struct(mt.name, mt.vtables, mt.ifaces)
+        sid = create_mt_struct(mt.name, mt.vtables, mt.ifaces, mt.vtable_slots)
         if sid == -1:
             print(f"Failed to create struct {mt.name}")
             continue
@@ -101,5 +138,8 @@ def main():
     for fn in parsed_data.functions:
         create_method(fn.name, fn.address)
 
+    for glob in parsed_data.globals:
+        idc.set_name(glob.address, glob.name, idc.SN_NOCHECK | 0x800)
+
 if __name__ == '__main__':
     main()
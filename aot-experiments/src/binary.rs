@@ -1,3 +1,18 @@
+pub mod address_kind;
+pub mod cctor_context;
+pub mod debug_info;
+pub mod dispatch;
+pub mod field_access;
+pub mod frozen;
+pub mod generics;
+pub mod inlining;
+pub mod interop;
+pub mod invoke_map;
+pub mod symbols;
+pub mod type_manager;
+pub mod type_map;
+pub mod vtable;
+
 pub mod headers {
     pub mod mt;
     pub mod rtr;
@@ -6,67 +21,148 @@ pub mod headers {
 use std::{
     collections::{HashMap, hash_map::Entry},
     rc::Rc,
+    sync::OnceLock,
 };
 
-use anyhow::{Result, bail};
 use binary_rw::{BinaryReader, Endian, SeekStream};
-use pelite::pe64::{Pe, PeFile, PeObject};
+use log::{debug, warn};
+use pelite::{
+    image::RUNTIME_FUNCTION,
+    pe64::{Pe, PeFile, PeObject, Rva, Va, headers::SectionHeader},
+};
 
 use crate::{
     binary::headers::{
-        mt::{ElementType, MethodTable},
-        rtr::ReadyToRunHeader,
+        mt::{ElementType, MethodTable, OwnedMethodTable},
+        rtr::{ReadyToRunHeader, ReadyToRunSectionType},
     },
-    native_format::View,
+    embedded_meta::handles::FieldHandle,
+    error::{AotError, Result},
+    native_format::{View, array::NativeArray, parser::NativeParser, reader::NativeReader},
 };
 
 pub struct NativeAotBinary<'a> {
     pe: PeFile<'a>,
 
     rtr: ReadyToRunHeader<'a>,
+
+    /// Sections that are scanned for the RTR header and MethodTables
+    scan_sections: ScanSections,
+
+    /// See [`Self::symbol_index`]
+    symbols: OnceLock<symbols::SymbolIndex>,
+
+    /// See [`Self::address_index`]
+    addresses: OnceLock<address_kind::AddressIndex>,
+
+    /// See [`Self::field_storage`]
+    field_storages: OnceLock<HashMap<FieldHandle, field_access::FieldStorage>>,
+}
+
+/// Which sections of the image are scanned for NativeAOT data structures
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanSections {
+    /// Only the sections with these names, in order
+    Named(Vec<String>),
+
+    /// Every section in the image, for binaries that place their data in unusual sections
+    All,
+}
+
+impl Default for ScanSections {
+    fn default() -> Self {
+        Self::Named(
+            NativeAotBinary::CANDIDATE_DATA_SECTIONS
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+        )
+    }
+}
+
+impl ScanSections {
+    /// The headers of the sections to scan, sections that aren't in the image are skipped
+    pub fn headers<'a>(&self, pe: PeFile<'a>) -> Vec<&'a SectionHeader> {
+        match self {
+            Self::Named(names) => names
+                .iter()
+                .filter_map(|name| {
+                    let sect = pe.section_headers().by_name(name);
+                    if sect.is_none() {
+                        debug!("Image has no {name} section to scan");
+                    }
+
+                    sect
+                })
+                .collect(),
+            Self::All => pe.section_headers().iter().collect(),
+        }
+    }
 }
 
 // Initialization
 impl<'a> NativeAotBinary<'a> {
-    const CANDIDATE_DATA_SECTIONS: &'static [&'static str] = &[".rdata", ".pdata", ".data"];
+    pub const CANDIDATE_DATA_SECTIONS: &'static [&'static str] = &[".rdata", ".pdata", ".data"];
 
     // Loads the NativeAOT binary given a known RTR header address
     pub fn from_pe(pe: PeFile<'a>, rtr_address: u64) -> Result<Self> {
         let mut view = View::new(pe, rtr_address);
         let rtr = ReadyToRunHeader::parse(&mut view)?;
 
-        Ok(Self { pe, rtr })
+        Ok(Self {
+            pe,
+            rtr,
+            scan_sections: ScanSections::default(),
+            symbols: OnceLock::new(),
+            addresses: OnceLock::new(),
+            field_storages: OnceLock::new(),
+        })
     }
 
-    // Loads the NativeAOT binary by scanning for an RTR header
-    pub fn load_pe(pe: PeFile<'a>) -> Result<Self> {
-        for sect_name in Self::CANDIDATE_DATA_SECTIONS {
-            let Some(sect) = pe.section_headers().by_name(sect_name) else {
-                continue;
-            };
-
-            for offset in sect.file_range().step_by(8) {
-                let offset = offset as usize;
-                let signature =
-                    u32::from_le_bytes(pe.image()[offset..offset + 4].try_into().unwrap());
-
-                if headers::rtr::Signature::try_from(signature).is_ok() {
-                    let Ok(va) = pe
-                        .file_offset_to_rva(offset)
-                        .and_then(|rva| pe.rva_to_va(rva))
-                    else {
-                        continue;
-                    };
-
-                    let mut view = View::new(pe, va);
-                    if let Ok(rtr) = ReadyToRunHeader::parse(&mut view) {
-                        return Ok(Self { pe, rtr });
+    // Loads the NativeAOT binary by scanning the given sections for an RTR header
+    //
+    // The same sections are scanned for MethodTables later on.
+    pub fn load_pe(pe: PeFile<'a>, scan_sections: ScanSections) -> Result<Self> {
+        for sect in scan_sections.headers(pe) {
+            let sect_name = sect.name().unwrap_or("<invalid>");
+
+            for (view, slot) in section_slots(pe, sect) {
+                let signature = u32::from_le_bytes([slot[0], slot[1], slot[2], slot[3]]);
+
+                // A header without a single valid section is most likely a false positive
+                if headers::rtr::Signature::try_from(signature).is_ok()
+                    && let Ok(rtr) = ReadyToRunHeader::parse_lenient(&mut { view })
+                    && !rtr.sections.is_empty()
+                {
+                    debug!(
+                        "Found ReadyToRun header at {:#x} in {sect_name} ({} sections)",
+                        view.va(),
+                        rtr.sections.len()
+                    );
+
+                    for (index, why) in &rtr.section_errors {
+                        warn!("Skipped ReadyToRun section {index}: {why}");
                     }
+
+                    return Ok(Self {
+                        pe,
+                        rtr,
+                        scan_sections,
+                        symbols: OnceLock::new(),
+                        addresses: OnceLock::new(),
+                        field_storages: OnceLock::new(),
+                    });
                 }
             }
         }
 
-        bail!("Unable to locate ReadyToRun header");
+        Err(AotError::RtrHeaderNotFound)
+    }
+
+    // Makes the metadata reader fail on strings that aren't valid UTF-8, rather than replacing the invalid bytes
+    pub fn with_strict_strings(mut self, strict_strings: bool) -> Self {
+        self.rtr.strict_strings = strict_strings;
+        self
     }
 }
 
@@ -75,6 +171,13 @@ impl<'a> NativeAotBinary<'a> {
     pub fn pe(&self) -> PeFile<'a> {
         self.pe
     }
+
+    /// Returns `None` for addresses without file backing, e.g. in the uninitialized part of a section
+    pub fn va_to_file_offset(&self, va: Va) -> Option<usize> {
+        let rva = self.pe.va_to_rva(va).ok()?;
+
+        self.pe.rva_to_file_offset(rva).ok()
+    }
 }
 
 // RTR stuff
@@ -84,6 +187,136 @@ impl<'a> NativeAotBinary<'a> {
     }
 }
 
+// Code stuff
+impl<'a> NativeAotBinary<'a> {
+    pub fn runtime_functions(&self) -> Option<&'a [RUNTIME_FUNCTION]> {
+        // R2R images carry their own table, otherwise fall back to the one in the exception directory
+        if let Some(section) = self.rtr.section(ReadyToRunSectionType::RuntimeFunctions) {
+            let rva = self.pe.va_to_rva(section.start.va()).ok()?;
+            let count = (section.end.va() - section.start.va()) as usize
+                / std::mem::size_of::<RUNTIME_FUNCTION>();
+
+            return self.pe.derva_slice(rva, count).ok();
+        }
+
+        self.pe.exception().ok().map(|exception| exception.image())
+    }
+
+    /// The machine code of the function starting at `rva`
+    ///
+    /// The function is sized using the RuntimeFunctions table. Addresses that don't start an entry in the table (e.g. a
+    /// code fragment) extend up to the next function instead. Returns `None` for images without the table.
+    pub fn function_bytes(&self, rva: Rva) -> Option<&'a [u8]> {
+        let functions = self.runtime_functions()?;
+
+        // The table is sorted by start address
+        let end = match functions.binary_search_by_key(&rva, |function| function.BeginAddress) {
+            Ok(index) => functions[index].EndAddress,
+            Err(index) => functions.get(index)?.BeginAddress,
+        };
+
+        self.pe
+            .derva_slice(rva, end.checked_sub(rva)? as usize)
+            .ok()
+    }
+
+    /// Returns the entry points of all compiled method definitions by their 1-based ECMA method def row
+    ///
+    /// The rows index the MethodDef table of the input assembly, which the NativeAOT metadata doesn't keep: its handles
+    /// are offsets into the metadata blob, and there's no table mapping one onto the other. The rows thus can't be
+    /// resolved to a [`MethodHandle`](crate::embedded_meta::handles::MethodHandle) or to a name, and the addresses
+    /// are all RUNTIME_FUNCTION starts, which [`Self::classify_address`] already knows as functions.
+    pub fn method_entry_points(&self) -> Result<Vec<(u32, Rva)>> {
+        let Some(section) = self
+            .rtr
+            .section(ReadyToRunSectionType::MethodDefEntryPoints)
+        else {
+            return Err(AotError::MissingTable("a MethodDefEntryPoints section"));
+        };
+
+        let Some(functions) = self.runtime_functions() else {
+            return Err(AotError::MissingTable("a RuntimeFunctions table"));
+        };
+
+        let reader = NativeReader::new(section.start.bytes()?)?;
+        let array = NativeArray::new(NativeParser::new(reader, 0))?;
+        let mut entry_points = Vec::new();
+
+        for (index, mut parser) in array.iter() {
+            // Methods that require fixups have bit 0 set, the fixup list itself is not needed here
+            let id = parser.get_unsigned()?;
+            let function_index = if id & 1 != 0 { id >> 2 } else { id >> 1 };
+
+            let Some(function) = functions.get(function_index as usize) else {
+                continue;
+            };
+
+            // The array is indexed by the method def row, minus one
+            entry_points.push((index + 1, function.BeginAddress));
+        }
+
+        debug!(
+            "MethodDefEntryPoints has {} entry points",
+            entry_points.len()
+        );
+
+        Ok(entry_points)
+    }
+
+    /// The static constructors that run eagerly at startup, before the module initializers
+    pub fn eager_cctors(&self) -> Result<Vec<Va>> {
+        self.initializer_list(ReadyToRunSectionType::EagerCctor)
+    }
+
+    /// The module initializers, in the order they run at startup
+    pub fn module_initializers(&self) -> Result<Vec<Va>> {
+        self.initializer_list(ReadyToRunSectionType::ModuleInitializerList)
+    }
+
+    // Both lists are arrays of relative pointers to the functions to call, empty when the image doesn't have them
+    fn initializer_list(&self, section_type: ReadyToRunSectionType) -> Result<Vec<Va>> {
+        let Some(section) = self.rtr.section(section_type) else {
+            return Ok(Vec::new());
+        };
+
+        let mut functions = Vec::new();
+        let mut position = section.start.va();
+
+        while position + 4 <= section.end.va() {
+            let mut view = View::new(self.pe, position);
+            let delta = BinaryReader::new(&mut view, Endian::Little).read_i32()?;
+
+            functions.push(position.wrapping_add_signed(delta as i64));
+            position += 4;
+        }
+
+        Ok(functions)
+    }
+}
+
+/// The 8 byte slots of the raw data of a section, together with a view of every slot
+///
+/// The section is only translated to a VA once, after which the slots are read straight from the file.
+fn section_slots<'a>(
+    pe: PeFile<'a>,
+    section: &SectionHeader,
+) -> impl Iterator<Item = (View<'a>, [u8; 8])> + 'a {
+    let range = section.file_range();
+    let start = View::from_file_offset(pe, range.start as usize).ok();
+    let bytes = pe
+        .image()
+        .get(range.start as usize..range.end as usize)
+        .filter(|_| start.is_some())
+        .unwrap_or_default();
+
+    bytes
+        .chunks_exact(8)
+        .enumerate()
+        .filter_map(move |(index, slot)| {
+            Some((start?.with_offset(index as Va * 8), slot.try_into().ok()?))
+        })
+}
+
 /// Scanning implementation
 impl<'a> NativeAotBinary<'a> {
     pub fn scan_method_tables(&self) -> Result<Vec<MethodTable<'a>>> {
@@ -94,24 +327,7 @@ impl<'a> NativeAotBinary<'a> {
         let object_table = self.find_object_mt()?;
         tables.insert(object_table.view.va(), object_table);
 
-        let mut min = u32::MAX;
-        let mut max = u32::MIN;
-
-        for sect_name in Self::CANDIDATE_DATA_SECTIONS {
-            let sect = self
-                .pe
-                .section_headers()
-                .by_name(sect_name)
-                .ok_or(pelite::Error::Bounds)?;
-
-            if sect.VirtualAddress < min {
-                min = sect.VirtualAddress;
-            }
-
-            if sect.VirtualAddress + sect.VirtualSize > max {
-                max = sect.VirtualAddress + sect.VirtualSize;
-            }
-        }
+        let (min, max) = self.scan_range();
 
         // Store all addresses, we'll need to crawl them all
         let mut unmatched = (min..max).step_by(8).collect::<Vec<_>>();
@@ -123,20 +339,26 @@ impl<'a> NativeAotBinary<'a> {
             unmatched.clear();
 
             for &ptr in &agenda {
-                let Ok(va) = self.pe.rva_to_va(ptr) else {
+                let Ok(mut view) = View::from_rva(self.pe, ptr) else {
                     continue;
                 };
+                let va = view.va();
 
-                let mut view = View::new(self.pe, va);
                 let mut reader = BinaryReader::new(&mut view, Endian::Little);
 
                 // Our goal is that `view` points to a MethodTable we already know
+                // For arrays, pointers and byrefs this is the element type instead, which links them the same way
                 reader.seek(0x8)?; // baseType is located at +0x8
                 let Ok(base_type_va) = reader.read_u64() else {
                     continue;
                 };
                 reader.seek(0)?;
 
+                // Only System.Object, interfaces and the like have no related type, and they're never linked
+                if base_type_va == 0 {
+                    continue;
+                }
+
                 let Ok(rva) = self.pe.va_to_rva(base_type_va) else {
                     continue;
                 };
@@ -188,8 +410,9 @@ impl<'a> NativeAotBinary<'a> {
                     interfaces.push(interface);
                 }
 
-                if let Some(mt) = tables.get_mut(&base_type_va) {
-                    mt.interfaces.borrow_mut().extend(interfaces);
+                // Replaced rather than extended, as a table can be matched again on a later pass
+                if let Some(mt) = tables.get(&va) {
+                    *mt.interfaces.borrow_mut() = interfaces;
                 }
             }
 
@@ -198,22 +421,152 @@ impl<'a> NativeAotBinary<'a> {
             }
         }
 
-        return Ok(tables.into_values().collect());
+        debug!("Found {} method tables", tables.len());
+
+        // Ordered by address, so dumps of the same image are identical
+        let mut tables = tables.into_values().collect::<Vec<_>>();
+        tables.sort_by_key(|mt| mt.view.va());
+
+        return Ok(tables);
+    }
+
+    /// Like [`Self::scan_method_tables`], but only matches the tables at `targets` and their base types
+    ///
+    /// Every target is followed through its related types until it reaches a known table, which takes a few reads
+    /// instead of a pass over every slot of the scan sections. Targets that don't lead to System.Object are left out.
+    ///
+    /// Interfaces are parsed on their own, so their related types aren't linked, and named by looking each of them up
+    /// with [`Self::type_names_of`].
+    pub fn scan_method_tables_for(&self, targets: &[Va]) -> Result<Vec<MethodTable<'a>>> {
+        let mut tables = HashMap::new();
+
+        let object_table = self.find_object_mt()?;
+        tables.insert(object_table.view.va(), object_table);
+
+        let (min, max) = self.scan_range();
+
+        for &target in targets {
+            // The part of the hierarchy that isn't known yet, starting at the target
+            let mut chain: Vec<MethodTable<'a>> = Vec::new();
+            let mut va = target;
+
+            while !tables.contains_key(&va) {
+                let Ok(rva) = self.pe.va_to_rva(va) else {
+                    break;
+                };
+
+                // Related types that loop back into the chain can't be MethodTables either
+                if rva < min || rva >= max || chain.iter().any(|mt| mt.view.va() == va) {
+                    break;
+                }
+
+                let Ok(mt) = MethodTable::parse(&mut View::new(self.pe, va)) else {
+                    break;
+                };
+
+                va = mt.related_type_address;
+                chain.push(mt);
+            }
+
+            let Some(mut related_type) = tables.get(&va).cloned() else {
+                continue;
+            };
+
+            // Linked from the known table down to the target
+            for mut mt in chain.into_iter().rev() {
+                mt.related_type = Some(Rc::new(related_type));
+
+                let interfaces = mt
+                    .iface_addresses
+                    .iter()
+                    .filter(|&&va| va != 0)
+                    .filter_map(|&va| {
+                        tables
+                            .get(&va)
+                            .cloned()
+                            .or_else(|| MethodTable::parse(&mut View::new(self.pe, va)).ok())
+                    })
+                    .collect::<Vec<_>>();
+                mt.interfaces.borrow_mut().extend(interfaces);
+
+                related_type = mt.clone();
+                tables.insert(mt.view.va(), mt);
+            }
+        }
+
+        debug!(
+            "Found {} method tables for {} targets",
+            tables.len(),
+            targets.len()
+        );
+
+        let mut tables = tables.into_values().collect::<Vec<_>>();
+        tables.sort_by_key(|mt| mt.view.va());
+
+        let interfaces = tables
+            .iter()
+            .flat_map(|mt| mt.iface_addresses.iter().copied())
+            .filter(|&va| va != 0)
+            .collect::<Vec<_>>();
+
+        match self.type_names_of(interfaces) {
+            Ok(names) => self.name_interfaces(&mut tables, &names),
+            Err(why) => debug!("Unable to name interfaces: {why}"),
+        }
+
+        Ok(tables)
+    }
+
+    /// RVAs from the start of the first scan section up to the end of the last one, where MethodTables are looked for
+    fn scan_range(&self) -> (u32, u32) {
+        let mut min = u32::MAX;
+        let mut max = u32::MIN;
+
+        for sect in self.scan_sections.headers(self.pe) {
+            if sect.VirtualAddress < min {
+                min = sect.VirtualAddress;
+            }
+
+            if sect.VirtualAddress + sect.VirtualSize > max {
+                max = sect.VirtualAddress + sect.VirtualSize;
+            }
+        }
+
+        (min, max)
+    }
+
+    /// Fills in the [`MethodTable::iface_names`] of every table, given the names of [`Self::type_names`]
+    ///
+    /// [`Self::scan_method_tables`] leaves the names empty, as most callers don't need the whole TypeMap decoded. The
+    /// ones that do can pass the names they already have.
+    pub fn name_interfaces(&self, tables: &mut [MethodTable<'a>], names: &HashMap<Va, String>) {
+        for mt in tables {
+            mt.iface_names = mt
+                .iface_addresses
+                .iter()
+                .map(|&va| {
+                    names.get(&va).cloned().or_else(|| {
+                        // Instantiations like IEquatable<T> aren't in the TypeMap themselves
+                        MethodTable::parse(&mut View::new(self.pe, va))
+                            .ok()?
+                            .generic_instance_name(names)
+                    })
+                })
+                .collect();
+        }
+    }
+
+    /// Same as [`Self::scan_method_tables`], but produces tables that can be shared across threads
+    pub fn scan_method_tables_owned(&self) -> Result<Vec<OwnedMethodTable>> {
+        let tables = self.scan_method_tables()?;
+
+        Ok(OwnedMethodTable::from_tables(&tables))
     }
 
     pub fn find_object_mt(&self) -> Result<MethodTable<'a>> {
-        let scan_section = |name: &str| -> Result<Option<MethodTable<'a>>> {
-            let section = self
-                .pe
-                .section_headers()
-                .by_name(name)
-                .ok_or(pelite::Error::Bounds)?;
-
-            'out: for offset in section.file_range().step_by(8) {
-                let offset = offset as usize;
-                let va =
-                    u64::from_le_bytes(self.pe.image()[offset..offset + 8].try_into().unwrap());
-                let mut view = View::new(self.pe, va);
+        let scan_section = |section: &SectionHeader| -> Result<Option<MethodTable<'a>>> {
+            'out: for (_, slot) in section_slots(self.pe, section) {
+                let mut view = View::new(self.pe, u64::from_le_bytes(slot));
 
                 let Ok(mt) = MethodTable::parse(&mut view) else {
                     continue;
@@ -260,12 +613,215 @@ impl<'a> NativeAotBinary<'a> {
             Ok(None)
         };
 
-        for sect_name in Self::CANDIDATE_DATA_SECTIONS {
-            if let Some(table) = scan_section(sect_name)? {
+        for sect in self.scan_sections.headers(self.pe) {
+            if let Some(table) = scan_section(sect)? {
                 return Ok(table);
             }
         }
 
-        bail!("MethodTable not found or present in binary");
+        Err(AotError::MethodTableNotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use pelite::pe64::PeFile;
+
+    use crate::{
+        binary::{
+            NativeAotBinary, ScanSections,
+            headers::{
+                mt::{ElementType, MethodTable},
+                rtr::ReadyToRunSectionType,
+            },
+        },
+        embedded_meta::hashing::compute_name_hashcode,
+        error::AotError,
+        native_format::View,
+        test_utils::{
+            IMAGE_BASE, PeBuilder, ida_fixture_image, minimal_image, minimal_image_with_sections,
+        },
+    };
+
+    #[test]
+    fn load_pe_finds_rtr_header() {
+        let (image, offsets) = minimal_image();
+        let pe = PeFile::from_bytes(&image).unwrap();
+        let binary = NativeAotBinary::load_pe(pe, ScanSections::default()).unwrap();
+
+        assert_eq!(binary.rtr_header().number_of_sections, 1);
+
+        let metadata = binary.rtr_header().metadata().unwrap();
+        let typ = metadata.find_type("Game.Player").unwrap().unwrap();
+        assert_eq!(typ.handle().offset(), offsets.typ);
+    }
+
+    #[test]
+    fn load_pe_skips_corrupt_sections() {
+        // An unknown section pointing far outside of the image
+        let (image, _) = minimal_image_with_sections(&[(999, 0x7fff_0000_0000, 0x7fff_0000_1000)]);
+        let pe = PeFile::from_bytes(&image).unwrap();
+        let binary = NativeAotBinary::load_pe(pe, ScanSections::default()).unwrap();
+        let header = binary.rtr_header();
+
+        assert_eq!(header.number_of_sections, 2);
+        assert_eq!(header.sections.len(), 1);
+        assert_eq!(header.section_errors.len(), 1);
+        assert_eq!(header.section_errors[0].0, 1);
+        assert!(header.metadata().is_some());
+    }
+
+    #[test]
+    fn load_pe_skips_sections_ending_outside_the_sections() {
+        // Within the image, but in the padding after the data of `.rdata`
+        let rdata_va = IMAGE_BASE + PeBuilder::new().next_rva() as u64;
+        let (image, _) = minimal_image_with_sections(&[(999, rdata_va, rdata_va + 0xF00)]);
+        let pe = PeFile::from_bytes(&image).unwrap();
+        let binary = NativeAotBinary::load_pe(pe, ScanSections::default()).unwrap();
+        let header = binary.rtr_header();
+
+        assert_eq!(header.section_errors.len(), 1);
+        assert!(matches!(
+            header.section_errors[0].1,
+            AotError::BadSectionRange { .. }
+        ));
+        assert!(
+            header
+                .section(ReadyToRunSectionType::Unknown(999))
+                .is_none()
+        );
+        assert!(header.metadata().is_some());
+    }
+
+    #[test]
+    fn pointers_name_primitive_pointees_by_their_hashcode() {
+        let mut builder = PeBuilder::new();
+        let data_va = IMAGE_BASE + builder.next_rva() as u64;
+
+        // Int32, an enum based on it, and a pointer to either, all without vtable slots or interfaces
+        let tables = [
+            (ElementType::Int32, 0, "System.Int32"),
+            (ElementType::Int32, 0, "Game.Team"),
+            (ElementType::Pointer, data_va, ""),
+            (ElementType::Pointer, data_va + 0x18, ""),
+        ];
+        let mut data = Vec::new();
+        for (element_type, related_type, name) in tables {
+            data.extend_from_slice(&((element_type as u32) << 26).to_le_bytes());
+            data.extend_from_slice(&0x18u32.to_le_bytes()); // BaseSize
+            data.extend_from_slice(&related_type.to_le_bytes());
+            data.extend_from_slice(&[0; 4]); // VTableCount, InterfaceCount
+            data.extend_from_slice(&compute_name_hashcode(name).to_le_bytes());
+        }
+        builder.section(".data", data);
+
+        let image = builder.build();
+        let pe = PeFile::from_bytes(&image).unwrap();
+        let parse = |va| MethodTable::parse(&mut View::new(pe, va)).unwrap();
+
+        assert_eq!(
+            parse(data_va + 0x30).primitive_element(),
+            Some(ElementType::Int32)
+        );
+        assert_eq!(parse(data_va + 0x48).primitive_element(), None);
+        assert_eq!(parse(data_va).primitive_element(), None);
+    }
+
+    #[test]
+    fn single_tables_are_named_by_their_hashcode() {
+        let image = ida_fixture_image();
+        let pe = PeFile::from_bytes(&image).unwrap();
+        let binary = NativeAotBinary::load_pe(pe, ScanSections::default()).unwrap();
+
+        let tables = binary.scan_method_tables().unwrap();
+        let table = |name| {
+            tables
+                .iter()
+                .find(|mt| mt.hashcode as i32 == compute_name_hashcode(name))
+                .unwrap()
+        };
+        let (object, player) = (table("System.Object"), table("Game.Player"));
+
+        let handle = binary.find_type_definition(player).unwrap().unwrap();
+        assert_eq!(
+            binary.type_map().unwrap().get(&player.view.va()),
+            Some(&handle)
+        );
+        assert_eq!(binary.find_type_definition(object).unwrap(), None);
+
+        // System.Object has no metadata, so it stays unnamed
+        assert_eq!(
+            binary
+                .type_names_of([object.view.va(), player.view.va()])
+                .unwrap(),
+            HashMap::from([(player.view.va(), "Game.Player".to_string())])
+        );
+    }
+
+    #[test]
+    fn available_types_are_read_from_the_type_map() {
+        let image = ida_fixture_image();
+        let pe = PeFile::from_bytes(&image).unwrap();
+        let binary = NativeAotBinary::load_pe(pe, ScanSections::default()).unwrap();
+
+        let names = binary
+            .available_types()
+            .unwrap()
+            .iter()
+            .map(|typ| typ.get_full_name().unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, ["Game.Player"]);
+    }
+
+    #[test]
+    fn type_manager_is_found_through_its_indirection() {
+        let rdata_va = IMAGE_BASE + PeBuilder::new().next_rva() as u64;
+        let (image, _) = minimal_image_with_sections(&[(204, rdata_va, rdata_va + 8)]);
+        let pe = PeFile::from_bytes(&image).unwrap();
+        let binary = NativeAotBinary::load_pe(pe, ScanSections::default()).unwrap();
+
+        let type_manager = binary.type_manager().unwrap();
+
+        assert_eq!(type_manager.indirection, rdata_va);
+        assert_eq!(type_manager.header, rdata_va);
+        assert!(type_manager.handle.is_some());
+        assert_eq!(type_manager.gc_static_region, None);
+
+        let (image, _) = minimal_image();
+        let pe = PeFile::from_bytes(&image).unwrap();
+        let binary = NativeAotBinary::load_pe(pe, ScanSections::default()).unwrap();
+
+        assert!(binary.type_manager().is_none());
+    }
+
+    #[test]
+    fn scan_method_tables_for_only_matches_the_targets_and_their_bases() {
+        let image = ida_fixture_image();
+        let pe = PeFile::from_bytes(&image).unwrap();
+        let binary = NativeAotBinary::load_pe(pe, ScanSections::default()).unwrap();
+
+        let object = binary.find_object_mt().unwrap().view.va();
+        let player = binary
+            .scan_method_tables()
+            .unwrap()
+            .iter()
+            .find(|mt| mt.hashcode as i32 == compute_name_hashcode("Game.Player"))
+            .unwrap()
+            .view
+            .va();
+
+        // Addresses that aren't MethodTables are left out
+        let tables = binary
+            .scan_method_tables_for(&[player, IMAGE_BASE + 0x1000, 0])
+            .unwrap();
+
+        assert_eq!(
+            tables.iter().map(|mt| mt.view.va()).collect::<Vec<_>>(),
+            [object, player]
+        );
+        assert_eq!(tables[1].base_table().unwrap().view.va(), object);
     }
 }
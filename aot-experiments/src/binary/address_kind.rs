@@ -0,0 +1,147 @@
+//! Telling apart what a pointer found in the image points to
+
+use std::collections::HashSet;
+
+use log::{debug, warn};
+use pelite::pe64::{Pe, Va};
+
+use crate::{binary::NativeAotBinary, error::Result};
+
+/// What an address points to, see [`NativeAotBinary::classify_address`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressKind {
+    /// The start of a MethodTable found by the scan
+    MethodTable,
+    /// The start of a RUNTIME_FUNCTION, or the entry point of a method listed in the InvokeMap
+    Function,
+    /// Code called through a vtable slot that isn't the start of a known function
+    VtableThunk,
+    Unknown,
+}
+
+/// The addresses the image is known to have MethodTables and functions at
+#[derive(Debug, Clone, Default)]
+pub struct AddressIndex {
+    method_tables: HashSet<Va>,
+    functions: HashSet<Va>,
+    vtable_targets: HashSet<Va>,
+}
+
+impl AddressIndex {
+    pub fn classify(&self, va: Va) -> AddressKind {
+        if self.method_tables.contains(&va) {
+            AddressKind::MethodTable
+        } else if self.functions.contains(&va) {
+            AddressKind::Function
+        } else if self.vtable_targets.contains(&va) {
+            AddressKind::VtableThunk
+        } else {
+            AddressKind::Unknown
+        }
+    }
+}
+
+impl<'a> NativeAotBinary<'a> {
+    /// The index of the scanned MethodTables, known functions and vtable slot targets, built on first use
+    pub fn address_index(&self) -> Result<&AddressIndex> {
+        if let Some(index) = self.addresses.get() {
+            return Ok(index);
+        }
+
+        let mut index = AddressIndex::default();
+
+        for mt in self.scan_method_tables()? {
+            index.method_tables.insert(mt.view.va());
+            index
+                .vtable_targets
+                .extend(mt.vtable_addresses.iter().copied());
+        }
+
+        // Every compiled function has an entry, whether the metadata names it or not. This includes the
+        // MethodDefEntryPoints, which point into the same table
+        index.functions.extend(
+            self.runtime_functions()
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|function| self.pe.rva_to_va(function.BeginAddress).ok()),
+        );
+
+        match self.invoke_map().and_then(|map| map.method_pointers()) {
+            Ok(method_ptrs) => index.functions.extend(method_ptrs.into_values()),
+            Err(why) => warn!("Unable to read the InvokeMap: {why}"),
+        }
+
+        debug!(
+            "Address index has {} MethodTables, {} functions and {} vtable targets",
+            index.method_tables.len(),
+            index.functions.len(),
+            index.vtable_targets.len()
+        );
+
+        Ok(self.addresses.get_or_init(|| index))
+    }
+
+    /// Whether the address is the start of a MethodTable, a function, or code only reached through a vtable
+    ///
+    /// Addresses inside of a MethodTable or function are [`AddressKind::Unknown`], see [`Self::symbolize`] for those.
+    pub fn classify_address(&self, va: Va) -> AddressKind {
+        self.address_index()
+            .map_or(AddressKind::Unknown, |index| index.classify(va))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pelite::pe64::PeFile;
+
+    use crate::{
+        binary::{NativeAotBinary, ScanSections, address_kind::AddressKind},
+        test_utils::{IMAGE_BASE, ida_fixture_image, ida_fixture_image_with_sections},
+    };
+
+    #[test]
+    fn addresses_are_classified_by_what_starts_there() {
+        let image = ida_fixture_image();
+        let pe = PeFile::from_bytes(&image).unwrap();
+        let binary = NativeAotBinary::load_pe(pe, ScanSections::default()).unwrap();
+
+        // The vtable slots point to the start of `.text`, `Game.Player.Jump` follows them
+        let text_va = IMAGE_BASE + 0x1000;
+        let player_mt = IMAGE_BASE + 0x2000 + 0x60;
+
+        assert_eq!(binary.classify_address(player_mt), AddressKind::MethodTable);
+        assert_eq!(
+            binary.classify_address(text_va + 0x30),
+            AddressKind::Function
+        );
+        assert_eq!(binary.classify_address(text_va), AddressKind::VtableThunk);
+        assert_eq!(binary.classify_address(player_mt + 8), AddressKind::Unknown);
+    }
+
+    #[test]
+    fn vtable_targets_with_a_runtime_function_are_functions() {
+        const RUNTIME_FUNCTIONS: u32 = 102;
+
+        // Only the first vtable slot points to a function of its own, like a method missing from the InvokeMap
+        let image = ida_fixture_image_with_sections(|_| {
+            let mut functions = Vec::new();
+            for (begin, end) in [(0x1000u32, 0x1010u32), (0x1030, 0x1040)] {
+                functions.extend_from_slice(&begin.to_le_bytes());
+                functions.extend_from_slice(&end.to_le_bytes());
+                functions.extend_from_slice(&0u32.to_le_bytes()); // UnwindData
+            }
+
+            vec![(RUNTIME_FUNCTIONS, functions)]
+        });
+        let pe = PeFile::from_bytes(&image).unwrap();
+        let binary = NativeAotBinary::load_pe(pe, ScanSections::default()).unwrap();
+
+        let text_va = IMAGE_BASE + 0x1000;
+
+        assert_eq!(binary.classify_address(text_va), AddressKind::Function);
+        assert_eq!(
+            binary.classify_address(text_va + 0x10),
+            AddressKind::VtableThunk
+        );
+    }
+}
@@ -0,0 +1,166 @@
+//! The CCtorContextMap, linking the types with a lazily run static constructor to their class constructor context
+//!
+//! The runtime checks the context before every access to the statics of such a type, and runs the cctor it points to
+//! the first time. It is laid out right before the non-GC static base of the type:
+//!
+//! ```text
+//! u64 cctor_method_address    // StaticClassConstructionContext
+//! u8  non_gc_statics[]        // the non-GC static base, which the static field offsets are relative to
+//! ```
+
+use binary_rw::{BinaryReader, Endian};
+use log::debug;
+use pelite::pe64::Va;
+
+use crate::{
+    binary::{NativeAotBinary, headers::rtr::ReflectionMapBlob},
+    error::{AotError, Result},
+    native_format::View,
+};
+
+/// The class constructor context of a type, see [`NativeAotBinary::cctor_contexts`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CctorContext {
+    /// MethodTable of the type
+    pub declaring_type: Va,
+    /// Address of the StaticClassConstructionContext
+    pub context: Va,
+    /// The static constructor the context points to, `None` when the image has no pointer stored
+    pub cctor: Option<Va>,
+}
+
+impl CctorContext {
+    /// Size of the StaticClassConstructionContext, a single pointer
+    pub const SIZE: u64 = 8;
+
+    /// The non-GC static base of the type, which directly follows the context
+    pub fn non_gc_statics(&self) -> Va {
+        self.context + Self::SIZE
+    }
+}
+
+impl<'a> NativeAotBinary<'a> {
+    /// Parses the CCtorContextMap, returning nothing if the image doesn't have one
+    pub fn cctor_contexts(&self) -> Result<Vec<CctorContext>> {
+        let Some(table) = self.rtr.blob_hashtable(ReflectionMapBlob::CCtorContextMap) else {
+            return Ok(Vec::new());
+        };
+
+        let Some(fixups) = self.rtr.common_fixups_table() else {
+            return Err(AotError::MissingTable("a common fixups table"));
+        };
+
+        let mut contexts = Vec::new();
+
+        for mut parser in table.enumerate_all()? {
+            // MethodTable + StaticClassConstructionContext
+            let declaring_type = fixups.get_va_from_index(parser.get_unsigned()?);
+            let context = fixups.get_va_from_index(parser.get_unsigned()?);
+
+            let (Some(declaring_type), Some(context)) = (declaring_type, context) else {
+                continue;
+            };
+
+            let cctor = BinaryReader::new(&mut View::new(self.pe, context), Endian::Little)
+                .read_u64()
+                .ok()
+                .filter(|&address| address != 0);
+
+            contexts.push(CctorContext {
+                declaring_type,
+                context,
+                cctor,
+            });
+        }
+
+        debug!("CCtorContextMap has {} contexts", contexts.len());
+
+        Ok(contexts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pelite::pe64::PeFile;
+
+    use crate::{
+        binary::{NativeAotBinary, cctor_context::CctorContext},
+        test_utils::{
+            IMAGE_BASE, PeBuilder, encode_hashtable, encode_rtr_header, encode_unsigned,
+            rtr_header_size,
+        },
+    };
+
+    #[test]
+    fn cctor_contexts_point_to_the_cctor_and_the_statics() {
+        const COMMON_FIXUPS_TABLE: u32 = 300 + 8;
+        const CCTOR_CONTEXT_MAP: u32 = 300 + 10;
+
+        let mut builder = PeBuilder::new();
+        let text_va = IMAGE_BASE + builder.section(".text", vec![0xC3; 0x10]) as u64;
+
+        // A context pointing to the cctor, and one whose cctor has no pointer stored
+        let data_va = IMAGE_BASE + builder.next_rva() as u64;
+        let mut data = Vec::new();
+        data.extend_from_slice(&text_va.to_le_bytes());
+        data.extend_from_slice(&[0; 8]);
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.extend_from_slice(&[0; 8]);
+        builder.section(".data", data);
+
+        let mt_va = data_va + 0x100;
+        let rdata_va = IMAGE_BASE + builder.next_rva() as u64;
+        let fixups_va = rdata_va + rtr_header_size(2) as u64;
+        let fixups = [mt_va, data_va, mt_va + 0x18, data_va + 0x10]
+            .iter()
+            .enumerate()
+            .flat_map(|(index, &target)| {
+                ((target as i64 - (fixups_va + 4 * index as u64) as i64) as i32).to_le_bytes()
+            })
+            .collect::<Vec<_>>();
+
+        let entries = [(0, 1), (2, 3)]
+            .into_iter()
+            .map(|(mt, context)| {
+                let mut entry = Vec::new();
+                encode_unsigned(&mut entry, mt);
+                encode_unsigned(&mut entry, context);
+                (mt, entry)
+            })
+            .collect::<Vec<_>>();
+        let map = encode_hashtable(&entries);
+        let map_va = fixups_va + fixups.len() as u64;
+
+        let mut rdata = encode_rtr_header(&[
+            (COMMON_FIXUPS_TABLE, fixups_va, map_va),
+            (CCTOR_CONTEXT_MAP, map_va, map_va + map.len() as u64),
+        ]);
+        rdata.extend_from_slice(&fixups);
+        rdata.extend_from_slice(&map);
+        builder.section(".rdata", rdata);
+
+        let image = builder.build();
+        let pe = PeFile::from_bytes(&image).unwrap();
+        let binary = NativeAotBinary::from_pe(pe, rdata_va).unwrap();
+
+        let mut contexts = binary.cctor_contexts().unwrap();
+        contexts.sort_by_key(|context| context.context);
+
+        assert_eq!(
+            contexts,
+            vec![
+                CctorContext {
+                    declaring_type: mt_va,
+                    context: data_va,
+                    cctor: Some(text_va),
+                },
+                CctorContext {
+                    declaring_type: mt_va + 0x18,
+                    context: data_va + 0x10,
+                    cctor: None,
+                },
+            ]
+        );
+        assert_eq!(contexts[0].non_gc_statics(), data_va + 8);
+    }
+}
@@ -0,0 +1,441 @@
+//! The DebugInfo section, mapping the native code of methods back to IL offsets and variable locations
+//!
+//! The section is a NativeArray indexed by the RuntimeFunctions entry of a method. Every element starts with a
+//! lookback, which is either zero and followed by the debug info, or how far back the identical debug info of another
+//! method starts. The debug info itself is nibble encoded, as written by crossgen2:
+//!
+//! ```text
+//! u32 bounds_size             // in bytes
+//! u32 vars_size
+//! u8  bounds[bounds_size]     // count + (native offset delta + IL offset + source type) for every bound
+//! u8  vars[vars_size]         // count + (start + length + variable number + location) for every variable
+//! ```
+//!
+//! ILC doesn't write the section, NativeAOT images keep their debug info in the PDB instead.
+
+use pelite::pe64::Rva;
+
+use crate::{
+    binary::{NativeAotBinary, headers::rtr::ReadyToRunSectionType},
+    error::{AotError, Result},
+    native_format::{array::NativeArray, parser::NativeParser, reader::NativeReader},
+};
+
+/// The debug info of a single method, see [`NativeAotBinary::debug_info`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugInfo {
+    /// Which IL offset the code starting at each native offset was compiled from, sorted by native offset
+    pub bounds: Vec<OffsetMapping>,
+    pub variables: Vec<NativeVarInfo>,
+}
+
+/// The start of a block of native code, and the IL it was compiled from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OffsetMapping {
+    /// Offset from the start of the method
+    pub native_offset: u32,
+    pub il_offset: IlOffset,
+    /// `SourceTypes` flags of the JIT, e.g. `STACK_EMPTY` or `CALL_INSTRUCTION`
+    pub source: u32,
+}
+
+/// The IL a block of native code maps to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IlOffset {
+    Offset(u32),
+    NoMapping,
+    Prolog,
+    Epilog,
+}
+
+impl IlOffset {
+    // `ICorDebugInfo::MAX_MAPPING_VALUE`, which the IL offsets of the bounds are stored relative to
+    const MAX_MAPPING_VALUE: u32 = -3i32 as u32;
+    // `ICorDebugInfo::MAX_ILNUM`, which variable numbers are stored relative to
+    const MAX_ILNUM: u32 = -4i32 as u32;
+
+    fn from_encoded(value: u32) -> Self {
+        match value.wrapping_add(Self::MAX_MAPPING_VALUE) as i32 {
+            -1 => Self::NoMapping,
+            -2 => Self::Prolog,
+            -3 => Self::Epilog,
+            _ => Self::Offset(value.wrapping_add(Self::MAX_MAPPING_VALUE)),
+        }
+    }
+}
+
+/// Where a variable lives over a range of the native code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NativeVarInfo {
+    /// Native offset the location starts at
+    pub start: u32,
+    /// Native offset just past the end of the location
+    pub end: u32,
+    /// The IL argument or local number, or one of the special `ICorDebugInfo` numbers like `VARARGS_HND_ILNUM`
+    pub var_number: i32,
+    pub location: VarLocation,
+}
+
+/// `ICorDebugInfo::VarLoc`, with registers given by their number in the JIT's register enumeration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VarLocation {
+    Register(u32),
+    RegisterByRef(u32),
+    FloatRegister(u32),
+    Stack {
+        base: u32,
+        offset: i32,
+    },
+    StackByRef {
+        base: u32,
+        offset: i32,
+    },
+    RegisterPair(u32, u32),
+    RegisterStack {
+        register: u32,
+        base: u32,
+        offset: i32,
+    },
+    StackRegister {
+        base: u32,
+        offset: i32,
+        register: u32,
+    },
+    /// An 8 byte value across two stack slots
+    Stack2 {
+        base: u32,
+        offset: i32,
+    },
+    FloatStack(u32),
+    /// A fixed offset into the variable argument list
+    FixedVarArg(u32),
+}
+
+impl<'a> NativeAotBinary<'a> {
+    /// Decodes the debug info of the method whose code starts at `rva`
+    ///
+    /// Returns `None` for methods without debug info, and for images without a DebugInfo or RuntimeFunctions section.
+    pub fn debug_info(&self, rva: Rva) -> Option<DebugInfo> {
+        let section = self.rtr.section(ReadyToRunSectionType::DebugInfo)?;
+        let functions = self.runtime_functions()?;
+        let index = functions
+            .binary_search_by_key(&rva, |function| function.BeginAddress)
+            .ok()?;
+
+        let reader = NativeReader::new(section.start.bytes().ok()?).ok()?;
+        let array = NativeArray::new(NativeParser::new(reader, 0)).ok()?;
+        let parser = array.get(index as u32).ok()??;
+
+        // The lookback is relative to its own position
+        let mut offset = parser.offset();
+        let lookback = reader.decode_unsigned(&mut offset).ok()?;
+        if lookback != 0 {
+            offset = parser.offset().checked_sub(lookback as usize)?;
+        }
+
+        let bytes = section.start.bytes().ok()?.get(offset..)?;
+
+        DebugInfo::parse(bytes).ok()
+    }
+}
+
+impl DebugInfo {
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        let mut header = NibbleReader::new(bytes);
+        let bounds_size = header.read_u32()? as usize;
+        let vars_size = header.read_u32()? as usize;
+
+        let bounds_start = header.next_byte_index();
+        let vars_start = bounds_start + bounds_size;
+        let (Some(bounds), Some(vars)) = (
+            bytes.get(bounds_start..vars_start),
+            bytes.get(vars_start..vars_start + vars_size),
+        ) else {
+            return Err(AotError::BadImage);
+        };
+
+        Ok(Self {
+            bounds: Self::parse_bounds(bounds)?,
+            variables: Self::parse_vars(vars)?,
+        })
+    }
+
+    fn parse_bounds(bytes: &[u8]) -> Result<Vec<OffsetMapping>> {
+        if bytes.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut reader = NibbleReader::new(bytes);
+        let count = reader.read_u32()?;
+        let mut bounds = Vec::new();
+        let mut native_offset = 0u32;
+
+        for _ in 0..count {
+            // Native offsets are stored as the delta to the previous bound
+            native_offset = native_offset.wrapping_add(reader.read_u32()?);
+
+            bounds.push(OffsetMapping {
+                native_offset,
+                il_offset: IlOffset::from_encoded(reader.read_u32()?),
+                source: reader.read_u32()?,
+            });
+        }
+
+        Ok(bounds)
+    }
+
+    fn parse_vars(bytes: &[u8]) -> Result<Vec<NativeVarInfo>> {
+        if bytes.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut reader = NibbleReader::new(bytes);
+        let count = reader.read_u32()?;
+        let mut variables = Vec::new();
+
+        for _ in 0..count {
+            let start = reader.read_u32()?;
+            let end = start.wrapping_add(reader.read_u32()?);
+            let var_number = reader.read_u32()?.wrapping_add(IlOffset::MAX_ILNUM) as i32;
+
+            variables.push(NativeVarInfo {
+                start,
+                end,
+                var_number,
+                location: reader.read_var_location()?,
+            });
+        }
+
+        Ok(variables)
+    }
+}
+
+/// Reads the nibble encoding of CoreCLR's `NibbleReader`, where every nibble holds 3 bits of a value and whether more
+/// nibbles follow. The low nibble of a byte is read first.
+struct NibbleReader<'a> {
+    bytes: &'a [u8],
+    nibble: usize,
+}
+
+impl<'a> NibbleReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, nibble: 0 }
+    }
+
+    fn next_byte_index(&self) -> usize {
+        self.nibble.div_ceil(2)
+    }
+
+    fn read_nibble(&mut self) -> Result<u8> {
+        let Some(&byte) = self.bytes.get(self.nibble / 2) else {
+            return Err(AotError::BadImage);
+        };
+
+        let nibble = if self.nibble.is_multiple_of(2) {
+            byte & 0xF
+        } else {
+            byte >> 4
+        };
+        self.nibble += 1;
+
+        Ok(nibble)
+    }
+
+    fn read_u32(&mut self) -> Result<u32> {
+        let mut value = 0u32;
+
+        loop {
+            let nibble = self.read_nibble()?;
+            value = (value << 3) | (nibble & 0x7) as u32;
+
+            if nibble & 0x8 == 0 {
+                return Ok(value);
+            }
+        }
+    }
+
+    /// Signed values keep their sign in the lowest bit
+    fn read_i32(&mut self) -> Result<i32> {
+        let value = self.read_u32()?;
+        let magnitude = (value >> 1) as i32;
+
+        Ok(if value & 1 != 0 {
+            -magnitude
+        } else {
+            magnitude
+        })
+    }
+
+    fn read_var_location(&mut self) -> Result<VarLocation> {
+        Ok(match self.read_u32()? {
+            0 => VarLocation::Register(self.read_u32()?),
+            1 => VarLocation::RegisterByRef(self.read_u32()?),
+            2 => VarLocation::FloatRegister(self.read_u32()?),
+            3 => VarLocation::Stack {
+                base: self.read_u32()?,
+                offset: self.read_i32()?,
+            },
+            4 => VarLocation::StackByRef {
+                base: self.read_u32()?,
+                offset: self.read_i32()?,
+            },
+            5 => VarLocation::RegisterPair(self.read_u32()?, self.read_u32()?),
+            6 => VarLocation::RegisterStack {
+                register: self.read_u32()?,
+                base: self.read_u32()?,
+                offset: self.read_i32()?,
+            },
+            7 => VarLocation::StackRegister {
+                base: self.read_u32()?,
+                offset: self.read_i32()?,
+                register: self.read_u32()?,
+            },
+            8 => VarLocation::Stack2 {
+                base: self.read_u32()?,
+                offset: self.read_i32()?,
+            },
+            9 => VarLocation::FloatStack(self.read_u32()?),
+            10 => VarLocation::FixedVarArg(self.read_u32()?),
+            _ => return Err(AotError::BadImage),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pelite::pe64::PeFile;
+
+    use crate::{
+        binary::{
+            NativeAotBinary,
+            debug_info::{IlOffset, NativeVarInfo, OffsetMapping, VarLocation},
+        },
+        test_utils::{IMAGE_BASE, PeBuilder, encode_rtr_header, rtr_header_size},
+    };
+
+    /// The inverse of `NibbleReader`
+    fn encode_nibbles(values: &[u32]) -> Vec<u8> {
+        let mut nibbles = Vec::new();
+
+        for &value in values {
+            let mut groups = vec![value & 0x7];
+            let mut rest = value >> 3;
+            while rest != 0 {
+                groups.push(rest & 0x7);
+                rest >>= 3;
+            }
+
+            // Most significant group first, every group but the last one marked as continued
+            for (index, group) in groups.iter().rev().enumerate() {
+                let more = if index + 1 < groups.len() { 0x8 } else { 0 };
+                nibbles.push(*group as u8 | more);
+            }
+        }
+
+        nibbles
+            .chunks(2)
+            .map(|pair| pair[0] | pair.get(1).map_or(0, |high| high << 4))
+            .collect()
+    }
+
+    #[test]
+    fn il_offsets_are_relative_to_the_max_mapping_value() {
+        assert_eq!(IlOffset::from_encoded(0), IlOffset::Epilog);
+        assert_eq!(IlOffset::from_encoded(1), IlOffset::Prolog);
+        assert_eq!(IlOffset::from_encoded(2), IlOffset::NoMapping);
+        assert_eq!(IlOffset::from_encoded(3), IlOffset::Offset(0));
+        assert_eq!(IlOffset::from_encoded(0x2A + 3), IlOffset::Offset(0x2A));
+    }
+
+    #[test]
+    fn debug_info_maps_native_offsets_to_il() {
+        const RUNTIME_FUNCTIONS: u32 = 102;
+        const DEBUG_INFO: u32 = 105;
+
+        let mut builder = PeBuilder::new();
+        let text_rva = builder.section(".text", vec![0xC3; 0x40]);
+
+        // Prolog at 0, IL 0 at 0x4, IL 0x10 at 0x8, epilog at 0x30
+        let bounds = encode_nibbles(&[4, 0, 1, 0, 4, 3, 0, 4, 0x10 + 3, 1, 0x28, 0, 0]);
+        // Argument 0 in register 1, then local 1 on the stack at [rsp-0x10]
+        let vars = encode_nibbles(&[2, 0, 0x30, 4, 0, 1, 4, 0x10, 5, 3, 4, (0x10 << 1) | 1]);
+        let mut debug_info = encode_nibbles(&[bounds.len() as u32, vars.len() as u32]);
+        debug_info.extend_from_slice(&bounds);
+        debug_info.extend_from_slice(&vars);
+
+        // A NativeArray of a single block holding element 0, which starts with a lookback of 0
+        let mut section = vec![1 << 3, 1, 0, 0];
+        section.extend_from_slice(&debug_info);
+
+        let rdata_va = IMAGE_BASE + builder.next_rva() as u64;
+        let functions_va = rdata_va + rtr_header_size(2) as u64;
+        let debug_info_va = functions_va + 12;
+
+        let mut rdata = encode_rtr_header(&[
+            (RUNTIME_FUNCTIONS, functions_va, debug_info_va),
+            (
+                DEBUG_INFO,
+                debug_info_va,
+                debug_info_va + section.len() as u64,
+            ),
+        ]);
+        for value in [text_rva, text_rva + 0x40, 0] {
+            rdata.extend_from_slice(&value.to_le_bytes());
+        }
+        rdata.extend_from_slice(&section);
+        builder.section(".rdata", rdata);
+
+        let image = builder.build();
+        let pe = PeFile::from_bytes(&image).unwrap();
+        let binary = NativeAotBinary::from_pe(pe, rdata_va).unwrap();
+
+        assert_eq!(binary.debug_info(text_rva + 1), None);
+
+        let info = binary.debug_info(text_rva).unwrap();
+        assert_eq!(
+            info.bounds,
+            vec![
+                OffsetMapping {
+                    native_offset: 0,
+                    il_offset: IlOffset::Prolog,
+                    source: 0,
+                },
+                OffsetMapping {
+                    native_offset: 4,
+                    il_offset: IlOffset::Offset(0),
+                    source: 0,
+                },
+                OffsetMapping {
+                    native_offset: 8,
+                    il_offset: IlOffset::Offset(0x10),
+                    source: 1,
+                },
+                OffsetMapping {
+                    native_offset: 0x30,
+                    il_offset: IlOffset::Epilog,
+                    source: 0,
+                },
+            ]
+        );
+        assert_eq!(
+            info.variables,
+            vec![
+                NativeVarInfo {
+                    start: 0,
+                    end: 0x30,
+                    var_number: 0,
+                    location: VarLocation::Register(1),
+                },
+                NativeVarInfo {
+                    start: 4,
+                    end: 0x14,
+                    var_number: 1,
+                    location: VarLocation::Stack {
+                        base: 4,
+                        offset: -0x10,
+                    },
+                },
+            ]
+        );
+    }
+}
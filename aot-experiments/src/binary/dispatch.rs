@@ -0,0 +1,290 @@
+//! Interface dispatch, resolving the implementation of an interface method on a concrete type
+//!
+//! Types that implement interface methods have a dispatch map, referenced by a relative pointer among the optional
+//! fields following the interface map of their MethodTable (see [`MethodTable::dispatch_map`]). It is laid out as:
+//!
+//! ```text
+//! u16 standard_entry_count         // Implementations on the type itself
+//! u16 default_entry_count          // Default interface implementations
+//! u16 standard_static_entry_count  // Implementations of static virtual methods
+//! u16 default_static_entry_count
+//! (u16 interface_index, u16 interface_slot, u16 impl_slot)[standard + default]
+//! (u16 interface_index, u16 interface_slot, u16 impl_slot, u16 context)[standard static + default static]
+//! ```
+//!
+//! `interface_index` indexes the interface map of the type, `interface_slot` is the vtable slot of the method on the
+//! interface, and `impl_slot` is the vtable slot of the implementation on the type. Slots past the end of the vtable
+//! refer to the sealed vtable instead, which holds the implementations that can't be overridden.
+//!
+//! A type only lists the interface methods it implements itself, the rest is resolved by its base types. Default
+//! entries point at the default implementation of an interface method, and are only used when no type in the
+//! hierarchy implements the method itself.
+
+use binary_rw::{BinaryReader, Endian};
+use pelite::pe64::Va;
+
+use crate::{
+    binary::{NativeAotBinary, headers::mt::MethodTable},
+    error::Result,
+    native_format::View,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DispatchMapEntry {
+    pub interface_index: u16,
+    pub interface_slot: u16,
+    pub impl_slot: u16,
+    /// Whether this is a default interface implementation rather than one of the type itself
+    pub is_default: bool,
+}
+
+/// Parses the instance entries of a dispatch map, standard implementations first
+pub fn parse_dispatch_map(mut view: View<'_>) -> Result<Vec<DispatchMapEntry>> {
+    let mut reader = BinaryReader::new(&mut view, Endian::Little);
+
+    let standard_count = reader.read_u16()?;
+    let default_count = reader.read_u16()?;
+    let _standard_static_count = reader.read_u16()?;
+    let _default_static_count = reader.read_u16()?;
+
+    // Widened, as both counts are u16s of their own
+    let count = standard_count as usize + default_count as usize;
+    let mut entries = Vec::with_capacity(count);
+
+    for index in 0..count {
+        entries.push(DispatchMapEntry {
+            interface_index: reader.read_u16()?,
+            interface_slot: reader.read_u16()?,
+            impl_slot: reader.read_u16()?,
+            is_default: index >= standard_count as usize,
+        });
+    }
+
+    Ok(entries)
+}
+
+impl<'a> NativeAotBinary<'a> {
+    // Guards against cycles in corrupt base type chains
+    const MAX_HIERARCHY_DEPTH: usize = 256;
+
+    /// Finds the code that is called for `slot` of the interface `iface_mt` on an instance of `type_mt`
+    ///
+    /// Falls back to the default implementation of the interface when no type in the hierarchy implements it.
+    pub fn interface_dispatch(&self, type_mt: Va, iface_mt: Va, slot: u16) -> Option<Va> {
+        self.find_dispatch(type_mt, iface_mt, slot, false)
+            .or_else(|| self.find_dispatch(type_mt, iface_mt, slot, true))
+    }
+
+    fn find_dispatch(&self, type_mt: Va, iface_mt: Va, slot: u16, default: bool) -> Option<Va> {
+        let mut current = type_mt;
+
+        for _ in 0..Self::MAX_HIERARCHY_DEPTH {
+            if current == 0 {
+                return None;
+            }
+
+            let mt = MethodTable::parse(&mut View::new(self.pe, current)).ok()?;
+
+            if let Some(index) = mt.iface_addresses.iter().position(|&va| va == iface_mt)
+                && let Some(map) = mt.dispatch_map()
+                && let Ok(entries) = parse_dispatch_map(View::new(self.pe, map))
+                && let Some(entry) = entries.iter().find(|entry| {
+                    entry.interface_index as usize == index
+                        && entry.interface_slot == slot
+                        && entry.is_default == default
+                })
+            {
+                let vtable_count = mt.vtable_addresses.len() as u16;
+
+                return match entry.impl_slot.checked_sub(vtable_count) {
+                    Some(sealed_slot) => mt.sealed_virtual_slot(sealed_slot),
+                    None => mt.vtable_addresses.get(entry.impl_slot as usize).copied(),
+                };
+            }
+
+            current = mt.related_type_address;
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pelite::pe64::PeFile;
+
+    use crate::{
+        binary::{
+            NativeAotBinary,
+            dispatch::{DispatchMapEntry, parse_dispatch_map},
+            headers::mt::MethodTable,
+        },
+        native_format::View,
+        test_utils::{
+            IMAGE_BASE, PeBuilder, encode_method_table, encode_relative_pointer, encode_rtr_header,
+        },
+    };
+
+    const CLASS: u32 = 0x14 << 26;
+
+    fn encode_dispatch_map(standard: &[(u16, u16, u16)], default: &[(u16, u16, u16)]) -> Vec<u8> {
+        let mut map = Vec::new();
+        for count in [standard.len(), default.len(), 0, 0] {
+            map.extend_from_slice(&(count as u16).to_le_bytes());
+        }
+
+        for &(interface_index, interface_slot, impl_slot) in standard.iter().chain(default) {
+            for value in [interface_index, interface_slot, impl_slot] {
+                map.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+
+        map
+    }
+
+    /// `Derived : Base`, both implementing a single interface. Base implements slot 0 in its vtable and slot 3 in its
+    /// sealed vtable, and carries the default implementation of slot 1. Derived only implements slot 2.
+    struct Fixture {
+        image: Vec<u8>,
+        rtr_va: u64,
+        functions: [u64; 6],
+        iface: u64,
+        derived: u64,
+    }
+
+    fn fixture() -> Fixture {
+        let mut builder = PeBuilder::new();
+        let text_va = IMAGE_BASE + builder.section(".text", vec![0xC3; 0x60]) as u64;
+        let functions: [u64; 6] = std::array::from_fn(|index| text_va + 0x10 * index as u64);
+
+        let data_va = IMAGE_BASE + builder.next_rva() as u64;
+        let (base, derived) = (data_va, data_va + 0x40);
+        let (base_map, derived_map) = (data_va + 0x80, data_va + 0xA0);
+        let sealed_vtable = data_va + 0xC0;
+        let iface = data_va + 0x100;
+
+        let mut data = vec![0; 0x100];
+        let mut write = |offset: u64, bytes: &[u8]| {
+            let offset = (offset - data_va) as usize;
+            data[offset..offset + bytes.len()].copy_from_slice(bytes);
+        };
+
+        let flags = CLASS | MethodTable::HAS_DISPATCH_MAP | MethodTable::HAS_SEALED_VTABLE_ENTRIES;
+        let table = encode_method_table(flags, 0x18, 0, &functions[..2], &[iface], 1);
+        write(base, &table);
+
+        // TypeManager + WritableData + DispatchMap + SealedVirtualSlots
+        let optional = base + table.len() as u64;
+        write(
+            optional + 8,
+            &encode_relative_pointer(optional + 8, base_map),
+        );
+        write(
+            optional + 12,
+            &encode_relative_pointer(optional + 12, sealed_vtable),
+        );
+
+        let flags = CLASS | MethodTable::HAS_DISPATCH_MAP;
+        let table = encode_method_table(flags, 0x18, base, &functions[2..4], &[iface], 2);
+        write(derived, &table);
+
+        let optional = derived + table.len() as u64;
+        write(
+            optional + 8,
+            &encode_relative_pointer(optional + 8, derived_map),
+        );
+
+        // Slots past the two vtable slots are in the sealed vtable
+        write(
+            base_map,
+            &encode_dispatch_map(&[(0, 0, 1), (0, 3, 3)], &[(0, 1, 2)]),
+        );
+        write(derived_map, &encode_dispatch_map(&[(0, 2, 0)], &[]));
+
+        for (index, target) in [functions[4], functions[5]].into_iter().enumerate() {
+            let slot = sealed_vtable + 4 * index as u64;
+            write(slot, &encode_relative_pointer(slot, target));
+        }
+
+        builder.section(".data", data);
+
+        let rtr_va = IMAGE_BASE + builder.next_rva() as u64;
+        builder.section(".rdata", encode_rtr_header(&[]));
+
+        Fixture {
+            image: builder.build(),
+            rtr_va,
+            functions,
+            iface,
+            derived,
+        }
+    }
+
+    #[test]
+    fn interface_dispatch_walks_the_hierarchy() {
+        let fixture = fixture();
+        let pe = PeFile::from_bytes(&fixture.image).unwrap();
+        let binary = NativeAotBinary::from_pe(pe, fixture.rtr_va).unwrap();
+        let dispatch = |slot| binary.interface_dispatch(fixture.derived, fixture.iface, slot);
+
+        let [_, base_1, derived_0, _, sealed_0, sealed_1] = fixture.functions;
+
+        assert_eq!(
+            dispatch(2),
+            Some(derived_0),
+            "implemented by the type itself"
+        );
+        assert_eq!(dispatch(0), Some(base_1), "inherited from the base type");
+        assert_eq!(
+            dispatch(3),
+            Some(sealed_1),
+            "implemented in the sealed vtable"
+        );
+        assert_eq!(dispatch(1), Some(sealed_0), "default implementation");
+        assert_eq!(dispatch(4), None);
+
+        // An interface the type doesn't implement
+        assert_eq!(
+            binary.interface_dispatch(fixture.derived, fixture.iface + 8, 0),
+            None
+        );
+    }
+
+    #[test]
+    fn dispatch_map_counts_do_not_overflow() {
+        let mut map = encode_dispatch_map(&[], &[(0, 0, 0)]);
+        map[..2].copy_from_slice(&u16::MAX.to_le_bytes());
+
+        let mut builder = PeBuilder::new();
+        let va = IMAGE_BASE + builder.section(".data", map) as u64;
+        let image = builder.build();
+        let pe = PeFile::from_bytes(&image).unwrap();
+
+        // The map claims far more entries than it holds
+        assert!(parse_dispatch_map(View::new(pe, va)).is_err());
+
+        let map = encode_dispatch_map(&[(0, 1, 2)], &[(0, 3, 4)]);
+        let mut builder = PeBuilder::new();
+        let va = IMAGE_BASE + builder.section(".data", map) as u64;
+        let image = builder.build();
+        let pe = PeFile::from_bytes(&image).unwrap();
+
+        assert_eq!(
+            parse_dispatch_map(View::new(pe, va)).unwrap(),
+            [
+                DispatchMapEntry {
+                    interface_index: 0,
+                    interface_slot: 1,
+                    impl_slot: 2,
+                    is_default: false,
+                },
+                DispatchMapEntry {
+                    interface_index: 0,
+                    interface_slot: 3,
+                    impl_slot: 4,
+                    is_default: true,
+                },
+            ]
+        );
+    }
+}
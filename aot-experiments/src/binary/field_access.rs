@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+
+use log::debug;
+use pelite::pe64::Va;
+
+use crate::{
+    binary::{
+        NativeAotBinary,
+        headers::rtr::{ReadyToRunSectionType, ReflectionMapBlob},
+    },
+    embedded_meta::handles::{BaseHandle, FieldHandle, HandleType},
+    error::{AotError, Result},
+};
+
+#[repr(transparent)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct FieldTableFlags(u32);
+
+impl FieldTableFlags {
+    pub const INSTANCE: u32 = 0x00;
+    pub const STATIC: u32 = 0x01;
+    pub const THREAD_STATIC: u32 = 0x02;
+    pub const STORAGE_CLASS_MASK: u32 = 0x03;
+
+    pub const HAS_METADATA_HANDLE: u32 = 0x08;
+    pub const IS_GC_SECTION: u32 = 0x10;
+    pub const FIELD_OFFSET_ENCODED_DIRECTLY: u32 = 0x20;
+    pub const IS_ANY_CANONICAL_ENTRY: u32 = 0x40;
+    pub const IS_INIT_ONLY: u32 = 0x80;
+
+    pub const fn new(raw: u32) -> Self {
+        Self(raw)
+    }
+
+    pub const fn raw(self) -> u32 {
+        self.0
+    }
+
+    pub fn storage_class(self) -> u32 {
+        self.0 & Self::STORAGE_CLASS_MASK
+    }
+
+    pub fn has_metadata_handle(self) -> bool {
+        self.0 & Self::HAS_METADATA_HANDLE != 0
+    }
+
+    pub fn is_gc_section(self) -> bool {
+        self.0 & Self::IS_GC_SECTION != 0
+    }
+
+    pub fn is_offset_encoded_directly(self) -> bool {
+        self.0 & Self::FIELD_OFFSET_ENCODED_DIRECTLY != 0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldStorage {
+    /// Offset from the start of the object (after the MethodTable pointer)
+    Instance { offset: u32 },
+
+    /// Address of the static field
+    Static { address: Va },
+
+    /// Offset from the statics base of a generic instantiation, which has no single address
+    StaticOffset { offset: u32 },
+
+    /// Offset into the thread static block of the declaring type, which every thread has its own copy of
+    ThreadStatic {
+        offset: u32,
+
+        /// Offset from the start of the module's TLS block, for images listing one in the ThreadStaticOffsetRegion
+        tls_offset: Option<u64>,
+    },
+}
+
+/// Where a static field lives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StaticKind {
+    /// In a static base that's reported to the GC, for reference types and structs containing them
+    Gc,
+    /// In a static base that the GC doesn't scan, for primitives and other unmanaged values
+    NonGc,
+    /// In per-thread storage, for `[ThreadStatic]` fields
+    ThreadStatic,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FieldAccessEntry {
+    pub flags: FieldTableFlags,
+    pub declaring_type: Va,
+    pub field: FieldHandle,
+    pub storage: FieldStorage,
+}
+
+impl FieldAccessEntry {
+    /// The kind of static storage of the field, or `None` for instance fields
+    pub fn static_kind(&self) -> Option<StaticKind> {
+        match self.flags.storage_class() {
+            FieldTableFlags::INSTANCE => None,
+            FieldTableFlags::THREAD_STATIC => Some(StaticKind::ThreadStatic),
+            _ if self.flags.is_gc_section() => Some(StaticKind::Gc),
+            _ => Some(StaticKind::NonGc),
+        }
+    }
+}
+
+impl<'a> NativeAotBinary<'a> {
+    /// Parses the FieldAccessMap, skipping entries that are not keyed by a metadata handle
+    pub fn field_access_entries(&self) -> Result<Vec<FieldAccessEntry>> {
+        let Some(table) = self.rtr.blob_hashtable(ReflectionMapBlob::FieldAccessMap) else {
+            return Err(AotError::MissingTable("a FieldAccessMap"));
+        };
+
+        let Some(fixups) = self.rtr.common_fixups_table() else {
+            return Err(AotError::MissingTable("a common fixups table"));
+        };
+
+        let tls_base = self.thread_static_tls_offset();
+        let mut entries = Vec::new();
+
+        for mut parser in table.enumerate_all()? {
+            // Flags + DeclaringType + MdHandle or Name + StaticBase and/or Offset
+            let flags = FieldTableFlags::new(parser.get_unsigned()?);
+            let Some(declaring_type) = fixups.get_va_from_index(parser.get_unsigned()?) else {
+                continue;
+            };
+
+            if !flags.has_metadata_handle() {
+                continue;
+            }
+
+            let field = BaseHandle::from_offset(HandleType::Field, parser.get_unsigned()?)
+                .to_handle::<FieldHandle>()?;
+
+            let storage = if flags.storage_class() == FieldTableFlags::INSTANCE {
+                FieldStorage::Instance {
+                    offset: parser.get_unsigned()?,
+                }
+            } else if flags.storage_class() == FieldTableFlags::THREAD_STATIC {
+                // Thread statics reference the index of their type's thread static block, which has no address
+                if !flags.is_offset_encoded_directly() {
+                    parser.skip_integer()?;
+                }
+
+                let offset = parser.get_unsigned()?;
+
+                FieldStorage::ThreadStatic {
+                    offset,
+                    tls_offset: tls_base.map(|base| base + offset as u64),
+                }
+            } else if flags.is_offset_encoded_directly() {
+                FieldStorage::StaticOffset {
+                    offset: parser.get_unsigned()?,
+                }
+            } else {
+                // Non-generic statics reference their statics base through the fixups table
+                let Some(base) = fixups.get_va_from_index(parser.get_unsigned()?) else {
+                    continue;
+                };
+
+                FieldStorage::Static {
+                    address: base + parser.get_unsigned()? as Va,
+                }
+            };
+
+            entries.push(FieldAccessEntry {
+                flags,
+                declaring_type,
+                field,
+                storage,
+            });
+        }
+
+        debug!("FieldAccessMap has {} entries", entries.len());
+
+        Ok(entries)
+    }
+
+    /// The offset of the inlined thread statics from the start of the module's TLS block
+    fn thread_static_tls_offset(&self) -> Option<u64> {
+        let section = self
+            .rtr
+            .section(ReadyToRunSectionType::ThreadStaticOffsetRegion)?;
+        let bytes = section.start.bytes().ok()?;
+
+        Some(u32::from_le_bytes(bytes.get(..4)?.try_into().ok()?) as u64)
+    }
+
+    /// Where the field is stored, looked up in the FieldAccessMap which is indexed on first use
+    pub fn field_storage(&self, field: FieldHandle) -> Result<Option<FieldStorage>> {
+        if let Some(storages) = self.field_storages.get() {
+            return Ok(storages.get(&field).copied());
+        }
+
+        // Fields listed more than once keep their first entry
+        let mut storages = HashMap::new();
+        for entry in self.field_access_entries()? {
+            storages.entry(entry.field).or_insert(entry.storage);
+        }
+
+        Ok(self
+            .field_storages
+            .get_or_init(|| storages)
+            .get(&field)
+            .copied())
+    }
+}
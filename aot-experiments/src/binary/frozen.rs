@@ -0,0 +1,188 @@
+//! The frozen object region, holding managed objects the compiler preallocated in the image
+//!
+//! These are mostly string literals and boxed constants. Like objects on the GC heap, every object starts with an
+//! object header, followed by its MethodTable pointer and its fields:
+//!
+//! ```text
+//! u64 object_header   // Always zero in the image
+//! u64 method_table
+//! u32 length          // Arrays and strings only
+//! ...                 // Fields or elements
+//! ```
+//!
+//! The base size of the MethodTable includes the object header, arrays and strings add `length` times their
+//! component size. Objects are aligned to 8 bytes.
+
+use binary_rw::{BinaryReader, Endian};
+use pelite::pe64::Va;
+
+use crate::{
+    binary::{
+        NativeAotBinary,
+        headers::{
+            mt::{ElementType, MethodTable},
+            rtr::ReadyToRunSectionType,
+        },
+    },
+    error::{AotError, Result},
+    native_format::View,
+};
+
+/// An object in the frozen object region, see [`NativeAotBinary::frozen_objects`]
+#[derive(Debug, Clone)]
+pub struct FrozenObject<'a> {
+    /// Address of the object, which points at its MethodTable pointer like a managed reference does
+    pub va: Va,
+    pub method_table: MethodTable<'a>,
+    /// Element count of arrays, or the character count of strings
+    pub length: Option<u32>,
+}
+
+impl<'a> NativeAotBinary<'a> {
+    // The object header plus the MethodTable pointer
+    const MIN_OBJECT_SIZE: u64 = 16;
+
+    /// Walks the objects in the FrozenObjectRegion, returning nothing if the image doesn't have one
+    ///
+    /// Stops at the first object with an unreadable MethodTable, as the size of the objects following it can't be
+    /// known.
+    pub fn frozen_objects(&self) -> Result<Vec<FrozenObject<'a>>> {
+        let Some(section) = self.rtr.section(ReadyToRunSectionType::FrozenObjectRegion) else {
+            return Ok(Vec::new());
+        };
+
+        let (start, end) = (section.start.va(), section.end.va());
+        let mut objects = Vec::new();
+        let mut position = start;
+
+        while position + Self::MIN_OBJECT_SIZE <= end {
+            let va = position + 8;
+            let mut view = View::new(self.pe, va);
+            let mut reader = BinaryReader::new(&mut view, Endian::Little);
+
+            let mt_va = reader.read_u64()?;
+            if mt_va == 0 {
+                // Padding between objects
+                position += 8;
+                continue;
+            }
+
+            let Ok(method_table) = MethodTable::parse(&mut View::new(self.pe, mt_va)) else {
+                break;
+            };
+
+            let length = match method_table.component_size() {
+                Some(_) => Some(reader.read_u32()?),
+                None => None,
+            };
+
+            let size = method_table.base_size as u64
+                + method_table.component_size().unwrap_or(0) as u64 * length.unwrap_or(0) as u64;
+            if size < Self::MIN_OBJECT_SIZE {
+                return Err(AotError::BadImage);
+            }
+
+            objects.push(FrozenObject {
+                va,
+                method_table,
+                length,
+            });
+
+            position += size.next_multiple_of(8);
+        }
+
+        Ok(objects)
+    }
+
+    /// The text of every frozen `System.String`, by the address of the string object
+    pub fn frozen_strings(&self) -> Result<Vec<(Va, String)>> {
+        let mut strings = Vec::new();
+
+        for object in self.frozen_objects()? {
+            let (Some(length), true) = (object.length, is_string(&object.method_table)) else {
+                continue;
+            };
+
+            // The characters follow the MethodTable pointer and the length
+            let mut view = View::new(self.pe, object.va + 12);
+            let mut reader = BinaryReader::new(&mut view, Endian::Little);
+
+            let mut chars = Vec::with_capacity(length as usize);
+            for _ in 0..length {
+                chars.push(reader.read_u16()?);
+            }
+
+            strings.push((object.va, String::from_utf16_lossy(&chars)));
+        }
+
+        Ok(strings)
+    }
+}
+
+/// Whether a MethodTable has the shape of `System.String`, the only class with 2 byte components
+///
+/// Its base size covers the object header, the MethodTable pointer, the length and the null terminator.
+fn is_string(mt: &MethodTable<'_>) -> bool {
+    mt.component_size() == Some(2) && mt.element_type == ElementType::Class && mt.base_size == 0x16
+}
+
+#[cfg(test)]
+mod tests {
+    use pelite::pe64::PeFile;
+
+    use crate::{
+        binary::NativeAotBinary,
+        test_utils::{IMAGE_BASE, PeBuilder, encode_rtr_header, rtr_header_size},
+    };
+
+    #[test]
+    fn frozen_strings_reads_string_objects() {
+        const FROZEN_OBJECT_REGION: u32 = 206;
+
+        let mut builder = PeBuilder::new();
+        let rdata_va = IMAGE_BASE + builder.next_rva() as u64;
+        let mt_va = rdata_va + rtr_header_size(1) as u64;
+        let region_va = mt_va + 24;
+
+        // System.String: a class with 2 byte components and no vtable or interfaces
+        let mut mt = Vec::new();
+        mt.extend_from_slice(&(0x80000000u32 | (0x14 << 26) | 2).to_le_bytes());
+        mt.extend_from_slice(&0x16u32.to_le_bytes());
+        mt.extend_from_slice(&0u64.to_le_bytes());
+        mt.extend_from_slice(&[0; 8]);
+
+        // Two strings, the first one padded to the next object
+        let mut region = Vec::new();
+        for text in ["hi", "Hytale"] {
+            let chars = text.encode_utf16().collect::<Vec<_>>();
+
+            region.extend_from_slice(&0u64.to_le_bytes());
+            region.extend_from_slice(&mt_va.to_le_bytes());
+            region.extend_from_slice(&(chars.len() as u32).to_le_bytes());
+            region.extend(chars.iter().flat_map(|char| char.to_le_bytes()));
+            region.extend_from_slice(&[0, 0]);
+            region.resize(region.len().next_multiple_of(8), 0);
+        }
+
+        let mut rdata = encode_rtr_header(&[(
+            FROZEN_OBJECT_REGION,
+            region_va,
+            region_va + region.len() as u64,
+        )]);
+        rdata.extend_from_slice(&mt);
+        rdata.extend_from_slice(&region);
+        builder.section(".rdata", rdata);
+
+        let image = builder.build();
+        let pe = PeFile::from_bytes(&image).unwrap();
+        let binary = NativeAotBinary::from_pe(pe, rdata_va).unwrap();
+
+        assert_eq!(
+            binary.frozen_strings().unwrap(),
+            vec![
+                (region_va + 8, "hi".to_string()),
+                (region_va + 40, "Hytale".to_string())
+            ]
+        );
+    }
+}
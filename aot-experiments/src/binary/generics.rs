@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+use log::debug;
+use pelite::pe64::Va;
+
+use crate::{
+    binary::{
+        NativeAotBinary,
+        headers::{mt::MethodTable, rtr::ReflectionMapBlob},
+    },
+    embedded_meta::{handles::TypeDefinitionHandle, hashing::compute_generic_instance_hashcode},
+    error::{AotError, Result},
+    native_format::View,
+};
+
+/// A constructed generic type, e.g. the MethodTable of `List<int>`
+#[derive(Debug, Clone)]
+pub struct ConstructedGenericType {
+    pub method_table: Va,
+    pub definition: TypeDefinitionHandle,
+    /// MethodTables of the type arguments
+    pub generic_arguments: Vec<Va>,
+}
+
+impl<'a> NativeAotBinary<'a> {
+    /// Whether the MethodTable at `va` is `System.__Canon`, see [`MethodTable::is_canonical`]
+    pub fn is_canonical_type(&self, va: Va) -> bool {
+        MethodTable::parse(&mut View::new(self.pe, va)).is_ok_and(|mt| mt.is_canonical())
+    }
+
+    /// MethodTables of the constructed generic types listed in the GenericsHashtable
+    pub fn generic_type_instances(&self) -> Result<Vec<Va>> {
+        let Some(table) = self
+            .rtr
+            .blob_hashtable(ReflectionMapBlob::GenericsHashtable)
+        else {
+            return Err(AotError::MissingTable("a GenericsHashtable"));
+        };
+
+        let Some(references) = self.rtr.native_references_table() else {
+            return Err(AotError::MissingTable("a NativeReferences table"));
+        };
+
+        let mut instances = Vec::new();
+
+        for mut parser in table.enumerate_all()? {
+            // MethodTable
+            if let Some(mt_va) = references.get_va_from_index(parser.get_unsigned()?) {
+                instances.push(mt_va);
+            }
+        }
+
+        debug!("GenericsHashtable has {} types", instances.len());
+
+        Ok(instances)
+    }
+
+    /// Recovers the generic definition and type arguments of the types in the GenericsHashtable
+    ///
+    /// `type_map` should come from [`Self::type_map`], and is used to find the metadata of the definitions.
+    /// Types whose decoded composition doesn't hash to the hashcode stored in their MethodTable are left out.
+    pub fn constructed_generic_types(
+        &self,
+        type_map: &HashMap<Va, TypeDefinitionHandle>,
+    ) -> Result<Vec<ConstructedGenericType>> {
+        let Some(metadata) = self.rtr.metadata_result()? else {
+            return Err(AotError::MissingTable("a metadata section"));
+        };
+
+        let instances = self.generic_type_instances()?;
+        let parse = |va: Va| MethodTable::parse(&mut View::new(self.pe, va)).ok();
+
+        let mut types = Vec::new();
+
+        for mt_va in instances.iter().copied() {
+            let Some(mt) = parse(mt_va) else {
+                continue;
+            };
+
+            let Some(definition_va) = mt.generic_definition() else {
+                continue;
+            };
+
+            let (Some(&definition), Some(definition_mt)) =
+                (type_map.get(&definition_va), parse(definition_va))
+            else {
+                continue;
+            };
+
+            let Ok(arity) = definition
+                .to_data(metadata)
+                .and_then(|def| def.generic_parameters.count())
+            else {
+                continue;
+            };
+
+            let Some(generic_arguments) = mt.generic_argument_addresses(arity as usize) else {
+                continue;
+            };
+
+            let Some(argument_hashcodes) = generic_arguments
+                .iter()
+                .map(|&va| parse(va).map(|arg| arg.hashcode as i32))
+                .collect::<Option<Vec<_>>>()
+            else {
+                continue;
+            };
+
+            // Guards against misreading the optional fields of the MethodTable
+            if compute_generic_instance_hashcode(definition_mt.hashcode as i32, &argument_hashcodes)
+                != mt.hashcode as i32
+            {
+                continue;
+            }
+
+            types.push(ConstructedGenericType {
+                method_table: mt_va,
+                definition,
+                generic_arguments,
+            });
+        }
+
+        debug!(
+            "Decoded {}/{} constructed generic types",
+            types.len(),
+            instances.len()
+        );
+
+        Ok(types)
+    }
+}
@@ -1,10 +1,22 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::{Ref, RefCell},
+    collections::HashMap,
+    iter,
+    rc::Rc,
+};
 
-use anyhow::{Result, bail};
 use binary_rw::{BinaryReader, Endian};
 use num_enum::{IntoPrimitive, TryFromPrimitive};
+use pelite::pe64::Va;
 
-use crate::native_format::View;
+use crate::{
+    embedded_meta::hashing::{compute_generic_instance_hashcode, compute_name_hashcode},
+    error::{AotError, Result},
+    native_format::View,
+};
+
+/// How `System.__Canon` is named, the type argument shared generic code is compiled against
+pub const CANONICAL_TYPE_NAME: &str = "__Canon";
 
 #[derive(Debug, Clone)]
 pub struct MethodTable<'a> {
@@ -18,6 +30,8 @@ pub struct MethodTable<'a> {
 
     pub vtable_addresses: Rc<[u64]>,
     pub iface_addresses: Rc<[u64]>,
+    /// The names of the interfaces in `iface_addresses`, filled in by [`crate::binary::NativeAotBinary::name_interfaces`]
+    pub iface_names: Rc<[Option<String>]>,
 
     // I'm not too well versed in memory optimizations, but not adding Rc's here made the app waste over 30 GiB of memory (obviously due to self-referencing)
     // Additionally adding Rc<RefCell<...>> further reduced mem usage from 2.2 GiB to ~40 MiB (not including std::fs::read) on my test binary of ~50 MiB
@@ -31,6 +45,16 @@ impl<'a> MethodTable<'a> {
     const ELEMENT_TYPE_MASK: u32 = 0x7C000000;
     const ELEMENT_TYPE_SHIFT: u32 = 26;
 
+    pub const HAS_COMPONENT_SIZE: u32 = 0x80000000;
+    pub const HAS_DISPATCH_MAP: u32 = 0x00040000;
+    pub const IS_DYNAMIC_TYPE: u32 = 0x00080000;
+    pub const HAS_FINALIZER: u32 = 0x00100000;
+    pub const HAS_SEALED_VTABLE_ENTRIES: u32 = 0x00400000;
+    pub const IS_GENERIC: u32 = 0x02000000;
+
+    // Flags + BaseSize + RelatedType + VTableCount + InterfaceCount + HashCode
+    const FIXED_SIZE: u64 = 24;
+
     pub fn parse(view: &mut View<'a>) -> Result<Self> {
         let table_view = *view;
         let mut reader = BinaryReader::new(view, Endian::Little);
@@ -42,12 +66,30 @@ impl<'a> MethodTable<'a> {
         let iface_count = reader.read_u16()?;
         let hashcode = reader.read_u32()?;
 
+        // Validate the whole header before reading the vtable and interface arrays, most candidates of the scan fail here
+        let element_type =
+            ElementType::try_from((flags & Self::ELEMENT_TYPE_MASK) >> Self::ELEMENT_TYPE_SHIFT)
+                .unwrap_or(ElementType::Unknown);
+        if element_type == ElementType::Interface {
+            if base_size != 0x00 {
+                return Err(AotError::BadMethodTable(
+                    "unexpected non-zero interface base size",
+                ));
+            } else if related_type != 0x00 {
+                return Err(AotError::BadMethodTable(
+                    "unexpected non-zero interface related type",
+                ));
+            }
+        } else if base_size < 0x10 {
+            return Err(AotError::BadMethodTable("unexpected base size"));
+        }
+
         if (vtable_count as i16) < 0 || vtable_count >= 1000 {
-            bail!("invalid vtable slot count");
+            return Err(AotError::BadMethodTable("invalid vtable slot count"));
         }
 
         if (iface_count as i16) < 0 || iface_count >= 1000 {
-            bail!("invalid interface count");
+            return Err(AotError::BadMethodTable("invalid interface count"));
         }
 
         let mut vtables = Vec::with_capacity(vtable_count as _);
@@ -60,19 +102,6 @@ impl<'a> MethodTable<'a> {
             ifaces.push(reader.read_u64()?);
         }
 
-        let element_type =
-            ElementType::try_from((flags & Self::ELEMENT_TYPE_MASK) >> Self::ELEMENT_TYPE_SHIFT)
-                .unwrap_or(ElementType::Unknown);
-        if element_type == ElementType::Interface {
-            if base_size != 0x00 {
-                bail!("unexpected non-zero interface base size");
-            } else if related_type != 0x00 {
-                bail!("unexpected non-zero interface related type");
-            }
-        } else if base_size < 0x10 {
-            bail!("unexpected base size")
-        }
-
         Ok(Self {
             view: table_view,
 
@@ -84,11 +113,345 @@ impl<'a> MethodTable<'a> {
 
             vtable_addresses: vtables.into(),
             iface_addresses: ifaces.into(),
+            iface_names: Rc::new([]),
 
             related_type: None,
             interfaces: Rc::new(RefCell::new(Vec::with_capacity(iface_count as _))),
         })
     }
+
+    /// The MethodTable of the base type, if the scan matched it
+    ///
+    /// Arrays, pointers and byrefs store their element type there instead, see [`MethodTable::parameterized_element`].
+    pub fn base_table(&self) -> Option<&MethodTable<'a>> {
+        if self.element_type.is_parameterized() {
+            return None;
+        }
+
+        self.related_type.as_deref()
+    }
+
+    /// The MethodTable of the element type of an array, pointer or byref, if the scan matched it
+    pub fn parameterized_element(&self) -> Option<Rc<MethodTable<'a>>> {
+        if !self.element_type.is_parameterized() {
+            return None;
+        }
+
+        self.related_type.clone()
+    }
+
+    /// The primitive an array, pointer or byref is made of, e.g. `Int32` for `int*`
+    ///
+    /// Read from the element on demand, so it's known even when the scan didn't match its MethodTable. Enums carry the
+    /// element type of their underlying type, so the element only counts when its hashcode is that of the primitive.
+    pub fn primitive_element(&self) -> Option<ElementType> {
+        if !self.element_type.is_parameterized() || self.related_type_address == 0 {
+            return None;
+        }
+
+        let mut element = View::new(self.view.pe, self.related_type_address);
+        let mut reader = BinaryReader::new(&mut element, Endian::Little);
+
+        let flags = reader.read_u32().ok()?;
+        let _base_size = reader.read_u32().ok()?;
+        let _related_type = reader.read_u64().ok()?;
+        let _vtable_count = reader.read_u16().ok()?;
+        let _iface_count = reader.read_u16().ok()?;
+        let hashcode = reader.read_u32().ok()?;
+
+        let element_type =
+            ElementType::try_from((flags & Self::ELEMENT_TYPE_MASK) >> Self::ELEMENT_TYPE_SHIFT)
+                .ok()?;
+        let name = element_type.primitive_name()?;
+
+        (hashcode as i32 == compute_name_hashcode(&name)).then_some(element_type)
+    }
+
+    /// The MethodTables of the interfaces the scan matched
+    ///
+    /// Every item keeps the interface list borrowed, so don't hold on to them while the scan is still running.
+    pub fn interface_tables(&self) -> impl Iterator<Item = Ref<'_, MethodTable<'a>>> {
+        let count = self.interfaces.borrow().len();
+
+        (0..count).map(|index| Ref::map(self.interfaces.borrow(), |interfaces| &interfaces[index]))
+    }
+
+    /// The names of the interfaces this type implements, in the order of the interface map
+    ///
+    /// Interfaces that [`crate::binary::NativeAotBinary::name_interfaces`] didn't name are written as
+    /// `<MethodTable 0x...>`.
+    pub fn interface_names(&self) -> Vec<String> {
+        self.iface_addresses
+            .iter()
+            .enumerate()
+            .map(|(index, va)| match self.iface_names.get(index) {
+                Some(Some(name)) => name.clone(),
+                _ => format!("<MethodTable {va:#x}>"),
+            })
+            .collect()
+    }
+
+    /// This MethodTable followed by the MethodTables of all its base types, up to the first one the scan didn't match
+    pub fn walk_hierarchy(&self) -> impl Iterator<Item = &MethodTable<'a>> {
+        iter::successors(Some(self), |mt| mt.base_table())
+    }
+
+    /// Size of the header, the vtable and the interface map, the optional fields follow right after
+    pub fn size(&self) -> u64 {
+        Self::FIXED_SIZE
+            + 8 * self.vtable_addresses.len() as u64
+            + 8 * self.iface_addresses.len() as u64
+    }
+
+    /// Size of every element of an array or character of a string, stored in the lower bits of the flags
+    pub fn component_size(&self) -> Option<u16> {
+        (self.flags & Self::HAS_COMPONENT_SIZE != 0).then_some(self.flags as u16)
+    }
+
+    pub fn is_generic(&self) -> bool {
+        self.flags & Self::IS_GENERIC != 0
+    }
+
+    /// Whether this is `System.__Canon`, which stands in for every reference type argument of shared generic code
+    ///
+    /// It has no metadata, so it's recognized by the hashcode of its name.
+    pub fn is_canonical(&self) -> bool {
+        self.element_type == ElementType::Class
+            && self.hashcode as i32 == compute_name_hashcode("System.__Canon")
+    }
+
+    /// Offset of one of the optional fields following the interface map, if the table has it
+    ///
+    /// These are relative pointers, in the order of the TypeManager indirection, the writable data, the dispatch map,
+    /// the finalizer, the sealed vtable, the generic definition and the generic composition.
+    fn optional_field_offset(&self, field: OptionalField) -> Option<u64> {
+        // Dynamic types are created at runtime and never stored in the image
+        if self.flags & Self::IS_DYNAMIC_TYPE != 0 {
+            return None;
+        }
+
+        let mut offset = self.size();
+
+        for (candidate, present) in [
+            (OptionalField::TypeManager, true),
+            (OptionalField::WritableData, true),
+            (
+                OptionalField::DispatchMap,
+                self.flags & Self::HAS_DISPATCH_MAP != 0,
+            ),
+            (
+                OptionalField::Finalizer,
+                self.flags & Self::HAS_FINALIZER != 0,
+            ),
+            (
+                OptionalField::SealedVirtualSlots,
+                self.flags & Self::HAS_SEALED_VTABLE_ENTRIES != 0,
+            ),
+            (OptionalField::GenericDefinition, self.is_generic()),
+            (OptionalField::GenericComposition, self.is_generic()),
+        ] {
+            if candidate == field {
+                return present.then_some(offset);
+            }
+
+            if present {
+                offset += 4;
+            }
+        }
+
+        None
+    }
+
+    /// The MethodTable of the generic type definition this type was constructed from
+    pub fn generic_definition(&self) -> Option<Va> {
+        read_relative_pointer(
+            self.view
+                .with_offset(self.optional_field_offset(OptionalField::GenericDefinition)?),
+        )
+    }
+
+    /// The MethodTables of the type arguments, e.g. `Int32` and `String` for `Dictionary<int, string>`
+    ///
+    /// The arity is read from the generic definition, which stores it in place of the component size. Returns `None`
+    /// for non-generic types, and for instantiations whose arguments don't parse as MethodTables or don't hash to the
+    /// hashcode of this table, like the canonical forms shared code is compiled against.
+    pub fn generic_arguments(&self) -> Option<Vec<Rc<MethodTable<'a>>>> {
+        let parse = |va: Va| MethodTable::parse(&mut View::new(self.view.pe, va)).ok();
+
+        let definition = parse(self.generic_definition()?)?;
+        let arity = definition.flags as u16 as usize;
+        if arity == 0 {
+            return None;
+        }
+
+        let arguments = self
+            .generic_argument_addresses(arity)?
+            .into_iter()
+            .map(|va| parse(va).map(Rc::new))
+            .collect::<Option<Vec<_>>>()?;
+
+        // Guards against a misread arity or composition
+        let hashcodes = arguments
+            .iter()
+            .map(|argument| argument.hashcode as i32)
+            .collect::<Vec<_>>();
+        (compute_generic_instance_hashcode(definition.hashcode as i32, &hashcodes)
+            == self.hashcode as i32)
+            .then_some(arguments)
+    }
+
+    /// Names a constructed generic type after its definition and type arguments, e.g. `Dictionary<Int32, String>`
+    ///
+    /// Used for the instantiations the GenericsHashtable doesn't list, `names` holds the names that are already known.
+    pub fn generic_instance_name(&self, names: &HashMap<Va, String>) -> Option<String> {
+        let definition = names.get(&self.generic_definition()?)?;
+        // Definitions are named with their generic parameters, e.g. Dictionary<TKey, TValue>
+        let definition = definition
+            .split_once(['<', '`'])
+            .map_or(definition.as_str(), |(name, _)| name);
+
+        let args = self
+            .generic_arguments()?
+            .iter()
+            .map(|arg| {
+                names
+                    .get(&arg.view.va())
+                    .cloned()
+                    .or_else(|| arg.is_canonical().then(|| CANONICAL_TYPE_NAME.to_owned()))
+                    .or_else(|| arg.generic_instance_name(names))
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(format!("{definition}<{}>", args.join(", ")))
+    }
+
+    /// The addresses of the MethodTables of the type arguments, given the arity of the generic definition
+    pub fn generic_argument_addresses(&self, arity: usize) -> Option<Vec<Va>> {
+        let composition = self
+            .view
+            .with_offset(self.optional_field_offset(OptionalField::GenericComposition)?);
+
+        // A single argument is stored inline, longer lists are stored out of line so they can be shared
+        if arity == 1 {
+            return Some(vec![read_relative_pointer(composition)?]);
+        }
+
+        let list = View::new(self.view.pe, read_relative_pointer(composition)?);
+
+        (0..arity as u64)
+            .map(|index| read_relative_pointer(list.with_offset(index * 4)))
+            .collect()
+    }
+
+    /// Address of the TypeManagerIndirection cell of the module, see [`crate::binary::type_manager`]
+    pub fn type_manager_indirection(&self) -> Option<Va> {
+        read_relative_pointer(
+            self.view
+                .with_offset(self.optional_field_offset(OptionalField::TypeManager)?),
+        )
+    }
+
+    /// Address of the interface dispatch map, see [`crate::binary::dispatch`]
+    pub fn dispatch_map(&self) -> Option<Va> {
+        read_relative_pointer(
+            self.view
+                .with_offset(self.optional_field_offset(OptionalField::DispatchMap)?),
+        )
+    }
+
+    /// Code of a slot in the sealed vtable, which holds the virtual methods that can't be overridden
+    ///
+    /// These are numbered after the regular vtable slots, so `slot` is relative to the end of the vtable.
+    pub fn sealed_virtual_slot(&self, slot: u16) -> Option<Va> {
+        let table = read_relative_pointer(
+            self.view
+                .with_offset(self.optional_field_offset(OptionalField::SealedVirtualSlots)?),
+        )?;
+
+        read_relative_pointer(View::new(self.view.pe, table).with_offset(slot as u64 * 4))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OptionalField {
+    TypeManager,
+    WritableData,
+    DispatchMap,
+    Finalizer,
+    SealedVirtualSlots,
+    GenericDefinition,
+    GenericComposition,
+}
+
+fn read_relative_pointer(mut view: View<'_>) -> Option<Va> {
+    let base = view.va();
+    let delta = BinaryReader::new(&mut view, Endian::Little)
+        .read_i32()
+        .ok()?;
+
+    Some(base.wrapping_add_signed(delta as i64))
+}
+
+/// A `Send + Sync` version of [`MethodTable`], for consumers that process tables on multiple threads
+///
+/// Relationships are stored as indices into the `Vec<OwnedMethodTable>` the table was created in, instead of `Rc`s.
+/// Every table owns its vtable and interface addresses, which costs more memory than sharing them, but the indices
+/// avoid the self-referencing copies that made a naive owned representation waste tens of GiBs.
+#[derive(Debug, Clone)]
+pub struct OwnedMethodTable {
+    pub va: Va,
+
+    pub flags: u32,
+    pub base_size: u32,
+    pub related_type_address: u64,
+    pub hashcode: u32,
+    pub element_type: ElementType,
+
+    pub vtable_addresses: Vec<u64>,
+    pub iface_addresses: Vec<u64>,
+    pub iface_names: Vec<Option<String>>,
+
+    pub related_type: Option<usize>,
+    pub interfaces: Vec<usize>,
+}
+
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<OwnedMethodTable>();
+};
+
+impl OwnedMethodTable {
+    pub fn from_tables(tables: &[MethodTable<'_>]) -> Vec<Self> {
+        let indices = tables
+            .iter()
+            .enumerate()
+            .map(|(index, mt)| (mt.view.va(), index))
+            .collect::<HashMap<_, _>>();
+
+        tables
+            .iter()
+            .map(|mt| Self {
+                va: mt.view.va(),
+
+                flags: mt.flags,
+                base_size: mt.base_size,
+                related_type_address: mt.related_type_address,
+                hashcode: mt.hashcode,
+                element_type: mt.element_type,
+
+                vtable_addresses: mt.vtable_addresses.to_vec(),
+                iface_addresses: mt.iface_addresses.to_vec(),
+                iface_names: mt.iface_names.to_vec(),
+
+                related_type: indices.get(&mt.related_type_address).copied(),
+                interfaces: mt
+                    .iface_addresses
+                    .iter()
+                    .filter_map(|va| indices.get(va).copied())
+                    .collect(),
+            })
+            .collect()
+    }
 }
 
 #[derive(TryFromPrimitive, IntoPrimitive, Clone, Copy, PartialEq, Eq, Debug)]
@@ -127,3 +490,146 @@ pub enum ElementType {
     Pointer = 0x1A,
     FunctionPointer = 0x1B,
 }
+
+impl ElementType {
+    /// Whether the related type of a MethodTable with this element type is its element type rather than its base type
+    pub fn is_parameterized(self) -> bool {
+        matches!(
+            self,
+            Self::Array | Self::SzArray | Self::ByRef | Self::Pointer
+        )
+    }
+
+    /// The element type of a primitive type, given its full name, e.g. `Int32` for `System.Int32`
+    pub fn from_primitive_name(name: &str) -> Option<Self> {
+        let name = name.strip_prefix("System.")?;
+
+        (Self::Void as u32..=Self::Double as u32)
+            .filter_map(|value| Self::try_from(value).ok())
+            .find(|element_type| format!("{element_type:?}") == name)
+    }
+
+    /// The C# keyword of the integral types enums can be based on, e.g. `int` for `Int32`
+    pub fn keyword(self) -> Option<&'static str> {
+        Some(match self {
+            Self::Boolean => "bool",
+            Self::Char => "char",
+            Self::SByte => "sbyte",
+            Self::Byte => "byte",
+            Self::Int16 => "short",
+            Self::UInt16 => "ushort",
+            Self::Int32 => "int",
+            Self::UInt32 => "uint",
+            Self::Int64 => "long",
+            Self::UInt64 => "ulong",
+            Self::IntPtr => "nint",
+            Self::UIntPtr => "nuint",
+            _ => return None,
+        })
+    }
+
+    /// Full name of the primitive type with this element type, e.g. `System.Int32`
+    pub fn primitive_name(self) -> Option<String> {
+        // The primitives are named after their type
+        (Self::Void as u32..=Self::Double as u32)
+            .contains(&(self as u32))
+            .then(|| format!("System.{self:?}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use pelite::pe64::PeFile;
+
+    use crate::{
+        binary::{NativeAotBinary, ScanSections},
+        test_utils::{IMAGE_BASE, PeBuilder, encode_method_table, encode_rtr_header},
+    };
+
+    const CLASS: u32 = 0x14 << 26;
+    const INTERFACE: u32 = 0x15 << 26;
+
+    #[test]
+    fn scanned_tables_link_their_hierarchy() {
+        let mut builder = PeBuilder::new();
+        let text_va = IMAGE_BASE + builder.section(".text", vec![0xC3; 0x30]) as u64;
+        let vtable = [text_va, text_va + 0x10, text_va + 0x20];
+
+        // `Derived : Base : System.Object`, where Base implements one interface and Derived another one on top
+        let data_va = IMAGE_BASE + builder.next_rva() as u64;
+        let (object, base, derived) = (data_va, data_va + 0x40, data_va + 0x80);
+        let (first_iface, second_iface) = (data_va + 0xC0, data_va + 0xE0);
+
+        let mut data = Vec::new();
+        for (va, table) in [
+            (object, encode_method_table(CLASS, 0x18, 0, &vtable, &[], 1)),
+            (
+                base,
+                encode_method_table(CLASS, 0x18, object, &vtable, &[first_iface], 2),
+            ),
+            (
+                derived,
+                encode_method_table(CLASS, 0x20, base, &vtable, &[first_iface, second_iface], 3),
+            ),
+            (
+                first_iface,
+                encode_method_table(INTERFACE, 0, 0, &[], &[], 4),
+            ),
+            (
+                second_iface,
+                encode_method_table(INTERFACE, 0, 0, &[], &[], 5),
+            ),
+        ] {
+            data.resize((va - data_va) as usize, 0);
+            data.extend_from_slice(&table);
+        }
+        data.resize(0x100, 0);
+        builder.section(".data", data);
+        let rtr_va = IMAGE_BASE + builder.section(".rdata", encode_rtr_header(&[])) as u64;
+
+        let image = builder.build();
+        let pe = PeFile::from_bytes(&image).unwrap();
+        let binary = NativeAotBinary::from_pe(pe, rtr_va).unwrap();
+
+        let mut tables = binary.scan_method_tables().unwrap();
+        assert!(tables.iter().all(|mt| mt.iface_names.is_empty()));
+
+        // Only the first interface has a name, like an interface without an entry in the TypeMap
+        binary.name_interfaces(
+            &mut tables,
+            &HashMap::from([(first_iface, "Game.IFirst".to_string())]),
+        );
+        let table = |va| tables.iter().find(|mt| mt.view.va() == va).unwrap();
+
+        assert_eq!(
+            table(derived)
+                .walk_hierarchy()
+                .map(|mt| mt.view.va())
+                .collect::<Vec<_>>(),
+            [derived, base, object]
+        );
+        assert_eq!(table(derived).base_table().unwrap().view.va(), base);
+        assert!(table(object).base_table().is_none());
+
+        let interfaces = |va| {
+            table(va)
+                .interface_tables()
+                .map(|mt| mt.view.va())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(interfaces(derived), [first_iface, second_iface]);
+        assert_eq!(interfaces(base), [first_iface]);
+        assert!(interfaces(object).is_empty());
+
+        assert_eq!(
+            table(derived).interface_names(),
+            [
+                "Game.IFirst".to_string(),
+                format!("<MethodTable {second_iface:#x}>")
+            ]
+        );
+        assert_eq!(table(base).interface_names(), ["Game.IFirst"]);
+    }
+}
@@ -1,11 +1,13 @@
 use std::fmt::Debug;
 
-use anyhow::{Result, anyhow};
 use binary_rw::{BinaryReader, Endian};
+use log::trace;
 use num_enum::FromPrimitive;
+use pelite::pe64::{Pe, PeFile, Va};
 
 use crate::{
     embedded_meta::MetadataReader,
+    error::{AotError, Result},
     native_format::{
         View, hashtable::NativeHashtable, parser::NativeParser, reader::NativeReader,
         ref_table::ExternalReferencesTable,
@@ -16,6 +18,9 @@ use crate::{
 pub struct ReadyToRunHeader<'a> {
     signature: Signature,
 
+    /// Address of the header in the image
+    pub address: Va,
+
     pub major_version: u16,
     pub minor_version: u16,
     pub flags: u32,
@@ -23,6 +28,12 @@ pub struct ReadyToRunHeader<'a> {
     pub entry_size: u8,
     pub entry_type: u8,
     pub sections: Vec<ReadyToRunSection<'a>>,
+
+    /// The sections skipped by [`ReadyToRunHeader::parse_lenient`], by their index in the section table
+    pub section_errors: Vec<(usize, AotError)>,
+
+    /// Whether [`Self::metadata`] fails on strings that aren't valid UTF-8, set by `--strict-strings`
+    pub strict_strings: bool,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -49,6 +60,8 @@ pub enum ReadyToRunSectionType {
     DelayLoadMethodCallThunks,
     AvailableTypes,
     InstanceMethodEntryPoints,
+    // The inlining sections are parsed in `binary::inlining`. They're written by crossgen2, and key methods by their
+    // ECMA-335 MethodDef rows, which the NativeAOT metadata doesn't keep.
     InliningInfo,             // Added in v.2.1, deprecated in 4.1
     ProfileDataInfo,          // Added in v2.2
     ManifestMetadata,         // Added in v2.3
@@ -138,6 +151,18 @@ pub enum ReflectionMapBlob {
 
 impl<'a> ReadyToRunHeader<'a> {
     pub fn parse(view: &mut View<'a>) -> Result<Self> {
+        Self::parse_sections(view, false)
+    }
+
+    /// Like [`Self::parse`], but skips the sections that fail to parse instead of failing the whole header
+    ///
+    /// The skipped sections are recorded in `section_errors`.
+    pub fn parse_lenient(view: &mut View<'a>) -> Result<Self> {
+        Self::parse_sections(view, true)
+    }
+
+    fn parse_sections(view: &mut View<'a>, lenient: bool) -> Result<Self> {
+        let address = view.va();
         let mut reader = BinaryReader::new(view, Endian::Little);
 
         let signature = Signature::parse(&mut reader)?;
@@ -149,15 +174,41 @@ impl<'a> ReadyToRunHeader<'a> {
         let entry_type = reader.read_u8()?;
 
         // Sanity checks
-        anyhow::ensure!((number_of_sections as i16) >= 0 || number_of_sections < 1000);
+        if !((number_of_sections as i16) >= 0 || number_of_sections < 1000) {
+            return Err(AotError::BadSectionCount(number_of_sections));
+        }
 
         let mut sections = vec![];
-        for _ in 0..number_of_sections {
-            sections.push(ReadyToRunSection::parse(view)?);
+        let mut section_errors = vec![];
+
+        for index in 0..number_of_sections as usize {
+            let section = match ReadyToRunSection::parse(view) {
+                Ok(section) => section,
+                Err(why) if lenient => {
+                    section_errors.push((index, why));
+                    continue;
+                }
+                Err(why) => {
+                    return Err(AotError::BadSection {
+                        index,
+                        source: Box::new(why),
+                    });
+                }
+            };
+
+            trace!(
+                "Section {:?} at {:#x}..{:#x}",
+                section.section_type,
+                section.start.va(),
+                section.end.va()
+            );
+
+            sections.push(section);
         }
 
         Ok(Self {
             signature,
+            address,
             major_version,
             minor_version,
             flags,
@@ -165,9 +216,15 @@ impl<'a> ReadyToRunHeader<'a> {
             entry_size,
             entry_type,
             sections,
+            section_errors,
+            strict_strings: false,
         })
     }
 
+    pub fn signature(&self) -> Signature {
+        self.signature
+    }
+
     pub fn section(&self, section_type: ReadyToRunSectionType) -> Option<ReadyToRunSection<'a>> {
         self.sections
             .iter()
@@ -179,25 +236,76 @@ impl<'a> ReadyToRunHeader<'a> {
         self.section(ReadyToRunSectionType::ReflectionMapBlob(blob_type))
     }
 
-    pub fn blob_hashtable(&self, blob_type: ReflectionMapBlob) -> Option<NativeHashtable<'a>> {
-        let blob = self.blob(blob_type)?;
-        let reader = NativeReader::new(blob.start.bytes().ok()?).ok()?;
+    pub fn section_hashtable(
+        &self,
+        section_type: ReadyToRunSectionType,
+    ) -> Option<NativeHashtable<'a>> {
+        let section = self.section(section_type)?;
+        let reader = NativeReader::new(section.start.bytes().ok()?).ok()?;
         let parser = NativeParser::new(reader, 0);
 
         Some(NativeHashtable::new(parser).ok()?)
     }
 
+    pub fn blob_hashtable(&self, blob_type: ReflectionMapBlob) -> Option<NativeHashtable<'a>> {
+        self.section_hashtable(ReadyToRunSectionType::ReflectionMapBlob(blob_type))
+    }
+
     pub fn metadata(&self) -> Option<MetadataReader<'a>> {
-        let blob = self.blob(ReflectionMapBlob::EmbeddedMetadata)?;
-        let reader = MetadataReader::new(blob.start.bytes().ok()?).ok()?;
+        self.metadata_result().ok().flatten()
+    }
+
+    /// Like [`Self::metadata`], but tells a missing metadata section apart from one that fails to parse
+    pub fn metadata_result(&self) -> Result<Option<MetadataReader<'a>>> {
+        let Some(blob) = self.blob(ReflectionMapBlob::EmbeddedMetadata) else {
+            return Ok(None);
+        };
 
-        Some(reader)
+        let reader = blob
+            .start
+            .bytes()
+            .map_err(AotError::from)
+            .and_then(|bytes| MetadataReader::with_strict_strings(bytes, self.strict_strings))
+            .map_err(|why| AotError::BadMetadata(Box::new(why)))?;
+
+        Ok(Some(reader))
+    }
+
+    /// The MVIDs of the manifest assemblies, by their index in the manifest metadata
+    ///
+    /// Only composite images have the section, for the others this is empty.
+    pub fn manifest_assembly_mvids(&self) -> Result<Vec<[u8; 16]>> {
+        let Some(section) = self.section(ReadyToRunSectionType::ManifestAssemblyMvids) else {
+            return Ok(Vec::new());
+        };
+
+        let len = (section.end.va() - section.start.va()) as usize;
+        if !len.is_multiple_of(16) {
+            return Err(AotError::BadImage);
+        }
+
+        let bytes = section
+            .start
+            .bytes()?
+            .get(..len)
+            .ok_or(AotError::BadImage)?;
+
+        Ok(bytes
+            .chunks_exact(16)
+            .map(|mvid| mvid.try_into().unwrap())
+            .collect())
     }
 
     pub fn common_fixups_table(&self) -> Option<ExternalReferencesTable<'a>> {
         self.blob(ReflectionMapBlob::CommonFixupsTable)
             .map(|sect| ExternalReferencesTable::new(sect.start, sect.end.va() - sect.start.va()))
     }
+
+    /// The references of the tables used by the type loader, such as the GenericsHashtable
+    pub fn native_references_table(&self) -> Option<ExternalReferencesTable<'a>> {
+        self.blob(ReflectionMapBlob::NativeReferences)
+            .map(|sect| ExternalReferencesTable::new(sect.start, sect.end.va() - sect.start.va()))
+    }
 }
 
 impl<'a> ReadyToRunSection<'a> {
@@ -211,6 +319,15 @@ impl<'a> ReadyToRunSection<'a> {
         let start = reader.read_u64()?;
         let end = reader.read_u64()?;
 
+        // Either reversed, or not within a single section of the image
+        if end < start || (start != end && !within_one_section(view.pe, start, end)) {
+            return Err(AotError::BadSectionRange {
+                section_type,
+                start,
+                end,
+            });
+        }
+
         Ok(Self {
             view: sect_view,
 
@@ -222,6 +339,18 @@ impl<'a> ReadyToRunSection<'a> {
     }
 }
 
+/// Whether `start..end` lies within the virtual range of a single section, so a `View` of it never reads past the
+/// data the image maps there
+fn within_one_section(pe: PeFile<'_>, start: Va, end: Va) -> bool {
+    let (Ok(start), Ok(last)) = (pe.va_to_rva(start), pe.va_to_rva(end - 1)) else {
+        return false;
+    };
+
+    pe.section_headers()
+        .by_rva(start)
+        .is_some_and(|sect| last >= start && last < sect.VirtualAddress + sect.VirtualSize)
+}
+
 impl ReadyToRunSectionType {
     fn from_u32(num: u32) -> Self {
         match num {
@@ -302,12 +431,12 @@ impl Signature {
 }
 
 impl TryFrom<u32> for Signature {
-    type Error = anyhow::Error;
+    type Error = AotError;
 
-    fn try_from(value: u32) -> std::result::Result<Self, Self::Error> {
+    fn try_from(value: u32) -> Result<Self> {
         (value == Self::SIGNATURE)
             .then_some(Self)
-            .ok_or_else(|| anyhow!("invalid ReadyToRunHeader signature"))
+            .ok_or(AotError::BadRtrSignature(value))
     }
 }
 
@@ -316,3 +445,73 @@ impl Debug for Signature {
         f.write_str("\"RTR\\0\"")
     }
 }
+
+/// Formats a GUID the way .NET does, e.g. `c0a1b2d3-e4f5-0617-2839-4a5b6c7d8e9f`
+pub fn format_guid(guid: &[u8; 16]) -> String {
+    // The first three groups are little endian
+    format!(
+        "{:08x}-{:04x}-{:04x}-{:02x}{:02x}-{}",
+        u32::from_le_bytes(guid[0..4].try_into().unwrap()),
+        u16::from_le_bytes(guid[4..6].try_into().unwrap()),
+        u16::from_le_bytes(guid[6..8].try_into().unwrap()),
+        guid[8],
+        guid[9],
+        guid[10..]
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<String>()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use pelite::pe64::PeFile;
+
+    use crate::{
+        binary::{NativeAotBinary, ScanSections, headers::rtr::format_guid},
+        test_utils::{IMAGE_BASE, PeBuilder, encode_rtr_header, minimal_image, rtr_header_size},
+    };
+
+    #[test]
+    fn guids_are_formatted_with_little_endian_groups() {
+        let guid = std::array::from_fn(|index| index as u8);
+
+        assert_eq!(format_guid(&guid), "03020100-0504-0706-0809-0a0b0c0d0e0f");
+    }
+
+    #[test]
+    fn manifest_assembly_mvids_are_read_in_index_order() {
+        const MANIFEST_ASSEMBLY_MVIDS: u32 = 118;
+
+        let mut builder = PeBuilder::new();
+        let rdata_va = IMAGE_BASE + builder.next_rva() as u64;
+        let mvids_va = rdata_va + rtr_header_size(1) as u64;
+
+        let mvids = [[0x11; 16], [0x22; 16]];
+        let mut rdata = encode_rtr_header(&[(MANIFEST_ASSEMBLY_MVIDS, mvids_va, mvids_va + 32)]);
+        rdata.extend(mvids.iter().flatten());
+        builder.section(".rdata", rdata);
+
+        let image = builder.build();
+        let pe = PeFile::from_bytes(&image).unwrap();
+        let binary = NativeAotBinary::from_pe(pe, rdata_va).unwrap();
+
+        assert_eq!(
+            binary.rtr_header().manifest_assembly_mvids().unwrap(),
+            mvids
+        );
+
+        // Images that aren't composite don't have the section
+        let (image, _) = minimal_image();
+        let pe = PeFile::from_bytes(&image).unwrap();
+        let binary = NativeAotBinary::load_pe(pe, ScanSections::default()).unwrap();
+
+        assert!(
+            binary
+                .rtr_header()
+                .manifest_assembly_mvids()
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
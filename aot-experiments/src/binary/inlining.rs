@@ -0,0 +1,338 @@
+//! The InliningInfo, InliningInfo2 and CrossModuleInlineInfo sections, listing the callers every method was inlined into
+//!
+//! All three are `NativeHashtable`s keyed by a hash of the inlinee, where every entry is a stream of unsigned integers
+//! prefixed with the number of integers that follow:
+//!
+//! ```text
+//! InliningInfo (v2.1):          count, inlinee_row, inliner_row_delta...
+//! InliningInfo2 (v4.1):         count, inlinee_row << 1 | foreign, [module], (inliner_row_delta << 1 | foreign, [module])...
+//! CrossModuleInlineInfo (v6.3): count, inlinee_index << 2 | flags, [il_body_count, il_body_index_delta...],
+//!                               (inliner_row_delta << 1 | foreign, [module])...
+//! ```
+//!
+//! The inliner rows are delta encoded against the previous inliner of the entry. A foreign method is followed by the
+//! index of the module defining it.
+
+use log::debug;
+
+use crate::{
+    binary::{NativeAotBinary, headers::rtr::ReadyToRunSectionType},
+    error::Result,
+    native_format::{hashtable::NativeHashtable, parser::NativeParser, reader::NativeReader},
+};
+
+/// A method the inlining sections refer to
+///
+/// Methods are identified by their 1-based ECMA MethodDef row, like in [`NativeAotBinary::method_entry_points`], as the
+/// NativeAOT metadata doesn't keep the MethodDef table to map them onto handles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InlineMethod {
+    /// A method definition, in the module with the given index, or in the module of the image itself when `None`
+    Definition { module: Option<u32>, row: u32 },
+    /// A method outside of the version bubble whose IL was embedded, by its index in the ILBody import section
+    IlBody(u32),
+}
+
+/// The callers a method was inlined into, see [`NativeAotBinary::inlining_info`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InliningRecord {
+    pub inlinee: InlineMethod,
+    pub inliners: Vec<InlineMethod>,
+}
+
+/// The encoding of the entries of an inlining section
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InliningFormat {
+    /// InliningInfo, only ever referring to the module of the image
+    Local,
+    /// InliningInfo2, allowing methods of other modules in the version bubble
+    Foreign,
+    /// CrossModuleInlineInfo, allowing inlinees from outside of the version bubble
+    CrossModule,
+}
+
+impl InliningFormat {
+    // CrossModuleInlineFlags
+    const CROSS_MODULE_INLINEE: u32 = 0x1;
+    const HAS_CROSS_MODULE_INLINERS: u32 = 0x2;
+    const CROSS_MODULE_INDEX_SHIFT: u32 = 2;
+
+    fn parse_entry(self, parser: &mut NativeParser<'_>) -> Result<InliningRecord> {
+        let remaining = parser.get_unsigned()?;
+        let mut stream = EntryStream { parser, remaining };
+
+        let value = stream.next()?;
+        let mut inliners = Vec::new();
+
+        let inlinee = match self {
+            Self::Local => InlineMethod::Definition {
+                module: None,
+                row: value,
+            },
+            Self::Foreign => InlineMethod::Definition {
+                module: stream.module_if(value & 1 != 0)?,
+                row: value >> 1,
+            },
+            Self::CrossModule => {
+                if value & Self::HAS_CROSS_MODULE_INLINERS != 0 {
+                    let mut il_body = 0u32;
+
+                    for _ in 0..stream.next()? {
+                        il_body = il_body.wrapping_add(stream.next()?);
+                        inliners.push(InlineMethod::IlBody(il_body));
+                    }
+                }
+
+                let index = value >> Self::CROSS_MODULE_INDEX_SHIFT;
+                if value & Self::CROSS_MODULE_INLINEE != 0 {
+                    InlineMethod::IlBody(index)
+                } else {
+                    InlineMethod::Definition {
+                        module: None,
+                        row: index,
+                    }
+                }
+            }
+        };
+
+        let mut row = 0u32;
+        while stream.remaining > 0 {
+            let value = stream.next()?;
+
+            let module = if self == Self::Local {
+                row = row.wrapping_add(value);
+                None
+            } else {
+                row = row.wrapping_add(value >> 1);
+                stream.module_if(value & 1 != 0)?
+            };
+
+            inliners.push(InlineMethod::Definition { module, row });
+        }
+
+        Ok(InliningRecord { inlinee, inliners })
+    }
+}
+
+/// The integers of an entry, counting down how many are left
+struct EntryStream<'p, 'a> {
+    parser: &'p mut NativeParser<'a>,
+    remaining: u32,
+}
+
+impl EntryStream<'_, '_> {
+    fn next(&mut self) -> Result<u32> {
+        self.remaining = self.remaining.saturating_sub(1);
+        self.parser.get_unsigned()
+    }
+
+    /// The module index following a foreign method
+    fn module_if(&mut self, foreign: bool) -> Result<Option<u32>> {
+        if foreign {
+            self.next().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<'a> NativeAotBinary<'a> {
+    /// Parses the inlining sections, returning nothing if the image doesn't have any
+    ///
+    /// InliningInfo2 replaced InliningInfo in v4.1, so the latter is only read when the former is missing. The
+    /// CrossModuleInlineInfo records are added to either.
+    pub fn inlining_info(&self) -> Result<Vec<InliningRecord>> {
+        let mut records = Vec::new();
+
+        let inlining_info = if self
+            .rtr
+            .section(ReadyToRunSectionType::InliningInfo2)
+            .is_some()
+        {
+            (
+                ReadyToRunSectionType::InliningInfo2,
+                InliningFormat::Foreign,
+            )
+        } else {
+            (ReadyToRunSectionType::InliningInfo, InliningFormat::Local)
+        };
+        let sections = [
+            inlining_info,
+            (
+                ReadyToRunSectionType::CrossModuleInlineInfo,
+                InliningFormat::CrossModule,
+            ),
+        ];
+
+        for (section_type, format) in sections {
+            let Some(section) = self.rtr.section(section_type) else {
+                continue;
+            };
+
+            let reader = NativeReader::new(section.start.bytes()?)?;
+            let table = NativeHashtable::new(NativeParser::new(reader, 0))?;
+
+            for mut parser in table.enumerate_all()? {
+                records.push(format.parse_entry(&mut parser)?);
+            }
+        }
+
+        debug!("Inlining sections have {} inlinees", records.len());
+
+        Ok(records)
+    }
+
+    /// The methods that were inlined into `inliner`, which thus may not have code of their own in the image
+    pub fn inlinees_of(&self, inliner: InlineMethod) -> Result<Vec<InlineMethod>> {
+        Ok(self
+            .inlining_info()?
+            .into_iter()
+            .filter(|record| record.inliners.contains(&inliner))
+            .map(|record| record.inlinee)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pelite::pe64::PeFile;
+
+    use crate::{
+        binary::{
+            NativeAotBinary,
+            inlining::{InlineMethod, InliningRecord},
+        },
+        test_utils::{
+            IMAGE_BASE, PeBuilder, encode_hashtable, encode_rtr_header, encode_unsigned,
+            rtr_header_size,
+        },
+    };
+
+    const INLINING_INFO: u32 = 110;
+    const INLINING_INFO_2: u32 = 114;
+    const CROSS_MODULE_INLINE_INFO: u32 = 119;
+
+    fn entry(values: &[u32]) -> Vec<u8> {
+        let mut entry = Vec::new();
+        encode_unsigned(&mut entry, values.len() as u32);
+        for &value in values {
+            encode_unsigned(&mut entry, value);
+        }
+
+        entry
+    }
+
+    /// An image with an inlining section of every given type, each a hashtable of the given entries
+    fn image_with_sections(sections: &[(u32, Vec<Vec<u8>>)]) -> (Vec<u8>, u64) {
+        let mut builder = PeBuilder::new();
+        let rdata_va = IMAGE_BASE + builder.next_rva() as u64;
+
+        let mut position = rdata_va + rtr_header_size(sections.len()) as u64;
+        let mut headers = Vec::new();
+        let mut tables = Vec::new();
+
+        for (section_type, entries) in sections {
+            let entries = entries
+                .iter()
+                .enumerate()
+                .map(|(hashcode, entry)| (hashcode as u32, entry.clone()))
+                .collect::<Vec<_>>();
+            let table = encode_hashtable(&entries);
+
+            headers.push((*section_type, position, position + table.len() as u64));
+            position += table.len() as u64;
+            tables.extend_from_slice(&table);
+        }
+
+        let mut rdata = encode_rtr_header(&headers);
+        rdata.extend_from_slice(&tables);
+        builder.section(".rdata", rdata);
+
+        (builder.build(), rdata_va)
+    }
+
+    fn local(row: u32) -> InlineMethod {
+        InlineMethod::Definition { module: None, row }
+    }
+
+    #[test]
+    fn inlining_info_lists_the_delta_encoded_inliners() {
+        // Method 4 is inlined into methods 2 and 7
+        let (image, rtr_va) = image_with_sections(&[(INLINING_INFO, vec![entry(&[4, 2, 5])])]);
+        let pe = PeFile::from_bytes(&image).unwrap();
+        let binary = NativeAotBinary::from_pe(pe, rtr_va).unwrap();
+
+        assert_eq!(
+            binary.inlining_info().unwrap(),
+            vec![InliningRecord {
+                inlinee: local(4),
+                inliners: vec![local(2), local(7)],
+            }]
+        );
+        assert_eq!(binary.inlinees_of(local(7)).unwrap(), vec![local(4)]);
+        assert_eq!(binary.inlinees_of(local(4)).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn inlining_info_2_replaces_the_legacy_section() {
+        // Method 3 of module 1 is inlined into method 2, and into method 5 of module 6
+        let (image, rtr_va) = image_with_sections(&[
+            (INLINING_INFO, vec![entry(&[9, 1])]),
+            (
+                INLINING_INFO_2,
+                vec![entry(&[3 << 1 | 1, 1, 2 << 1, 3 << 1 | 1, 6])],
+            ),
+        ]);
+        let pe = PeFile::from_bytes(&image).unwrap();
+        let binary = NativeAotBinary::from_pe(pe, rtr_va).unwrap();
+
+        assert_eq!(
+            binary.inlining_info().unwrap(),
+            vec![InliningRecord {
+                inlinee: InlineMethod::Definition {
+                    module: Some(1),
+                    row: 3,
+                },
+                inliners: vec![
+                    local(2),
+                    InlineMethod::Definition {
+                        module: Some(6),
+                        row: 5,
+                    },
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn cross_module_inline_info_lists_il_bodies() {
+        // IL body 2 is inlined into IL bodies 1 and 4 and into method 8, method 5 only into method 6
+        let (image, rtr_va) = image_with_sections(&[(
+            CROSS_MODULE_INLINE_INFO,
+            vec![
+                entry(&[2 << 2 | 0x3, 2, 1, 3, 8 << 1]),
+                entry(&[5 << 2, 6 << 1]),
+            ],
+        )]);
+        let pe = PeFile::from_bytes(&image).unwrap();
+        let binary = NativeAotBinary::from_pe(pe, rtr_va).unwrap();
+
+        assert_eq!(
+            binary.inlining_info().unwrap(),
+            vec![
+                InliningRecord {
+                    inlinee: InlineMethod::IlBody(2),
+                    inliners: vec![InlineMethod::IlBody(1), InlineMethod::IlBody(4), local(8),],
+                },
+                InliningRecord {
+                    inlinee: local(5),
+                    inliners: vec![local(6)],
+                },
+            ]
+        );
+        assert_eq!(
+            binary.inlinees_of(InlineMethod::IlBody(4)).unwrap(),
+            vec![InlineMethod::IlBody(2)]
+        );
+    }
+}
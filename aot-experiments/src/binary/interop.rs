@@ -0,0 +1,129 @@
+use log::debug;
+use pelite::pe64::Va;
+
+use crate::{
+    binary::{NativeAotBinary, headers::rtr::ReflectionMapBlob},
+    error::Result,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarshallingStubKind {
+    StructMarshal,
+    StructUnmarshal,
+    StructCleanup,
+    DelegateOpenStatic,
+    DelegateClosed,
+    DelegateForwardCreation,
+}
+
+impl MarshallingStubKind {
+    /// Suffix used when naming the stub after the type it belongs to
+    pub fn suffix(self) -> &'static str {
+        match self {
+            Self::StructMarshal => "StructMarshal",
+            Self::StructUnmarshal => "StructUnmarshal",
+            Self::StructCleanup => "StructCleanup",
+            Self::DelegateOpenStatic => "DelegateMarshalOpenStatic",
+            Self::DelegateClosed => "DelegateMarshal",
+            Self::DelegateForwardCreation => "DelegateCreate",
+        }
+    }
+}
+
+/// A generated interop thunk, belonging to a struct or delegate type
+#[derive(Debug, Clone, Copy)]
+pub struct MarshallingStub {
+    pub kind: MarshallingStubKind,
+    /// MethodTable of the struct or delegate
+    pub declaring_type: Va,
+    pub address: Va,
+}
+
+impl<'a> NativeAotBinary<'a> {
+    const HAS_MARSHALLING_DATA: u32 = 0x01;
+
+    /// Parses the StructMarshallingStubMap, returning nothing if the image doesn't have one
+    pub fn struct_marshalling_stubs(&self) -> Result<Vec<MarshallingStub>> {
+        const KINDS: [MarshallingStubKind; 3] = [
+            MarshallingStubKind::StructMarshal,
+            MarshallingStubKind::StructUnmarshal,
+            MarshallingStubKind::StructCleanup,
+        ];
+
+        let (Some(table), Some(fixups)) = (
+            self.rtr
+                .blob_hashtable(ReflectionMapBlob::StructMarshallingStubMap),
+            self.rtr.common_fixups_table(),
+        ) else {
+            return Ok(Vec::new());
+        };
+
+        let mut stubs = Vec::new();
+
+        for mut parser in table.enumerate_all()? {
+            // StructType + InteropDataFlags + (MarshallingThunk + UnmarshallingThunk + CleanupThunk)
+            let Some(declaring_type) = fixups.get_va_from_index(parser.get_unsigned()?) else {
+                continue;
+            };
+
+            let flags = parser.get_unsigned()?;
+            if flags & Self::HAS_MARSHALLING_DATA == 0 {
+                continue;
+            }
+
+            for kind in KINDS {
+                if let Some(address) = fixups.get_va_from_index(parser.get_unsigned()?) {
+                    stubs.push(MarshallingStub {
+                        kind,
+                        declaring_type,
+                        address,
+                    });
+                }
+            }
+        }
+
+        debug!("StructMarshallingStubMap has {} stubs", stubs.len());
+
+        Ok(stubs)
+    }
+
+    /// Parses the DelegateMarshallingStubMap, returning nothing if the image doesn't have one
+    pub fn delegate_marshalling_stubs(&self) -> Result<Vec<MarshallingStub>> {
+        const KINDS: [MarshallingStubKind; 3] = [
+            MarshallingStubKind::DelegateOpenStatic,
+            MarshallingStubKind::DelegateClosed,
+            MarshallingStubKind::DelegateForwardCreation,
+        ];
+
+        let (Some(table), Some(fixups)) = (
+            self.rtr
+                .blob_hashtable(ReflectionMapBlob::DelegateMarshallingStubMap),
+            self.rtr.common_fixups_table(),
+        ) else {
+            return Ok(Vec::new());
+        };
+
+        let mut stubs = Vec::new();
+
+        for mut parser in table.enumerate_all()? {
+            // DelegateType + OpenStaticStub + ClosedStub + ForwardDelegateCreationStub
+            let Some(declaring_type) = fixups.get_va_from_index(parser.get_unsigned()?) else {
+                continue;
+            };
+
+            for kind in KINDS {
+                if let Some(address) = fixups.get_va_from_index(parser.get_unsigned()?) {
+                    stubs.push(MarshallingStub {
+                        kind,
+                        declaring_type,
+                        address,
+                    });
+                }
+            }
+        }
+
+        debug!("DelegateMarshallingStubMap has {} stubs", stubs.len());
+
+        Ok(stubs)
+    }
+}
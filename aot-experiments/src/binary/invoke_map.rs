@@ -0,0 +1,303 @@
+use std::collections::HashMap;
+
+use pelite::pe64::Va;
+
+use crate::{
+    binary::{NativeAotBinary, headers::rtr::ReflectionMapBlob},
+    embedded_meta::handles::{BaseHandle, HandleType, MethodHandle},
+    error::{AotError, Result},
+    native_format::{
+        hashtable::NativeHashtable, parser::NativeParser, ref_table::ExternalReferencesTable,
+    },
+};
+
+#[derive(Debug, Clone)]
+pub struct InvokeMapEntry {
+    pub flags: u32,
+    /// `None` for methods that are identified by a native layout signature instead of metadata
+    pub method: Option<MethodHandle>,
+    pub declaring_type: Option<Va>,
+    pub entrypoint: Option<Va>,
+    /// MethodTables of the type arguments, for instantiations of generic methods
+    pub generic_arguments: Vec<Va>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenericInstantiation {
+    pub generic_arguments: Vec<Va>,
+    pub entrypoint: Va,
+}
+
+impl InvokeMapEntry {
+    pub fn is_generic_instantiation(&self) -> bool {
+        self.flags & InvokeMap::IS_GENERIC_METHOD != 0
+    }
+}
+
+/// The InvokeMap blob, which maps reflectable methods to their compiled code
+///
+/// Entries are keyed by the hashcode of the declaring type's MethodTable.
+#[derive(Clone, Copy)]
+pub struct InvokeMap<'a> {
+    table: NativeHashtable<'a>,
+    fixups: ExternalReferencesTable<'a>,
+}
+
+impl<'a> InvokeMap<'a> {
+    pub const HAS_VIRTUAL_INVOKE: u32 = 0x01;
+    pub const IS_GENERIC_METHOD: u32 = 0x02;
+    pub const HAS_METADATA_HANDLE: u32 = 0x04;
+    pub const IS_DEFAULT_CONSTRUCTOR: u32 = 0x08;
+    pub const REQUIRES_INST_ARG: u32 = 0x10;
+    pub const HAS_ENTRYPOINT: u32 = 0x20;
+
+    pub fn new(table: NativeHashtable<'a>, fixups: ExternalReferencesTable<'a>) -> Self {
+        Self { table, fixups }
+    }
+
+    fn parse_entry(&self, mut parser: NativeParser<'a>) -> Result<InvokeMapEntry> {
+        // Flags + MethodHandle or NameAndSignature + DeclaringType + Entrypoint + GenericArguments
+        let flags = parser.get_unsigned()?;
+
+        let method_or_signature = parser.get_unsigned()?;
+        let method = if flags & Self::HAS_METADATA_HANDLE != 0 {
+            BaseHandle::from_offset(HandleType::Method, method_or_signature)
+                .to_handle::<MethodHandle>()
+                .ok()
+        } else {
+            None
+        };
+
+        let declaring_type = self.fixups.get_va_from_index(parser.get_unsigned()?);
+
+        let entrypoint = if flags & Self::HAS_ENTRYPOINT != 0 {
+            self.fixups.get_va_from_index(parser.get_unsigned()?)
+        } else {
+            None
+        };
+
+        let mut generic_arguments = Vec::new();
+        if flags & Self::IS_GENERIC_METHOD != 0 {
+            for _ in 0..parser.get_sequence_count()? {
+                let Some(va) = self.fixups.get_va_from_index(parser.get_unsigned()?) else {
+                    return Err(AotError::BadImage);
+                };
+
+                generic_arguments.push(va);
+            }
+        }
+
+        Ok(InvokeMapEntry {
+            flags,
+            method,
+            declaring_type,
+            entrypoint,
+            generic_arguments,
+        })
+    }
+
+    pub fn entries(&self) -> Result<impl Iterator<Item = Result<InvokeMapEntry>> + 'a> {
+        let map = *self;
+
+        Ok(self
+            .table
+            .enumerate_all()?
+            .map(move |parser| map.parse_entry(parser)))
+    }
+
+    /// Maps every reflectable method with an entrypoint to its address
+    ///
+    /// Instantiations of generic methods are left out, see [`Self::generic_instantiations`].
+    pub fn method_pointers(&self) -> Result<HashMap<MethodHandle, Va>> {
+        let mut method_ptrs = HashMap::new();
+
+        for entry in self.entries()? {
+            let entry = entry?;
+
+            if entry.is_generic_instantiation() {
+                continue;
+            }
+
+            let (Some(method), Some(va)) = (entry.method, entry.entrypoint) else {
+                continue;
+            };
+
+            method_ptrs.insert(method, va);
+        }
+
+        Ok(method_ptrs)
+    }
+
+    /// Maps generic method definitions to the addresses of their instantiations and the type arguments of each
+    pub fn generic_instantiations(
+        &self,
+    ) -> Result<HashMap<MethodHandle, Vec<GenericInstantiation>>> {
+        let mut instantiations = HashMap::<_, Vec<_>>::new();
+
+        for entry in self.entries()? {
+            let entry = entry?;
+
+            if !entry.is_generic_instantiation() {
+                continue;
+            }
+
+            let (Some(method), Some(entrypoint)) = (entry.method, entry.entrypoint) else {
+                continue;
+            };
+
+            instantiations
+                .entry(method)
+                .or_default()
+                .push(GenericInstantiation {
+                    generic_arguments: entry.generic_arguments,
+                    entrypoint,
+                });
+        }
+
+        Ok(instantiations)
+    }
+
+    /// Looks up the address of a single method, without building a map of every method in the image
+    pub fn lookup(&self, declaring_type_hashcode: u32, method: MethodHandle) -> Result<Option<Va>> {
+        for parser in self.table.lookup(declaring_type_hashcode as i32)? {
+            let entry = self.parse_entry(parser)?;
+
+            if entry.method == Some(method) && !entry.is_generic_instantiation() {
+                return Ok(entry.entrypoint);
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl<'a> NativeAotBinary<'a> {
+    pub fn invoke_map(&self) -> Result<InvokeMap<'a>> {
+        let Some(table) = self.rtr.blob_hashtable(ReflectionMapBlob::InvokeMap) else {
+            return Err(AotError::MissingTable("an invoke table"));
+        };
+
+        let Some(fixups) = self.rtr.common_fixups_table() else {
+            return Err(AotError::MissingTable("a common fixups table"));
+        };
+
+        Ok(InvokeMap::new(table, fixups))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pelite::pe64::PeFile;
+
+    use super::{GenericInstantiation, InvokeMap};
+    use crate::{
+        embedded_meta::handles::{BaseHandle, HandleType, MethodHandle},
+        native_format::{
+            View, hashtable::NativeHashtable, parser::NativeParser, reader::NativeReader,
+            ref_table::ExternalReferencesTable,
+        },
+        test_utils::{IMAGE_BASE, PeBuilder, encode_hashtable, encode_unsigned},
+    };
+
+    const TYPE_A: u32 = 0x1234_5678;
+    const TYPE_B: u32 = 0x9ABC_DEF0;
+
+    // (declaring type, method offset, entrypoint)
+    const METHODS: &[(u32, u32, Option<u64>)] = &[
+        (TYPE_A, 10, Some(IMAGE_BASE + 0x5000)),
+        (TYPE_A, 20, Some(IMAGE_BASE + 0x5100)),
+        (TYPE_A, 25, None),
+        (TYPE_B, 30, Some(IMAGE_BASE + 0x5200)),
+    ];
+
+    #[test]
+    fn lookup_matches_prebuilt_method_pointers() {
+        // Fixups: both declaring types, followed by every entrypoint
+        let mut targets = vec![IMAGE_BASE + 0x6000, IMAGE_BASE + 0x6100];
+        let mut entries = Vec::new();
+
+        for &(hashcode, offset, entrypoint) in METHODS {
+            let mut entry = Vec::new();
+            let flags = if entrypoint.is_some() {
+                InvokeMap::HAS_METADATA_HANDLE | InvokeMap::HAS_ENTRYPOINT
+            } else {
+                InvokeMap::HAS_METADATA_HANDLE
+            };
+
+            encode_unsigned(&mut entry, flags);
+            encode_unsigned(&mut entry, offset);
+            encode_unsigned(&mut entry, if hashcode == TYPE_A { 0 } else { 1 });
+
+            if let Some(va) = entrypoint {
+                encode_unsigned(&mut entry, targets.len() as u32);
+                targets.push(va);
+            }
+
+            entries.push((hashcode, entry));
+        }
+
+        // An instantiation of the first method over both types, which must not replace its definition
+        let mut entry = Vec::new();
+        encode_unsigned(
+            &mut entry,
+            InvokeMap::HAS_METADATA_HANDLE
+                | InvokeMap::HAS_ENTRYPOINT
+                | InvokeMap::IS_GENERIC_METHOD,
+        );
+        encode_unsigned(&mut entry, METHODS[0].1);
+        encode_unsigned(&mut entry, 0);
+        encode_unsigned(&mut entry, targets.len() as u32);
+        encode_unsigned(&mut entry, 2);
+        encode_unsigned(&mut entry, 0);
+        encode_unsigned(&mut entry, 1);
+        targets.push(IMAGE_BASE + 0x5300);
+        entries.push((TYPE_A, entry));
+
+        let mut builder = PeBuilder::new();
+        let fixups_va = IMAGE_BASE + builder.next_rva() as u64;
+        let fixups = targets
+            .iter()
+            .enumerate()
+            .flat_map(|(index, &target)| {
+                ((target as i64 - (fixups_va + 4 * index as u64) as i64) as i32).to_le_bytes()
+            })
+            .collect::<Vec<_>>();
+        builder.section(".rdata", fixups);
+
+        let hashtable = encode_hashtable(&entries);
+        let image = builder.build();
+        let pe = PeFile::from_bytes(&image).unwrap();
+
+        let table =
+            NativeHashtable::new(NativeParser::new(NativeReader::new(&hashtable).unwrap(), 0))
+                .unwrap();
+        let fixups =
+            ExternalReferencesTable::new(View::new(pe, fixups_va), 4 * targets.len() as u64);
+        let map = InvokeMap::new(table, fixups);
+
+        let prebuilt = map.method_pointers().unwrap();
+        assert_eq!(prebuilt.len(), 3);
+
+        for &(hashcode, offset, entrypoint) in METHODS {
+            let method = BaseHandle::from_offset(HandleType::Method, offset)
+                .to_handle::<MethodHandle>()
+                .unwrap();
+
+            let looked_up = map.lookup(hashcode, method).unwrap();
+            assert_eq!(looked_up, prebuilt.get(&method).copied());
+            assert_eq!(looked_up, entrypoint);
+        }
+
+        let instantiations = map.generic_instantiations().unwrap();
+        let method = BaseHandle::from_offset(HandleType::Method, METHODS[0].1)
+            .to_handle::<MethodHandle>()
+            .unwrap();
+        assert_eq!(
+            instantiations[&method],
+            vec![GenericInstantiation {
+                generic_arguments: vec![IMAGE_BASE + 0x6000, IMAGE_BASE + 0x6100],
+                entrypoint: IMAGE_BASE + 0x5300,
+            }]
+        );
+    }
+}
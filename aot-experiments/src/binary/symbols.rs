@@ -0,0 +1,207 @@
+//! Naming arbitrary addresses after the closest function or MethodTable before them
+
+use std::{collections::HashMap, ops::Range};
+
+use log::{debug, warn};
+use pelite::pe64::{Pe, Va};
+
+use crate::{
+    binary::{NativeAotBinary, headers::mt::MethodTable},
+    error::{AotError, Result},
+    native_format::View,
+};
+
+/// The named functions and MethodTables of an image, sorted by their start address
+#[derive(Debug, Clone, Default)]
+pub struct SymbolIndex {
+    symbols: Vec<(Range<Va>, String)>,
+}
+
+impl SymbolIndex {
+    /// Sorts the symbols, keeping the first name of every address
+    pub fn new(symbols: impl IntoIterator<Item = (Range<Va>, String)>) -> Self {
+        let mut symbols = symbols.into_iter().collect::<Vec<_>>();
+
+        symbols.sort_by_key(|(range, _)| range.start);
+        symbols.dedup_by_key(|(range, _)| range.start);
+
+        Self { symbols }
+    }
+
+    /// The symbol with the greatest start at or before the address, and how far past its start the address is
+    ///
+    /// Addresses past the end of that symbol aren't named.
+    pub fn lookup(&self, va: Va) -> Option<(&str, u64)> {
+        let index = self.symbols.partition_point(|(range, _)| range.start <= va);
+        let (range, name) = self.symbols.get(index.checked_sub(1)?)?;
+
+        range
+            .contains(&va)
+            .then(|| (name.as_str(), va - range.start))
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+}
+
+impl<'a> NativeAotBinary<'a> {
+    /// Names the compiled methods by their address, using the InvokeMap
+    pub fn method_names_by_address(&self) -> Result<HashMap<Va, String>> {
+        let Some(metadata) = self.rtr.metadata_result()? else {
+            return Err(AotError::MissingTable("a metadata section"));
+        };
+
+        let mut method_names = HashMap::new();
+
+        for scope in metadata
+            .header()
+            .scope_definitions()
+            .iter()?
+            .flatten()
+            .flat_map(|hdl| hdl.to_data(metadata))
+        {
+            let mut types = scope.get_all_types()?;
+            match scope.module_type() {
+                Ok(module) => types.extend(module),
+                Err(why) => warn!("Skipping the <Module> type: {why}"),
+            }
+
+            for typ in types {
+                let Ok(iter) = typ.methods.iter() else {
+                    continue;
+                };
+
+                let type_name = typ.get_full_name_with_generics()?;
+                for method in iter.flatten().flat_map(|hdl| hdl.to_data(metadata)) {
+                    method_names.insert(
+                        method.handle(),
+                        format!("{type_name}.{}", method.name.to_data(metadata)?.value),
+                    );
+                }
+            }
+        }
+
+        let addresses = match self.invoke_map().and_then(|map| map.method_pointers()) {
+            Ok(method_ptrs) => method_ptrs,
+            Err(why) => {
+                warn!("Unable to read the InvokeMap: {why}");
+                HashMap::new()
+            }
+        };
+
+        Ok(addresses
+            .into_iter()
+            .filter_map(|(handle, va)| Some((va, method_names.get(&handle)?.clone())))
+            .collect())
+    }
+
+    /// The index of the named functions and of the MethodTables in the TypeMap, built on first use
+    pub fn symbol_index(&self) -> Result<&SymbolIndex> {
+        if let Some(index) = self.symbols.get() {
+            return Ok(index);
+        }
+
+        let functions = self.runtime_functions().unwrap_or_default();
+        let section_end = |va: Va| {
+            let rva = self.pe.va_to_rva(va).ok()?;
+            let section = self.pe.section_headers().by_rva(rva)?;
+
+            self.pe.rva_to_va(section.virtual_range().end).ok()
+        };
+
+        // Functions end with their RUNTIME_FUNCTION or, when they're missing from the table, with the section
+        let mut symbols = Vec::new();
+        for (va, name) in self.method_names_by_address()? {
+            let end = self.pe.va_to_rva(va).ok().and_then(|rva| {
+                let index = functions
+                    .binary_search_by_key(&rva, |function| function.BeginAddress)
+                    .ok()?;
+
+                self.pe.rva_to_va(functions[index].EndAddress).ok()
+            });
+
+            if let Some(end) = end.or_else(|| section_end(va)) {
+                symbols.push((va..end, name));
+            }
+        }
+        // MethodTables end with their interface map, so the padding after a table isn't attributed to it
+        for (va, name) in self.type_names()? {
+            if let Ok(mt) = MethodTable::parse(&mut View::new(self.pe, va)) {
+                symbols.push((va..va + mt.size(), name));
+            }
+        }
+
+        let index = SymbolIndex::new(symbols);
+        debug!("Symbol index has {} symbols", index.len());
+
+        Ok(self.symbols.get_or_init(|| index))
+    }
+
+    /// Names an address after the closest function or MethodTable at or before it, together with the offset into it
+    ///
+    /// Returns `None` when no symbol precedes the address, or when the image lacks the tables to name them.
+    pub fn symbolize(&self, va: Va) -> Option<(String, u64)> {
+        let (name, delta) = self.symbol_index().ok()?.lookup(va)?;
+
+        Some((name.to_string(), delta))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pelite::pe64::PeFile;
+
+    use crate::{
+        binary::{NativeAotBinary, ScanSections, symbols::SymbolIndex},
+        test_utils::{IMAGE_BASE, ida_fixture_image},
+    };
+
+    #[test]
+    fn lookup_finds_the_closest_preceding_symbol() {
+        let index = SymbolIndex::new([
+            (0x2000..0x2020, "B".to_string()),
+            (0x1000..0x2000, "A".to_string()),
+            (0x1000..0x1010, "Duplicate".to_string()),
+        ]);
+
+        assert_eq!(index.len(), 2);
+        assert_eq!(index.lookup(0xfff), None);
+        assert_eq!(index.lookup(0x1000), Some(("A", 0)));
+        assert_eq!(index.lookup(0x1fff), Some(("A", 0xfff)));
+        assert_eq!(index.lookup(0x2010), Some(("B", 0x10)));
+        assert_eq!(index.lookup(0x2020), None);
+    }
+
+    #[test]
+    fn symbolize_names_functions_and_method_tables() {
+        let image = ida_fixture_image();
+        let pe = PeFile::from_bytes(&image).unwrap();
+        let binary = NativeAotBinary::load_pe(pe, ScanSections::default()).unwrap();
+
+        // `Game.Player.Jump` is the last function in `.text`, the MethodTable of `Game.Player` the last in `.data`
+        let text_va = IMAGE_BASE + 0x1000;
+        let player_mt = IMAGE_BASE + 0x2000 + 0x60;
+
+        assert_eq!(binary.symbolize(text_va), None);
+        assert_eq!(
+            binary.symbolize(text_va + 0x34),
+            Some(("Game.Player.Jump".to_string(), 4))
+        );
+        assert_eq!(
+            binary.symbolize(player_mt + 0x18),
+            Some(("Game.Player".to_string(), 0x18))
+        );
+
+        // The header and the 3 vtable slots
+        assert_eq!(
+            binary.symbolize(player_mt + 0x2f),
+            Some(("Game.Player".to_string(), 0x2f))
+        );
+        assert_eq!(binary.symbolize(player_mt + 0x30), None);
+    }
+}
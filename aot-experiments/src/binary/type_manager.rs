@@ -0,0 +1,55 @@
+//! The TypeManager, the runtime's bookkeeping of a module
+//!
+//! It ties the ReadyToRun header of the module to its static regions, and is allocated when the module registers with
+//! the runtime. The image only holds the cell its handle is stored in, the TypeManagerIndirection section:
+//!
+//! ```text
+//! u64 type_manager    // TypeManagerHandle, zero until the module is registered
+//! ```
+//!
+//! Every MethodTable points to the cell, which is how the runtime finds the module a type belongs to.
+
+use binary_rw::{BinaryReader, Endian};
+use pelite::pe64::Va;
+
+use crate::binary::{NativeAotBinary, headers::rtr::ReadyToRunSectionType};
+
+/// ILC's symbol name of the TypeManagerIndirection cell
+pub const TYPE_MANAGER_INDIRECTION_NAME: &str = "__typemanager_indirection";
+
+/// The TypeManager of the module, see [`NativeAotBinary::type_manager`]
+///
+/// Besides the cell, the fields are the ones the runtime fills the TypeManager with when the module is registered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypeManager {
+    /// Address of the TypeManagerIndirection cell
+    pub indirection: Va,
+    /// Value of the cell, `None` when the cell has no file backing
+    pub handle: Option<u64>,
+    /// The ReadyToRun header of the module
+    pub header: Va,
+    /// Start of the region holding the GC static bases of the module's types
+    pub gc_static_region: Option<Va>,
+    /// Start of the region holding the thread static bases of the module's types
+    pub thread_static_region: Option<Va>,
+}
+
+impl<'a> NativeAotBinary<'a> {
+    /// Locates the TypeManager of the module through the TypeManagerIndirection section
+    pub fn type_manager(&self) -> Option<TypeManager> {
+        let section = self
+            .rtr
+            .section(ReadyToRunSectionType::TypeManagerIndirection)?;
+        let region = |section_type| self.rtr.section(section_type).map(|s| s.start.va());
+
+        Some(TypeManager {
+            indirection: section.start.va(),
+            handle: BinaryReader::new(&mut { section.start }, Endian::Little)
+                .read_u64()
+                .ok(),
+            header: self.rtr.address,
+            gc_static_region: region(ReadyToRunSectionType::GCStaticRegion),
+            thread_static_region: region(ReadyToRunSectionType::ThreadStaticRegion),
+        })
+    }
+}
@@ -0,0 +1,305 @@
+use std::collections::{HashMap, HashSet};
+
+use log::debug;
+use pelite::pe64::Va;
+
+use crate::{
+    binary::{
+        NativeAotBinary,
+        headers::{mt::MethodTable, rtr::ReflectionMapBlob},
+    },
+    embedded_meta::{
+        TypeDefinition,
+        handles::{BaseHandle, TypeDefinitionHandle},
+    },
+    error::{AotError, Result},
+    native_format::{View, hashtable::NativeHashtable, ref_table::ExternalReferencesTable},
+};
+
+/// A group of the `ExternalTypeMap`, built from the `TypeMapAttribute`s of a single type map group
+#[derive(Debug, Clone)]
+pub struct ExternalTypeMapGroup {
+    /// The MethodTable of the group type
+    pub group: Va,
+
+    /// The external names, e.g. of Java or Objective-C classes, and the MethodTables they map to
+    pub types: Vec<(String, Va)>,
+}
+
+/// A group of the `ProxyTypeMap`, built from the `TypeMapAssociationAttribute`s of a single type map group
+#[derive(Debug, Clone)]
+pub struct ProxyTypeMapGroup {
+    /// The MethodTable of the group type
+    pub group: Va,
+
+    /// The MethodTables of the source types and of their proxies
+    pub proxies: Vec<(Va, Va)>,
+}
+
+impl<'a> NativeAotBinary<'a> {
+    /// Maps the MethodTables listed in the TypeMap to the type definitions they were created from
+    pub fn type_map(&self) -> Result<HashMap<Va, TypeDefinitionHandle>> {
+        let Some(table) = self.rtr.blob_hashtable(ReflectionMapBlob::TypeMap) else {
+            return Err(AotError::MissingTable("a TypeMap"));
+        };
+
+        let Some(fixups) = self.rtr.common_fixups_table() else {
+            return Err(AotError::MissingTable("a common fixups table"));
+        };
+
+        let mut types = HashMap::new();
+
+        for mut parser in table.enumerate_all()? {
+            // MethodTable + TypeDefinition
+            let Some(mt_va) = fixups.get_va_from_index(parser.get_unsigned()?) else {
+                continue;
+            };
+
+            let Ok(handle) =
+                BaseHandle::from_raw(parser.get_unsigned()?).to_handle::<TypeDefinitionHandle>()
+            else {
+                continue;
+            };
+
+            types.insert(mt_va, handle);
+        }
+
+        debug!("TypeMap has {} types", types.len());
+
+        Ok(types)
+    }
+
+    /// Maps the MethodTables listed in the TypeMap to the full names of their types, see [`Self::type_map`]
+    pub fn type_names(&self) -> Result<HashMap<Va, String>> {
+        let Some(metadata) = self.rtr.metadata_result()? else {
+            return Err(AotError::MissingTable("a metadata section"));
+        };
+
+        let mut names = HashMap::new();
+
+        for (va, handle) in self.type_map()? {
+            if let Ok(name) = handle
+                .to_data(metadata)
+                .and_then(|typ| typ.get_full_name_with_generics())
+            {
+                names.insert(va, name);
+            }
+        }
+
+        Ok(names)
+    }
+
+    /// Finds the type definition a MethodTable was created from, by looking its hashcode up in the TypeMap
+    ///
+    /// The reverse of [`Self::find_method_table`], for the callers that only need a few tables named.
+    pub fn find_type_definition(
+        &self,
+        mt: &MethodTable<'_>,
+    ) -> Result<Option<TypeDefinitionHandle>> {
+        let Some(table) = self.rtr.blob_hashtable(ReflectionMapBlob::TypeMap) else {
+            return Err(AotError::MissingTable("a TypeMap"));
+        };
+
+        let Some(fixups) = self.rtr.common_fixups_table() else {
+            return Err(AotError::MissingTable("a common fixups table"));
+        };
+
+        for mut parser in table.lookup(mt.hashcode as i32)? {
+            let mt_va = fixups.get_va_from_index(parser.get_unsigned()?);
+            let handle = BaseHandle::from_raw(parser.get_unsigned()?);
+
+            if mt_va == Some(mt.view.va()) {
+                return Ok(handle.to_handle::<TypeDefinitionHandle>().ok());
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Like [`Self::type_names`], but only names the MethodTables at `vas`, each by [`Self::find_type_definition`]
+    ///
+    /// Constructed generic types aren't in the TypeMap, so their generic definition and type arguments are named
+    /// instead, which is what [`MethodTable::generic_instance_name`] builds their name from.
+    pub fn type_names_of(&self, vas: impl IntoIterator<Item = Va>) -> Result<HashMap<Va, String>> {
+        let Some(metadata) = self.rtr.metadata_result()? else {
+            return Err(AotError::MissingTable("a metadata section"));
+        };
+
+        let mut names = HashMap::new();
+        let mut visited = HashSet::new();
+        let mut agenda = vas.into_iter().collect::<Vec<_>>();
+
+        while let Some(va) = agenda.pop() {
+            if !visited.insert(va) {
+                continue;
+            }
+
+            let Ok(mt) = MethodTable::parse(&mut View::new(self.pe, va)) else {
+                continue;
+            };
+
+            let name = self.find_type_definition(&mt)?.and_then(|handle| {
+                handle
+                    .to_data(metadata)
+                    .and_then(|typ| typ.get_full_name_with_generics())
+                    .ok()
+            });
+
+            match name {
+                Some(name) => {
+                    names.insert(va, name);
+                }
+                None => {
+                    agenda.extend(mt.generic_definition());
+                    agenda.extend(
+                        mt.generic_arguments()
+                            .into_iter()
+                            .flatten()
+                            .map(|arg| arg.view.va()),
+                    );
+                }
+            }
+        }
+
+        Ok(names)
+    }
+
+    /// The type definitions with a MethodTable in the TypeMap, ordered by handle, for cross-checking the namespace walk
+    /// of `get_all_types`
+    ///
+    /// crossgen2 lists the types of an R2R image in the AvailableTypes section, but ILC never writes it, so the TypeMap
+    /// is the closest a NativeAOT image has. Unlike the namespace walk, it only lists the types that were compiled.
+    pub fn available_types(&self) -> Result<Vec<TypeDefinition<'a>>> {
+        let Some(metadata) = self.rtr.metadata_result()? else {
+            return Err(AotError::MissingTable("a metadata section"));
+        };
+
+        let mut handles = self.type_map()?.into_values().collect::<Vec<_>>();
+        handles.sort();
+        handles.dedup();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.to_data(metadata))
+            .collect()
+    }
+
+    /// Finds the MethodTable of a type definition, by looking its hashcode up in the TypeMap
+    pub fn find_method_table(&self, typ: &TypeDefinition<'_>) -> Result<Option<Va>> {
+        let Some(table) = self.rtr.blob_hashtable(ReflectionMapBlob::TypeMap) else {
+            return Err(AotError::MissingTable("a TypeMap"));
+        };
+
+        let Some(fixups) = self.rtr.common_fixups_table() else {
+            return Err(AotError::MissingTable("a common fixups table"));
+        };
+
+        for mut parser in table.lookup(typ.runtime_hashcode()?)? {
+            let mt_va = fixups.get_va_from_index(parser.get_unsigned()?);
+
+            let Ok(handle) =
+                BaseHandle::from_raw(parser.get_unsigned()?).to_handle::<TypeDefinitionHandle>()
+            else {
+                continue;
+            };
+
+            if handle == typ.handle() {
+                return Ok(mt_va);
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Reads the groups of the `ExternalTypeMap`, which only exists in images built with .NET 10 or newer
+    ///
+    /// Returns no groups for images without the blob.
+    pub fn external_type_map(&self) -> Result<Vec<ExternalTypeMapGroup>> {
+        let mut groups = vec![];
+
+        self.read_type_map_groups(ReflectionMapBlob::ExternalTypeMap, |group, table| {
+            let mut types = vec![];
+
+            // External name + MethodTable
+            for mut parser in table.enumerate_all()? {
+                let name = parser.get_string()?;
+
+                if let Some(mt_va) = self.fixups()?.get_va_from_index(parser.get_unsigned()?) {
+                    types.push((name, mt_va));
+                }
+            }
+
+            groups.push(ExternalTypeMapGroup { group, types });
+            Ok(())
+        })?;
+
+        Ok(groups)
+    }
+
+    /// Reads the groups of the `ProxyTypeMap`, which only exists in images built with .NET 10 or newer
+    ///
+    /// Returns no groups for images without the blob.
+    pub fn proxy_type_map(&self) -> Result<Vec<ProxyTypeMapGroup>> {
+        let mut groups = vec![];
+
+        self.read_type_map_groups(ReflectionMapBlob::ProxyTypeMap, |group, table| {
+            let fixups = self.fixups()?;
+            let mut proxies = vec![];
+
+            // Source MethodTable + proxy MethodTable
+            for mut parser in table.enumerate_all()? {
+                let source = fixups.get_va_from_index(parser.get_unsigned()?);
+                let proxy = fixups.get_va_from_index(parser.get_unsigned()?);
+
+                if let (Some(source), Some(proxy)) = (source, proxy) {
+                    proxies.push((source, proxy));
+                }
+            }
+
+            groups.push(ProxyTypeMapGroup { group, proxies });
+            Ok(())
+        })?;
+
+        Ok(groups)
+    }
+
+    /// Both type map blobs are hashtables of groups, where every group points to a hashtable of its own entries
+    fn read_type_map_groups(
+        &self,
+        blob: ReflectionMapBlob,
+        mut read_group: impl FnMut(Va, NativeHashtable<'a>) -> Result<()>,
+    ) -> Result<()> {
+        let Some(table) = self.rtr.blob_hashtable(blob) else {
+            return Ok(());
+        };
+
+        let fixups = self.fixups()?;
+
+        for mut parser in table.enumerate_all()? {
+            let Some(group) = fixups.get_va_from_index(parser.get_unsigned()?) else {
+                continue;
+            };
+
+            // Invalid groups only point to the stub throwing the exception explaining why
+            if parser.get_unsigned()? != 1 {
+                debug!("{blob:?} group at {group:#x} is invalid");
+                continue;
+            }
+
+            read_group(
+                group,
+                NativeHashtable::new(parser.get_parser_from_rel_offset()?)?,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn fixups(&self) -> Result<ExternalReferencesTable<'a>> {
+        let Some(fixups) = self.rtr.common_fixups_table() else {
+            return Err(AotError::MissingTable("a common fixups table"));
+        };
+
+        Ok(fixups)
+    }
+}
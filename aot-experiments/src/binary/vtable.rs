@@ -0,0 +1,166 @@
+//! Mapping the vtable slots of a MethodTable back to the methods in the metadata
+//!
+//! Reflectable virtual methods are listed in the VirtualInvokeMap, keyed by the hashcode of the type declaring them:
+//!
+//! ```text
+//! unsigned containing_type         // Fixup index of the MethodTable of the declaring type
+//! unsigned method                  // Method handle, including the handle type
+//! unsigned parent_hierarchy << 1   // How many base types up the slot was introduced, and whether the method is a
+//!        | is_generic_virtual      // generic virtual method, which is resolved at runtime rather than by slot
+//! unsigned slot
+//! ```
+//!
+//! Slots are indices into the vtable of the declaring type, which starts with the slots of its base types, so they're
+//! valid for any type deriving from it too.
+
+use pelite::pe64::Va;
+
+use crate::{
+    binary::{
+        NativeAotBinary,
+        headers::{mt::MethodTable, rtr::ReflectionMapBlob},
+    },
+    embedded_meta::{
+        Method, TypeDefinition,
+        handles::{BaseHandle, Handle, MethodHandle},
+    },
+    error::Result,
+    native_format::View,
+};
+
+#[derive(Clone)]
+pub struct VtableSlot<'a> {
+    pub slot: u16,
+    /// The code called through this slot
+    pub address: Va,
+    /// `None` when neither the VirtualInvokeMap nor the metadata says which method the slot belongs to
+    pub method: Option<Method<'a>>,
+}
+
+impl<'a> NativeAotBinary<'a> {
+    const GENERIC_VIRTUAL_METHOD: u32 = 0x1;
+
+    /// Pairs every vtable slot of a type's MethodTable with the method it calls
+    ///
+    /// Slots are resolved through the VirtualInvokeMap first. The slots of methods that aren't reflectable are taken
+    /// from [`TypeDefinition::virtual_slots`], but only when it has as many slots as the MethodTable, as the compiler
+    /// drops the slots that are never called. Types without a MethodTable have no slots.
+    pub fn vtable_layout(&self, typ: &TypeDefinition<'a>) -> Result<Vec<VtableSlot<'a>>> {
+        let Some(mt_va) = self.find_method_table(typ)? else {
+            return Ok(Vec::new());
+        };
+
+        let mt = MethodTable::parse(&mut View::new(self.pe, mt_va))?;
+        let mut methods = vec![None; mt.vtable_addresses.len()];
+
+        // Base types first, so overrides replace the method they override
+        let mut hierarchy = typ.base_chain().collect::<Vec<_>>();
+        hierarchy.reverse();
+        hierarchy.push(typ.clone());
+
+        if let (Some(table), Some(fixups), Some(metadata)) = (
+            self.rtr.blob_hashtable(ReflectionMapBlob::VirtualInvokeMap),
+            self.rtr.common_fixups_table(),
+            self.rtr.metadata(),
+        ) {
+            for declaring_type in &hierarchy {
+                let Some(declaring_mt) = self.find_method_table(declaring_type)? else {
+                    continue;
+                };
+
+                for mut parser in table.lookup(declaring_type.runtime_hashcode()?)? {
+                    if fixups.get_va_from_index(parser.get_unsigned()?) != Some(declaring_mt) {
+                        continue;
+                    }
+
+                    // A full handle, with the handle type in the upper 7 bits like in the TypeMap. Only the
+                    // InvokeMap strips its handles down to the offset.
+                    let Ok(method) =
+                        BaseHandle::from_raw(parser.get_unsigned()?).to_handle::<MethodHandle>()
+                    else {
+                        continue;
+                    };
+
+                    if parser.get_unsigned()? & Self::GENERIC_VIRTUAL_METHOD != 0 {
+                        continue;
+                    }
+
+                    if let Some(entry) = methods.get_mut(parser.get_unsigned()? as usize) {
+                        *entry = Some(method.to_data(metadata)?);
+                    }
+                }
+            }
+        }
+
+        if let Some(assigned) = typ.virtual_slots()?
+            && assigned.len() == methods.len()
+        {
+            for (entry, method) in methods.iter_mut().zip(assigned) {
+                entry.get_or_insert(method);
+            }
+        }
+
+        Ok(mt
+            .vtable_addresses
+            .iter()
+            .zip(methods)
+            .enumerate()
+            .map(|(slot, (&address, method))| VtableSlot {
+                slot: slot as u16,
+                address,
+                method,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pelite::pe64::PeFile;
+
+    use crate::{
+        binary::{NativeAotBinary, ScanSections},
+        embedded_meta::{
+            handles::{BaseHandle, Handle, HandleType},
+            hashing::compute_name_hashcode,
+        },
+        test_utils::{encode_hashtable, encode_unsigned, ida_fixture_image_with_sections},
+    };
+
+    const VIRTUAL_INVOKE_MAP: u32 = 300 + 7;
+
+    #[test]
+    fn vtable_slots_are_resolved_through_the_virtual_invoke_map() {
+        // `Game.Player.Jump` in slot 1 of the MethodTable of `Game.Player`, which declares it
+        let image = ida_fixture_image_with_sections(|offsets| {
+            let mut entry = Vec::new();
+            encode_unsigned(&mut entry, 0);
+            encode_unsigned(
+                &mut entry,
+                BaseHandle::from_offset(HandleType::Method, offsets.method).to_value(),
+            );
+            encode_unsigned(&mut entry, 0);
+            encode_unsigned(&mut entry, 1);
+
+            let hashcode = compute_name_hashcode("Game.Player") as u32;
+            vec![(VIRTUAL_INVOKE_MAP, encode_hashtable(&[(hashcode, entry)]))]
+        });
+        let pe = PeFile::from_bytes(&image).unwrap();
+        let binary = NativeAotBinary::load_pe(pe, ScanSections::default()).unwrap();
+        let metadata = binary.rtr_header().metadata().unwrap();
+        let player = metadata.find_type("Game.Player").unwrap().unwrap();
+
+        let slots = binary.vtable_layout(&player).unwrap();
+        let names = slots
+            .iter()
+            .map(|slot| {
+                slot.method
+                    .as_ref()
+                    .map(|method| method.name.to_data(metadata).unwrap().value)
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, [None, Some("Jump".to_string()), None]);
+        assert_eq!(slots[1].slot, 1);
+    }
+}
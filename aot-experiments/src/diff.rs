@@ -0,0 +1,396 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Serialize;
+
+use crate::{
+    binary::headers::rtr::format_guid,
+    model::{FieldModel, MethodModel, TypeModel},
+    style::Style,
+};
+
+#[derive(Serialize, Default, Debug)]
+pub struct DiffReport {
+    /// The manifest assemblies of composite images, see [`match_components`]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub components: Vec<ComponentMatch>,
+    pub added_types: Vec<String>,
+    pub removed_types: Vec<String>,
+    pub changed_types: Vec<TypeChanges>,
+}
+
+#[derive(Serialize, Default, Debug)]
+pub struct TypeChanges {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_type_change: Option<BaseTypeChange>,
+    pub added_methods: Vec<String>,
+    pub removed_methods: Vec<String>,
+    pub signature_changes: Vec<SignatureChange>,
+    /// Methods whose signature is unchanged but moved to a different address, which is just recompilation noise
+    pub rva_changes: Vec<RvaChange>,
+    pub added_fields: Vec<FieldModel>,
+    pub removed_fields: Vec<FieldModel>,
+    pub field_changes: Vec<FieldChange>,
+}
+
+/// A manifest assembly of the old image, and the one of the new image with the same MVID
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct ComponentMatch {
+    pub mvid: String,
+    pub old_index: usize,
+    /// `None` when the new image has no assembly with the MVID, because it was rebuilt or removed
+    pub new_index: Option<usize>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SignatureChange {
+    pub old: String,
+    pub new: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct BaseTypeChange {
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RvaChange {
+    pub method: String,
+    pub old: Option<u32>,
+    pub new: Option<u32>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct FieldChange {
+    pub old: FieldModel,
+    pub new: FieldModel,
+}
+
+impl TypeChanges {
+    fn is_empty(&self) -> bool {
+        self.base_type_change.is_none()
+            && self.added_methods.is_empty()
+            && self.removed_methods.is_empty()
+            && self.signature_changes.is_empty()
+            && self.rva_changes.is_empty()
+            && self.added_fields.is_empty()
+            && self.removed_fields.is_empty()
+            && self.field_changes.is_empty()
+    }
+
+    /// Whether anything other than method addresses changed
+    fn has_layout_changes(&self) -> bool {
+        !(self.base_type_change.is_none()
+            && self.added_methods.is_empty()
+            && self.removed_methods.is_empty()
+            && self.signature_changes.is_empty()
+            && self.added_fields.is_empty()
+            && self.removed_fields.is_empty()
+            && self.field_changes.is_empty())
+    }
+}
+
+/// Compares two type dumps. Types are matched by their full name, methods by their name and signature.
+pub fn diff_types(old: &[TypeModel], new: &[TypeModel]) -> DiffReport {
+    let old_types = by_name(old);
+    let new_types = by_name(new);
+
+    let mut report = DiffReport::default();
+
+    for (&name, &old_type) in &old_types {
+        match new_types.get(name) {
+            Some(&new_type) => {
+                let changes = diff_type(old_type, new_type);
+                if !changes.is_empty() {
+                    report.changed_types.push(changes);
+                }
+            }
+            None => report.removed_types.push(name.to_string()),
+        }
+    }
+
+    report.added_types = new_types
+        .keys()
+        .filter(|name| !old_types.contains_key(*name))
+        .map(|name| name.to_string())
+        .collect();
+
+    report
+}
+
+/// Matches the manifest assemblies of two composite images by their MVIDs, in the order of the old image
+///
+/// Unlike the name and version of an assembly, its MVID only stays the same for the same build of it, so a matched
+/// component is known to be identical in both images.
+pub fn match_components(old: &[[u8; 16]], new: &[[u8; 16]]) -> Vec<ComponentMatch> {
+    old.iter()
+        .enumerate()
+        .map(|(old_index, mvid)| ComponentMatch {
+            mvid: format_guid(mvid),
+            old_index,
+            new_index: new.iter().position(|other| other == mvid),
+        })
+        .collect()
+}
+
+fn by_name(types: &[TypeModel]) -> BTreeMap<&str, &TypeModel> {
+    let mut map = BTreeMap::new();
+
+    for typ in types {
+        map.entry(typ.name.as_str()).or_insert(typ);
+    }
+
+    map
+}
+
+fn diff_type(old: &TypeModel, new: &TypeModel) -> TypeChanges {
+    let mut changes = TypeChanges {
+        name: old.name.clone(),
+        ..Default::default()
+    };
+
+    if old.base_type != new.base_type {
+        changes.base_type_change = Some(BaseTypeChange {
+            old: old.base_type.clone(),
+            new: new.base_type.clone(),
+        });
+    }
+
+    diff_methods(&old.methods, &new.methods, &mut changes);
+    diff_fields(&old.fields, &new.fields, &mut changes);
+
+    changes
+}
+
+fn diff_methods(old: &[MethodModel], new: &[MethodModel], changes: &mut TypeChanges) {
+    let old_methods = old
+        .iter()
+        .map(|method| (method.signature(), method))
+        .collect::<BTreeMap<_, _>>();
+    let new_methods = new
+        .iter()
+        .map(|method| (method.signature(), method))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+
+    for (signature, old_method) in &old_methods {
+        match new_methods.get(signature) {
+            Some(new_method) if new_method.rva != old_method.rva => {
+                changes.rva_changes.push(RvaChange {
+                    method: signature.clone(),
+                    old: old_method.rva,
+                    new: new_method.rva,
+                })
+            }
+            Some(_) => {}
+            None => removed.push(*old_method),
+        }
+    }
+
+    for (signature, new_method) in &new_methods {
+        if !old_methods.contains_key(signature) {
+            added.push(*new_method);
+        }
+    }
+
+    // A method that isn't overloaded on either side and only changed its signature is reported as a change
+    let old_names = count_names(old);
+    let new_names = count_names(new);
+
+    for old_method in removed {
+        let unique = old_names.get(old_method.name.as_str()) == Some(&1)
+            && new_names.get(old_method.name.as_str()) == Some(&1);
+
+        match added
+            .iter()
+            .position(|method| method.name == old_method.name)
+        {
+            Some(index) if unique => {
+                let new_method = added.remove(index);
+
+                changes.signature_changes.push(SignatureChange {
+                    old: old_method.signature(),
+                    new: new_method.signature(),
+                });
+            }
+            _ => changes.removed_methods.push(old_method.signature()),
+        }
+    }
+
+    changes.added_methods = added.iter().map(|method| method.signature()).collect();
+}
+
+fn count_names(methods: &[MethodModel]) -> HashMap<&str, usize> {
+    let mut counts = HashMap::new();
+
+    for method in methods {
+        *counts.entry(method.name.as_str()).or_insert(0) += 1;
+    }
+
+    counts
+}
+
+fn diff_fields(old: &[FieldModel], new: &[FieldModel], changes: &mut TypeChanges) {
+    for old_field in old {
+        match new.iter().find(|field| field.name == old_field.name) {
+            Some(new_field) if new_field != old_field => changes.field_changes.push(FieldChange {
+                old: old_field.clone(),
+                new: new_field.clone(),
+            }),
+            Some(_) => {}
+            None => changes.removed_fields.push(old_field.clone()),
+        }
+    }
+
+    for new_field in new {
+        if !old.iter().any(|field| field.name == new_field.name) {
+            changes.added_fields.push(new_field.clone());
+        }
+    }
+}
+
+pub fn print_report(report: &DiffReport, style: Style) {
+    if !report.components.is_empty() {
+        let rebuilt = report
+            .components
+            .iter()
+            .filter(|component| component.new_index.is_none())
+            .collect::<Vec<_>>();
+
+        println!(
+            "{} of {} components are unchanged",
+            report.components.len() - rebuilt.len(),
+            report.components.len()
+        );
+
+        for component in rebuilt {
+            println!("~ component {} ({})", component.old_index, component.mvid);
+        }
+    }
+
+    for name in &report.added_types {
+        println!("+ {}", style.declaration(name));
+    }
+
+    for name in &report.removed_types {
+        println!("- {}", style.declaration(name));
+    }
+
+    for changes in report
+        .changed_types
+        .iter()
+        .filter(|changes| changes.has_layout_changes())
+    {
+        println!("~ {}", style.declaration(&changes.name));
+
+        if let Some(change) = &changes.base_type_change {
+            println!(
+                "  ~ : {} -> {}",
+                change.old.as_deref().unwrap_or("<none>"),
+                change.new.as_deref().unwrap_or("<none>")
+            );
+        }
+
+        for method in &changes.added_methods {
+            println!("  + {method}");
+        }
+
+        for method in &changes.removed_methods {
+            println!("  - {method}");
+        }
+
+        for change in &changes.signature_changes {
+            println!("  ~ {} -> {}", change.old, change.new);
+        }
+
+        for field in &changes.added_fields {
+            println!("  + {}", format_field(field));
+        }
+
+        for field in &changes.removed_fields {
+            println!("  - {}", format_field(field));
+        }
+
+        for change in &changes.field_changes {
+            println!(
+                "  ~ {} -> {}",
+                format_field(&change.old),
+                format_field(&change.new)
+            );
+        }
+    }
+
+    let rva_changes = report
+        .changed_types
+        .iter()
+        .map(|changes| changes.rva_changes.len())
+        .sum::<usize>();
+
+    if rva_changes == 0 {
+        return;
+    }
+
+    println!();
+    println!(
+        "{}",
+        style.comment(&format!(
+            "// {rva_changes} methods only moved to a different RVA"
+        ))
+    );
+
+    for changes in &report.changed_types {
+        for change in &changes.rva_changes {
+            println!(
+                "  {}::{} {} -> {}",
+                changes.name,
+                change.method,
+                style.address(&format_rva(change.old)),
+                style.address(&format_rva(change.new))
+            );
+        }
+    }
+}
+
+fn format_field(field: &FieldModel) -> String {
+    if field.is_static {
+        format!("static {} {}", field.type_name, field.name)
+    } else {
+        format!("{} {} @ {:#x}", field.type_name, field.name, field.offset)
+    }
+}
+
+fn format_rva(rva: Option<u32>) -> String {
+    rva.map_or_else(|| "<none>".to_string(), |rva| format!("{rva:#x}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diff::{ComponentMatch, match_components};
+
+    #[test]
+    fn components_are_matched_by_mvid() {
+        let (first, second, rebuilt) = ([1; 16], [2; 16], [3; 16]);
+
+        // The new image lists the components in another order
+        let matches = match_components(&[first, second], &[rebuilt, first]);
+
+        assert_eq!(
+            matches,
+            vec![
+                ComponentMatch {
+                    mvid: "01010101-0101-0101-0101-010101010101".to_string(),
+                    old_index: 0,
+                    new_index: Some(1),
+                },
+                ComponentMatch {
+                    mvid: "02020202-0202-0202-0202-020202020202".to_string(),
+                    old_index: 1,
+                    new_index: None,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,155 @@
+use std::fmt;
+
+use sha1::{Digest, Sha1};
+
+use crate::{
+    embedded_meta::{
+        ByteCollection, MetadataReader, ScopeDefinition, ScopeReference,
+        handles::{ConstantStringValueHandle, Handle},
+    },
+    error::Result,
+};
+
+/// The identity of an assembly, see [`ScopeDefinition::assembly_name`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AssemblyName {
+    pub name: String,
+    /// Major, minor, build and revision number
+    pub version: (u16, u16, u16, u16),
+    /// `None` for culture neutral assemblies
+    pub culture: Option<String>,
+    /// `None` for assemblies without a strong name
+    pub public_key_token: Option<[u8; 8]>,
+}
+
+impl fmt::Display for AssemblyName {
+    /// Formats the name like `System.Private.CoreLib, Version=8.0.0.0, Culture=neutral, PublicKeyToken=7cec85d7bea7798e`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (major, minor, build, revision) = self.version;
+
+        write!(
+            f,
+            "{}, Version={major}.{minor}.{build}.{revision}, Culture={}, PublicKeyToken=",
+            self.name,
+            self.culture.as_deref().unwrap_or("neutral")
+        )?;
+
+        match self.public_key_token {
+            Some(token) => token.iter().try_for_each(|byte| write!(f, "{byte:02x}")),
+            None => write!(f, "null"),
+        }
+    }
+}
+
+// AssemblyFlags.PublicKey, set when the public key is stored in full instead of its token
+const PUBLIC_KEY_FLAG: u32 = 0x0001;
+
+impl<'a> ScopeDefinition<'a> {
+    pub fn assembly_name(&self) -> Result<AssemblyName> {
+        read_assembly_name(
+            self.reader,
+            self.flags,
+            self.name,
+            (
+                self.major_version,
+                self.minor_version,
+                self.build_number,
+                self.revision_number,
+            ),
+            self.public_key,
+            self.culture,
+        )
+    }
+}
+
+impl<'a> ScopeReference<'a> {
+    /// The identity of the referenced assembly, as it was recorded when the referencing assembly was compiled
+    pub fn assembly_name(&self) -> Result<AssemblyName> {
+        read_assembly_name(
+            self.reader,
+            self.flags,
+            self.name,
+            (
+                self.major_version,
+                self.minor_version,
+                self.build_number,
+                self.revision_number,
+            ),
+            self.public_key_or_token,
+            self.culture,
+        )
+    }
+}
+
+fn read_assembly_name(
+    reader: MetadataReader<'_>,
+    flags: u32,
+    name: ConstantStringValueHandle,
+    version: (u16, u16, u16, u16),
+    public_key: ByteCollection<'_>,
+    culture: ConstantStringValueHandle,
+) -> Result<AssemblyName> {
+    let culture = if culture.is_nil() {
+        None
+    } else {
+        Some(culture.to_data(reader)?.value).filter(|culture| !culture.is_empty())
+    };
+
+    let public_key = public_key.iter()?.collect::<Result<Vec<_>>>()?;
+
+    let public_key_token = if public_key.is_empty() {
+        None
+    } else if flags & PUBLIC_KEY_FLAG != 0 {
+        Some(public_key_token(&public_key))
+    } else {
+        public_key.try_into().ok()
+    };
+
+    Ok(AssemblyName {
+        name: name.to_data(reader)?.value,
+        version,
+        culture,
+        public_key_token,
+    })
+}
+
+/// The token of a public key is the last 8 bytes of its SHA-1, in reverse
+fn public_key_token(public_key: &[u8]) -> [u8; 8] {
+    let hash = Sha1::digest(public_key);
+    let mut token = [0; 8];
+
+    token.copy_from_slice(&hash[12..]);
+    token.reverse();
+
+    token
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn public_key_token_of_the_ecma_key() {
+        let ecma_key = [0, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0];
+
+        assert_eq!(
+            public_key_token(&ecma_key),
+            [0xb7, 0x7a, 0x5c, 0x56, 0x19, 0x34, 0xe0, 0x89]
+        );
+    }
+
+    #[test]
+    fn assembly_name_display_is_canonical() {
+        let name = AssemblyName {
+            name: "System.Runtime".into(),
+            version: (8, 0, 0, 0),
+            culture: None,
+            public_key_token: Some([0xb0, 0x3f, 0x5f, 0x7f, 0x11, 0xd5, 0x0a, 0x3a]),
+        };
+
+        assert_eq!(
+            name.to_string(),
+            "System.Runtime, Version=8.0.0.0, Culture=neutral, PublicKeyToken=b03f5f7f11d50a3a"
+        );
+    }
+}
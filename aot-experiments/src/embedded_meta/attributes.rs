@@ -0,0 +1,630 @@
+use crate::{
+    embedded_meta::{
+        CustomAttribute, Field, MetadataReader, Method, Property, TypeDefinition, TypeReference,
+        collections::CustomAttributeHandleCollection,
+        constants::ConstantValue,
+        flags::{TypeLayout, UnmanagedCallingConvention},
+        handles::{
+            BaseHandle, Handle, HandleType, MemberReferenceHandle, NamespaceReferenceHandle,
+            QualifiedMethodHandle, TypeDefinitionHandle, TypeInstantiationSignatureHandle,
+            TypeReferenceHandle, TypeSpecificationHandle,
+        },
+    },
+    error::Result,
+};
+
+/// A member carrying a custom attribute, see [`MetadataReader::find_by_attribute`]
+#[derive(Debug, Clone)]
+pub enum AttributeTarget<'a> {
+    Type(TypeDefinition<'a>),
+    Method(TypeDefinition<'a>, Method<'a>),
+    Field(TypeDefinition<'a>, Field<'a>),
+    Property(TypeDefinition<'a>, Property<'a>),
+}
+
+impl<'a> MetadataReader<'a> {
+    /// Finds every type, method, field and property carrying the given attribute
+    ///
+    /// The attribute can be given by its full name (`System.ObsoleteAttribute`), its short name (`ObsoleteAttribute`),
+    /// or either of those without the `Attribute` suffix, like in C#.
+    pub fn find_by_attribute(
+        &self,
+        attr_full_name: &str,
+    ) -> Result<Vec<(AttributeTarget<'a>, CustomAttribute<'a>)>> {
+        let mut targets = Vec::new();
+
+        for def in self
+            .header()
+            .scope_definitions()
+            .iter()?
+            .flatten()
+            .flat_map(|hdl| hdl.to_data(*self))
+        {
+            for typ in def.get_all_types()? {
+                if let Some(attribute) = self.find_attribute(typ.custom_attributes, attr_full_name)
+                {
+                    targets.push((AttributeTarget::Type(typ.clone()), attribute));
+                }
+
+                for method in typ
+                    .methods
+                    .iter()?
+                    .flatten()
+                    .flat_map(|hdl| hdl.to_data(*self))
+                {
+                    if let Some(attribute) =
+                        self.find_attribute(method.custom_attributes, attr_full_name)
+                    {
+                        targets.push((AttributeTarget::Method(typ.clone(), method), attribute));
+                    }
+                }
+
+                for field in typ
+                    .fields
+                    .iter()?
+                    .flatten()
+                    .flat_map(|hdl| hdl.to_data(*self))
+                {
+                    if let Some(attribute) =
+                        self.find_attribute(field.custom_attributes, attr_full_name)
+                    {
+                        targets.push((AttributeTarget::Field(typ.clone(), field), attribute));
+                    }
+                }
+
+                for property in typ
+                    .properties
+                    .iter()?
+                    .flatten()
+                    .flat_map(|hdl| hdl.to_data(*self))
+                {
+                    if let Some(attribute) =
+                        self.find_attribute(property.custom_attributes, attr_full_name)
+                    {
+                        targets.push((AttributeTarget::Property(typ.clone(), property), attribute));
+                    }
+                }
+            }
+        }
+
+        Ok(targets)
+    }
+
+    fn find_attribute(
+        &self,
+        attributes: CustomAttributeHandleCollection<'a>,
+        name: &str,
+    ) -> Option<CustomAttribute<'a>> {
+        attributes
+            .iter()
+            .ok()?
+            .flatten()
+            .flat_map(|hdl| hdl.to_data(*self))
+            .find(|attribute| {
+                attribute
+                    .attribute_type_name()
+                    .ok()
+                    .flatten()
+                    .is_some_and(|full_name| attribute_name_matches(&full_name, name))
+            })
+    }
+}
+
+/// A layout as declared by a `[StructLayout]` attribute, see [`TypeDefinition::layout_attribute`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LayoutAttribute {
+    /// `None` for `LayoutKind` values that aren't known
+    pub kind: Option<TypeLayout>,
+    pub size: Option<u32>,
+    pub packing_size: Option<u16>,
+}
+
+impl<'a> TypeDefinition<'a> {
+    /// The `[StructLayout]` attribute of this type, if it was kept as a custom attribute
+    ///
+    /// The C# compiler usually folds this pseudo-attribute into the type flags, size and packing size, so this is
+    /// mostly `None` and [`TypeDefinition::declared_layout`] should be preferred.
+    pub fn layout_attribute(&self) -> Result<Option<LayoutAttribute>> {
+        let Some(attribute) = self.reader.find_attribute(
+            self.custom_attributes,
+            "System.Runtime.InteropServices.StructLayoutAttribute",
+        ) else {
+            return Ok(None);
+        };
+
+        let kind = match attribute.fixed_arguments.iter()?.next() {
+            Some(handle) => match self.reader.decode_constant(handle?)?.as_integer() {
+                Some(0) => Some(TypeLayout::Sequential),
+                Some(2) => Some(TypeLayout::Explicit),
+                Some(3) => Some(TypeLayout::Auto),
+                _ => None,
+            },
+            None => None,
+        };
+
+        let mut layout = LayoutAttribute {
+            kind,
+            size: None,
+            packing_size: None,
+        };
+
+        for named in attribute
+            .named_arguments
+            .iter()?
+            .flatten()
+            .flat_map(|hdl| hdl.to_data(self.reader))
+        {
+            let value = self.reader.decode_constant(named.value)?.as_integer();
+
+            match named.name.to_data(self.reader)?.value.as_str() {
+                "Size" => layout.size = value.and_then(|value| value.try_into().ok()),
+                "Pack" => layout.packing_size = value.and_then(|value| value.try_into().ok()),
+                _ => {}
+            }
+        }
+
+        Ok(Some(layout))
+    }
+}
+
+impl<'a> TypeDefinition<'a> {
+    /// Whether the compiler generated this type, like the state machines of async methods and the closures of lambdas
+    ///
+    /// Besides `[CompilerGenerated]`, types are recognized by their name starting with `<` (e.g. `<Run>d__4` or
+    /// `<>c`), which C# doesn't allow in identifiers. Types nested in such a type count as generated as well.
+    pub fn is_compiler_generated(&self) -> Result<bool> {
+        if self
+            .reader
+            .find_attribute(
+                self.custom_attributes,
+                "System.Runtime.CompilerServices.CompilerGeneratedAttribute",
+            )
+            .is_some()
+        {
+            return Ok(true);
+        }
+
+        let mut typ = self.clone();
+        loop {
+            let name = typ.name.to_data(self.reader)?.value;
+            if name.starts_with('<') && name != "<Module>" {
+                return Ok(true);
+            }
+
+            if typ.enclosing_type.is_nil() {
+                return Ok(false);
+            }
+
+            typ = typ.enclosing_type.to_data(self.reader)?;
+        }
+    }
+
+    /// Whether this is a `ref struct`, which the compiler marks with `[IsByRefLike]`
+    pub fn is_by_ref_like(&self) -> bool {
+        self.reader
+            .find_attribute(
+                self.custom_attributes,
+                "System.Runtime.CompilerServices.IsByRefLikeAttribute",
+            )
+            .is_some()
+    }
+
+    /// Whether this is a `readonly struct`, which the compiler marks with `[IsReadOnly]`
+    pub fn is_readonly(&self) -> bool {
+        self.reader
+            .find_attribute(
+                self.custom_attributes,
+                "System.Runtime.CompilerServices.IsReadOnlyAttribute",
+            )
+            .is_some()
+    }
+}
+
+impl<'a> Field<'a> {
+    /// The offset given by the `[FieldOffset]` attribute of this field, if it was kept as a custom attribute
+    ///
+    /// Like `[StructLayout]` this is a pseudo-attribute, the compiler normally stores it in [`Field::offset`].
+    pub fn offset_attribute(&self) -> Result<Option<u32>> {
+        let Some(attribute) = self.reader.find_attribute(
+            self.custom_attributes,
+            "System.Runtime.InteropServices.FieldOffsetAttribute",
+        ) else {
+            return Ok(None);
+        };
+
+        let Some(handle) = attribute.fixed_arguments.iter()?.next() else {
+            return Ok(None);
+        };
+
+        Ok(self
+            .reader
+            .decode_constant(handle?)?
+            .as_integer()
+            .and_then(|offset| offset.try_into().ok()))
+    }
+}
+
+/// The native function a P/Invoke calls, see [`Method::pinvoke_import`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PInvokeImport {
+    /// The library the function is imported from
+    pub module: Option<String>,
+    /// Name of the function, which is the name of the method unless `EntryPoint` says otherwise
+    pub entry_point: String,
+    pub exact_spelling: Option<bool>,
+    /// The `CharSet` of a `[DllImport]`, or the `StringMarshalling` of a `[LibraryImport]`
+    pub char_set: Option<String>,
+    /// The `CallingConvention` of a `[DllImport]`, or the `[UnmanagedCallConv]` of a `[LibraryImport]`
+    pub calling_convention: Option<UnmanagedCallingConvention>,
+}
+
+impl<'a> Method<'a> {
+    /// Decodes the `[DllImport]` or `[LibraryImport]` attribute of this method
+    pub fn pinvoke_import(&self) -> Result<Option<PInvokeImport>> {
+        let (attribute, char_set_argument) = if let Some(attribute) = self.reader.find_attribute(
+            self.custom_attributes,
+            "System.Runtime.InteropServices.DllImportAttribute",
+        ) {
+            (attribute, "CharSet")
+        } else if let Some(attribute) = self.reader.find_attribute(
+            self.custom_attributes,
+            "System.Runtime.InteropServices.LibraryImportAttribute",
+        ) {
+            (attribute, "StringMarshalling")
+        } else {
+            return Ok(None);
+        };
+
+        let module = match attribute.fixed_arguments.iter()?.next() {
+            Some(handle) => match self.reader.decode_constant(handle?)? {
+                ConstantValue::String(module) => Some(module),
+                _ => None,
+            },
+            None => None,
+        };
+
+        let mut import = PInvokeImport {
+            module,
+            entry_point: self.name.to_data(self.reader)?.value,
+            exact_spelling: None,
+            char_set: None,
+            calling_convention: None,
+        };
+
+        for named in attribute
+            .named_arguments
+            .iter()?
+            .flatten()
+            .flat_map(|hdl| hdl.to_data(self.reader))
+        {
+            let name = named.name.to_data(self.reader)?.value;
+            let value = self.reader.decode_constant(named.value)?;
+
+            match (name.as_str(), value) {
+                ("EntryPoint", ConstantValue::String(entry_point)) => {
+                    import.entry_point = entry_point
+                }
+                ("ExactSpelling", ConstantValue::Boolean(exact_spelling)) => {
+                    import.exact_spelling = Some(exact_spelling)
+                }
+                (name, value) if name == char_set_argument => {
+                    import.char_set = Some(char_set_name(name, &value))
+                }
+                ("CallingConvention", value) => {
+                    import.calling_convention = value
+                        .as_integer()
+                        .and_then(UnmanagedCallingConvention::from_interop)
+                }
+                _ => {}
+            }
+        }
+
+        // `[LibraryImport]` takes the convention from a separate attribute, listing the `CallConv*` types
+        if import.calling_convention.is_none()
+            && let Some(attribute) = self.reader.find_attribute(
+                self.custom_attributes,
+                "System.Runtime.InteropServices.UnmanagedCallConvAttribute",
+            )
+        {
+            for named in attribute
+                .named_arguments
+                .iter()?
+                .flatten()
+                .flat_map(|hdl| hdl.to_data(self.reader))
+            {
+                if named.name.to_data(self.reader)?.value != "CallConvs" {
+                    continue;
+                }
+
+                if let ConstantValue::Array(types) = self.reader.decode_constant(named.value)? {
+                    import.calling_convention = types.iter().find_map(|typ| match typ {
+                        ConstantValue::Type(name) => {
+                            UnmanagedCallingConvention::from_call_conv_type(name)
+                        }
+                        _ => None,
+                    });
+                }
+            }
+        }
+
+        Ok(Some(import))
+    }
+}
+
+fn char_set_name(argument: &str, value: &ConstantValue) -> String {
+    let names: &[&str] = if argument == "CharSet" {
+        &["", "None", "Ansi", "Unicode", "Auto"]
+    } else {
+        &["Custom", "Utf8", "Utf16"]
+    };
+
+    value
+        .as_integer()
+        .and_then(|value| usize::try_from(value).ok())
+        .and_then(|value| names.get(value))
+        .filter(|name| !name.is_empty())
+        .map_or_else(|| value.to_string(), |name| name.to_string())
+}
+
+impl<'a> CustomAttribute<'a> {
+    /// Full name of the attribute type, resolved through the declaring type of its constructor
+    ///
+    /// Returns `None` when the constructor isn't declared on a named type.
+    pub fn attribute_type_name(&self) -> Result<Option<String>> {
+        let declaring_type = match self.constructor.handle_type() {
+            // Attributes defined in this metadata
+            Some(HandleType::QualifiedMethod) => self
+                .constructor
+                .to_handle::<QualifiedMethodHandle>()?
+                .to_data(self.reader)?
+                .enclosing_type
+                .to_base(),
+            // Attributes defined in referenced scopes
+            Some(HandleType::MemberReference) => {
+                self.constructor
+                    .to_handle::<MemberReferenceHandle>()?
+                    .to_data(self.reader)?
+                    .parent
+            }
+            _ => return Ok(None),
+        };
+
+        type_name(self.reader, declaring_type)
+    }
+
+    /// The constructor arguments followed by the named arguments, formatted like `(1, "text", Name = true)`
+    pub fn format_arguments(&self) -> Result<String> {
+        let mut arguments = Vec::new();
+
+        for handle in self.fixed_arguments.iter()? {
+            arguments.push(self.reader.decode_constant(handle?)?.to_string());
+        }
+
+        for named in self
+            .named_arguments
+            .iter()?
+            .flatten()
+            .flat_map(|hdl| hdl.to_data(self.reader))
+        {
+            arguments.push(format!(
+                "{} = {}",
+                named.name.to_data(self.reader)?.value,
+                self.reader.decode_constant(named.value)?
+            ));
+        }
+
+        Ok(format!("({})", arguments.join(", ")))
+    }
+}
+
+impl<'a> TypeReference<'a> {
+    pub fn get_full_name(&self) -> Result<String> {
+        let type_name = self.type_name.to_data(self.reader)?.value;
+
+        match self.parent_namespace_or_type.handle_type() {
+            // Nested types
+            Some(HandleType::TypeReference) => {
+                let enclosing_type = self
+                    .parent_namespace_or_type
+                    .to_handle::<TypeReferenceHandle>()?
+                    .to_data(self.reader)?;
+
+                Ok(format!("{}.{type_name}", enclosing_type.get_full_name()?))
+            }
+            _ => {
+                let mut ns_handle = self.parent_namespace_or_type;
+                let mut ns_names = Vec::new();
+
+                while ns_handle.handle_type() == Some(HandleType::NamespaceReference) {
+                    let namespace = ns_handle
+                        .to_handle::<NamespaceReferenceHandle>()?
+                        .to_data(self.reader)?;
+
+                    if namespace.name.is_nil() {
+                        break;
+                    }
+
+                    ns_names.push(namespace.name.to_data(self.reader)?.value);
+                    ns_handle = namespace.parent_scope_or_namespace;
+                }
+
+                ns_names.reverse();
+                Ok(qualified_name(&ns_names.join("."), type_name))
+            }
+        }
+    }
+}
+
+fn type_name(reader: MetadataReader<'_>, handle: BaseHandle) -> Result<Option<String>> {
+    match handle.handle_type() {
+        Some(HandleType::TypeDefinition) => {
+            let typ = handle
+                .to_handle::<TypeDefinitionHandle>()?
+                .to_data(reader)?;
+
+            Ok(Some(qualified_name(
+                &typ.namespace_name()?,
+                typ.name.to_data(reader)?.value,
+            )))
+        }
+        Some(HandleType::TypeReference) => Ok(Some(
+            handle
+                .to_handle::<TypeReferenceHandle>()?
+                .to_data(reader)?
+                .get_full_name()?,
+        )),
+        // Generic attributes, matched by their definition
+        Some(HandleType::TypeSpecification) => {
+            let spec = handle
+                .to_handle::<TypeSpecificationHandle>()?
+                .to_data(reader)?;
+
+            if spec.signature.handle_type() != Some(HandleType::TypeInstantiationSignature) {
+                return Ok(None);
+            }
+
+            let instantiation = spec
+                .signature
+                .to_handle::<TypeInstantiationSignatureHandle>()?
+                .to_data(reader)?;
+
+            type_name(reader, instantiation.generic_type)
+        }
+        _ => Ok(None),
+    }
+}
+
+fn qualified_name(namespace: &str, name: String) -> String {
+    if namespace.is_empty() {
+        name
+    } else {
+        format!("{namespace}.{name}")
+    }
+}
+
+fn attribute_name_matches(full_name: &str, name: &str) -> bool {
+    let short_name = full_name.rsplit('.').next().unwrap_or(full_name);
+
+    [full_name, short_name]
+        .into_iter()
+        .any(|candidate| candidate == name || candidate.strip_suffix("Attribute") == Some(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        embedded_meta::{
+            MetadataReader,
+            attributes::PInvokeImport,
+            flags::UnmanagedCallingConvention,
+            handles::{BaseHandle, HandleType, MethodHandle},
+        },
+        test_utils::MetadataBuilder,
+    };
+
+    #[test]
+    fn pinvoke_import_decodes_the_dll_import_arguments() {
+        let mut builder = MetadataBuilder::new();
+        builder.empty_collection(); // Header without scopes
+
+        let namespace = builder.namespace_reference("System.Runtime.InteropServices");
+        let [attribute_type, char_set_type, calling_convention_type] =
+            ["DllImportAttribute", "CharSet", "CallingConvention"].map(|name| {
+                builder.type_reference(HandleType::NamespaceReference, namespace, name)
+            });
+
+        let constructor_name = builder.add_string(".ctor");
+        let constructor = builder.offset();
+        builder
+            .base_handle(HandleType::TypeReference, attribute_type)
+            .handle(constructor_name)
+            .base_handle(HandleType::Null, 0);
+
+        let module = builder.add_string("user32.dll");
+        let entry_point = builder.add_string("MessageBoxW");
+        let exact_spelling = builder.offset();
+        builder.byte(1);
+
+        let enum_value = |builder: &mut MetadataBuilder, value: i32, typ: u32| {
+            let value_offset = builder.offset();
+            builder.signed(value);
+
+            let offset = builder.offset();
+            builder
+                .base_handle(HandleType::ConstantInt32Value, value_offset)
+                .base_handle(HandleType::TypeReference, typ);
+            offset
+        };
+        let unicode = enum_value(&mut builder, 3, char_set_type);
+        let std_call = enum_value(&mut builder, 3, calling_convention_type);
+
+        // `EntryPoint = "MessageBoxW", ExactSpelling = true, CharSet = CharSet.Unicode, CallingConvention = StdCall`
+        let mut named_arguments = Vec::new();
+        for (name, value_type, value) in [
+            ("EntryPoint", HandleType::ConstantStringValue, entry_point),
+            (
+                "ExactSpelling",
+                HandleType::ConstantBooleanValue,
+                exact_spelling,
+            ),
+            ("CharSet", HandleType::ConstantEnumValue, unicode),
+            ("CallingConvention", HandleType::ConstantEnumValue, std_call),
+        ] {
+            let name = builder.add_string(name);
+
+            named_arguments.push(builder.offset());
+            builder
+                .byte(0) // Field
+                .handle(name)
+                .base_handle(HandleType::Null, 0)
+                .base_handle(value_type, value);
+        }
+
+        let attribute = builder.offset();
+        builder
+            .base_handle(HandleType::MemberReference, constructor)
+            .unsigned(1)
+            .base_handle(HandleType::ConstantStringValue, module)
+            .handles(&named_arguments);
+
+        let method = |builder: &mut MetadataBuilder, attributes: &[u32]| {
+            let name = builder.add_string("MessageBox");
+            let offset = builder.offset();
+            builder
+                .unsigned(0x2016) // Public, Static, HideBySig, PinvokeImpl
+                .unsigned(0)
+                .handle(name)
+                .handle(0)
+                .empty_collection()
+                .empty_collection()
+                .handles(attributes);
+            offset
+        };
+        let imported = method(&mut builder, &[attribute]);
+        let managed = method(&mut builder, &[]);
+
+        let blob = builder.build();
+        let reader = MetadataReader::new(&blob).unwrap();
+        let pinvoke_import = |offset| {
+            BaseHandle::from_offset(HandleType::Method, offset)
+                .to_handle::<MethodHandle>()
+                .unwrap()
+                .to_data(reader)
+                .unwrap()
+                .pinvoke_import()
+                .unwrap()
+        };
+
+        assert_eq!(
+            pinvoke_import(imported),
+            Some(PInvokeImport {
+                module: Some("user32.dll".to_string()),
+                entry_point: "MessageBoxW".to_string(),
+                exact_spelling: Some(true),
+                char_set: Some("Unicode".to_string()),
+                calling_convention: Some(UnmanagedCallingConvention::StdCall),
+            })
+        );
+        assert_eq!(pinvoke_import(managed), None);
+    }
+}
@@ -3,10 +3,11 @@ use paste::paste;
 use crate::{
     embedded_meta::handles::{
         BaseHandle, CustomAttributeHandle, EventHandle, FieldHandle, GenericParameterHandle,
-        MethodHandle, NamespaceDefinitionHandle, ParameterHandle, PropertyHandle,
-        ScopeDefinitionHandle, TypeDefinitionHandle, TypeForwarderHandle,
+        MethodHandle, MethodSemanticsHandle, NamedArgumentHandle, NamespaceDefinitionHandle,
+        ParameterHandle, PropertyHandle, ScopeDefinitionHandle, TypeDefinitionHandle,
+        TypeForwarderHandle,
     },
-    native_format::reader::NativeReadable,
+    native_format::reader::{Cursor, NativeReadable},
 };
 
 macro_rules! define_collection {
@@ -33,7 +34,7 @@ macro_rules! define_collection {
 
             paste! {
                 pub fn iter(&self) -> $crate::error::Result<[<$name Iter>]<'a>> {
-                    [<$name Iter>]::new(self.reader, self.offset)
+                    [<$name Iter>]::new(Cursor::new(self.reader, self.offset))
                 }
             }
 
@@ -41,6 +42,12 @@ macro_rules! define_collection {
                 let mut _offset = self.offset;
                 self.reader.decode_unsigned(&mut _offset)
             }
+
+            /// Whether the collection has no elements, without decoding the whole count
+            pub fn is_empty(&self) -> $crate::error::Result<bool> {
+                // A count of zero is always encoded as a single zero byte
+                Ok(self.reader.read_u8(self.offset)? == 0)
+            }
         }
 
         impl<'a> core::fmt::Debug for $name<'a> {
@@ -54,55 +61,19 @@ macro_rules! define_collection {
             }
         }
 
-        paste !{
-            pub struct [<$name Iter>]<'a> {
-                reader: $crate::native_format::reader::NativeReader<'a>,
-                offset: usize,
-                remaining: u32,
-            }
-
-            impl<'a> [<$name Iter>]<'a> {
-                pub fn new(
-                    reader: $crate::native_format::reader::NativeReader<'a>,
-                    mut offset: usize,
-                ) -> $crate::error::Result<Self> {
-                    let count = reader.decode_unsigned(&mut offset)?;
-
-                    Ok(Self {
-                        reader,
-                        offset,
-                        remaining: count,
-                    })
-                }
-            }
-
-            impl<'a> Iterator for [<$name Iter>]<'a> {
-                type Item = $crate::error::Result<$handle>;
-
-                fn next(&mut self) -> Option<Self::Item> {
-                    if self.remaining == 0 {
-                        return None;
-                    }
-
-                    self.remaining -= 1;
-
-                    Some(self.reader.read::<$handle>(&mut self.offset))
-                }
-            }
+        paste! {
+            pub type [<$name Iter>]<'a> = $crate::native_format::reader::SequenceIter<'a, $handle>;
         }
     };
 
     (@reader $name:ident) => {
-        impl<'a> $crate::native_format::reader::NativeReadable<'a> for $name<'a> {
-            fn read(
-                reader: &$crate::native_format::reader::NativeReader<'a>,
-                offset: &mut usize,
-            ) -> crate::error::Result<Self> {
-                let collection = Self::new(*reader, *offset);
-                let count = reader.decode_unsigned(offset)?;
+        impl<'a> NativeReadable<'a> for $name<'a> {
+            fn read(cursor: &mut Cursor<'a>) -> crate::error::Result<Self> {
+                let collection = Self::new(cursor.reader(), cursor.offset());
+                let count = cursor.read_unsigned()?;
 
                 for _ in 0..count {
-                    reader.skip_integer(offset)?;
+                    cursor.skip_integer()?;
                 }
 
                 Ok(collection)
@@ -117,6 +88,8 @@ define_collection!(EventHandleCollection, EventHandle);
 define_collection!(FieldHandleCollection, FieldHandle);
 define_collection!(GenericParameterHandleCollection, GenericParameterHandle);
 define_collection!(MethodHandleCollection, MethodHandle);
+define_collection!(MethodSemanticsHandleCollection, MethodSemanticsHandle);
+define_collection!(NamedArgumentHandleCollection, NamedArgumentHandle);
 define_collection!(
     NamespaceDefinitionHandleCollection,
     NamespaceDefinitionHandle
@@ -129,13 +102,10 @@ define_collection!(TypeForwarderHandleCollection, TypeForwarderHandle);
 define_collection!(ByteCollection, u8, @skip_read_impl);
 
 impl<'a> NativeReadable<'a> for ByteCollection<'a> {
-    fn read(
-        reader: &crate::native_format::reader::NativeReader<'a>,
-        offset: &mut usize,
-    ) -> crate::error::Result<Self> {
-        let collection = ByteCollection::new(*reader, *offset);
-        let length = reader.decode_unsigned(offset)?;
-        *offset += length as usize * size_of::<u8>();
+    fn read(cursor: &mut Cursor<'a>) -> crate::error::Result<Self> {
+        let collection = ByteCollection::new(cursor.reader(), cursor.offset());
+        let length = cursor.read_unsigned()?;
+        cursor.skip(length as usize * size_of::<u8>());
 
         Ok(collection)
     }
@@ -0,0 +1,281 @@
+use std::fmt;
+
+use crate::{
+    embedded_meta::{
+        MetadataReader, collections::HandleCollection, handles::BaseHandle, handles::HandleType,
+        utils::canonical_type_name,
+    },
+    error::Result,
+    native_format::reader::Cursor,
+};
+
+/// A decoded constant, such as a default value or an argument of a custom attribute
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConstantValue {
+    Null,
+    Boolean(bool),
+    Char(char),
+    SByte(i8),
+    Byte(u8),
+    Int16(i16),
+    UInt16(u16),
+    Int32(i32),
+    UInt32(u32),
+    Int64(i64),
+    UInt64(u64),
+    Single(f32),
+    Double(f64),
+    String(String),
+    Enum {
+        type_name: String,
+        value: Box<ConstantValue>,
+    },
+    Array(Vec<ConstantValue>),
+    /// A `typeof` argument
+    Type(String),
+    /// A kind of constant that isn't decoded (yet)
+    Unknown(HandleType),
+}
+
+impl fmt::Display for ConstantValue {
+    /// Formats the constant the way it would be written in C#
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Null => write!(f, "null"),
+            Self::Boolean(value) => write!(f, "{value}"),
+            Self::Char(value) => write!(f, "{value:?}"),
+            Self::SByte(value) => write!(f, "{value}"),
+            Self::Byte(value) => write!(f, "{value}"),
+            Self::Int16(value) => write!(f, "{value}"),
+            Self::UInt16(value) => write!(f, "{value}"),
+            Self::Int32(value) => write!(f, "{value}"),
+            Self::UInt32(value) => write!(f, "{value}"),
+            Self::Int64(value) => write!(f, "{value}"),
+            Self::UInt64(value) => write!(f, "{value}"),
+            Self::Single(value) => write!(f, "{value}"),
+            Self::Double(value) => write!(f, "{value}"),
+            Self::String(value) => write!(f, "{value:?}"),
+            Self::Enum { type_name, value } => write!(f, "({type_name}){value}"),
+            Self::Array(values) if values.is_empty() => write!(f, "new[] {{}}"),
+            Self::Array(values) => {
+                write!(f, "new[] {{ ")?;
+
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+
+                    write!(f, "{value}")?;
+                }
+
+                write!(f, " }}")
+            }
+            Self::Type(name) => write!(f, "typeof({name})"),
+            Self::Unknown(handle_type) => write!(f, "<{handle_type:?}>"),
+        }
+    }
+}
+
+impl ConstantValue {
+    /// The value of an integral constant, looking through enums
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            Self::SByte(value) => Some(*value as i64),
+            Self::Byte(value) => Some(*value as i64),
+            Self::Int16(value) => Some(*value as i64),
+            Self::UInt16(value) => Some(*value as i64),
+            Self::Int32(value) => Some(*value as i64),
+            Self::UInt32(value) => Some(*value as i64),
+            Self::Int64(value) => Some(*value),
+            Self::UInt64(value) => i64::try_from(*value).ok(),
+            Self::Enum { value, .. } => value.as_integer(),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> MetadataReader<'a> {
+    /// Decodes the constant a handle refers to, e.g. a `ConstantInt32Value` or a `ConstantStringArray`
+    pub fn decode_constant(&self, handle: BaseHandle) -> Result<ConstantValue> {
+        let Some(handle_type) = handle.handle_type() else {
+            return Ok(ConstantValue::Unknown(HandleType::Invalid));
+        };
+
+        let mut cursor = Cursor::new(self.stream_reader, handle.offset() as usize);
+
+        if let Some(value) = decode_primitive(&mut cursor, handle_type)? {
+            return Ok(value);
+        }
+
+        // Arrays of primitives store their elements inline, the others are arrays of handles
+        if let Some(element_type) = primitive_element_type(handle_type) {
+            let count = cursor.read_unsigned()?;
+            let mut values = Vec::new();
+
+            for _ in 0..count {
+                values.push(
+                    decode_primitive(&mut cursor, element_type)?
+                        .unwrap_or(ConstantValue::Unknown(element_type)),
+                );
+            }
+
+            return Ok(ConstantValue::Array(values));
+        }
+
+        let value = match handle_type {
+            HandleType::ConstantReferenceValue => ConstantValue::Null,
+            HandleType::ConstantStringValue => ConstantValue::String(cursor.read_string()?),
+            HandleType::ConstantStringArray | HandleType::ConstantHandleArray => {
+                // The elements of a handle array can each be of a different type
+                let elements = cursor.read::<HandleCollection<'a>>()?;
+                let mut values = Vec::new();
+
+                for element in elements.iter()? {
+                    values.push(self.decode_constant(element?)?);
+                }
+
+                ConstantValue::Array(values)
+            }
+            HandleType::ConstantEnumValue => {
+                let value = cursor.read::<BaseHandle>()?;
+                let type_handle = cursor.read::<BaseHandle>()?;
+
+                ConstantValue::Enum {
+                    type_name: canonical_type_name(*self, type_handle)?,
+                    value: Box::new(self.decode_constant(value)?),
+                }
+            }
+            HandleType::ConstantEnumArray => {
+                let type_handle = cursor.read::<BaseHandle>()?;
+                let values = cursor.read::<BaseHandle>()?;
+                let type_name = canonical_type_name(*self, type_handle)?;
+
+                match self.decode_constant(values)? {
+                    ConstantValue::Array(values) => ConstantValue::Array(
+                        values
+                            .into_iter()
+                            .map(|value| ConstantValue::Enum {
+                                type_name: type_name.clone(),
+                                value: Box::new(value),
+                            })
+                            .collect(),
+                    ),
+                    value => value,
+                }
+            }
+            HandleType::TypeDefinition
+            | HandleType::TypeReference
+            | HandleType::TypeSpecification => {
+                ConstantValue::Type(canonical_type_name(*self, handle)?)
+            }
+            _ => ConstantValue::Unknown(handle_type),
+        };
+
+        Ok(value)
+    }
+}
+
+fn decode_primitive(
+    cursor: &mut Cursor<'_>,
+    handle_type: HandleType,
+) -> Result<Option<ConstantValue>> {
+    let value = match handle_type {
+        HandleType::ConstantBooleanValue => ConstantValue::Boolean(cursor.read_u8()? != 0),
+        HandleType::ConstantCharValue => ConstantValue::Char(
+            char::from_u32(cursor.read_unsigned()?).unwrap_or(char::REPLACEMENT_CHARACTER),
+        ),
+        HandleType::ConstantSByteValue => ConstantValue::SByte(cursor.read_u8()? as i8),
+        HandleType::ConstantByteValue => ConstantValue::Byte(cursor.read_u8()?),
+        HandleType::ConstantInt16Value => ConstantValue::Int16(cursor.read_signed()? as i16),
+        HandleType::ConstantUInt16Value => ConstantValue::UInt16(cursor.read_unsigned()? as u16),
+        HandleType::ConstantInt32Value => ConstantValue::Int32(cursor.read_signed()?),
+        HandleType::ConstantUInt32Value => ConstantValue::UInt32(cursor.read_unsigned()?),
+        HandleType::ConstantInt64Value => ConstantValue::Int64(cursor.read_signed_long()?),
+        HandleType::ConstantUInt64Value => ConstantValue::UInt64(cursor.read_unsigned_long()?),
+        // Floating point values are stored as is
+        HandleType::ConstantSingleValue => ConstantValue::Single(cursor.read_f32()?),
+        HandleType::ConstantDoubleValue => ConstantValue::Double(cursor.read_f64()?),
+        _ => return Ok(None),
+    };
+
+    Ok(Some(value))
+}
+
+fn primitive_element_type(array_type: HandleType) -> Option<HandleType> {
+    let element_type = match array_type {
+        HandleType::ConstantBooleanArray => HandleType::ConstantBooleanValue,
+        HandleType::ConstantCharArray => HandleType::ConstantCharValue,
+        HandleType::ConstantSByteArray => HandleType::ConstantSByteValue,
+        HandleType::ConstantByteArray => HandleType::ConstantByteValue,
+        HandleType::ConstantInt16Array => HandleType::ConstantInt16Value,
+        HandleType::ConstantUInt16Array => HandleType::ConstantUInt16Value,
+        HandleType::ConstantInt32Array => HandleType::ConstantInt32Value,
+        HandleType::ConstantUInt32Array => HandleType::ConstantUInt32Value,
+        HandleType::ConstantInt64Array => HandleType::ConstantInt64Value,
+        HandleType::ConstantUInt64Array => HandleType::ConstantUInt64Value,
+        HandleType::ConstantSingleArray => HandleType::ConstantSingleValue,
+        HandleType::ConstantDoubleArray => HandleType::ConstantDoubleValue,
+        _ => return None,
+    };
+
+    Some(element_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        embedded_meta::{
+            MetadataReader, constants::ConstantValue, handles::BaseHandle, handles::HandleType,
+        },
+        test_utils::MetadataBuilder,
+    };
+
+    #[test]
+    fn decodes_primitive_and_handle_arrays() {
+        let mut builder = MetadataBuilder::new();
+        builder.empty_collection(); // Header without scopes
+
+        let numbers = builder.offset();
+        builder.unsigned(3).signed(1).signed(-2).signed(3);
+
+        let empty = builder.offset();
+        builder.empty_collection();
+
+        let string = builder.add_string("hi");
+
+        let null = builder.offset();
+
+        // `new object[] { "hi", null, new int[] { 1, -2, 3 } }`
+        let mixed = builder.offset();
+        builder
+            .unsigned(3)
+            .base_handle(HandleType::ConstantStringValue, string)
+            .base_handle(HandleType::ConstantReferenceValue, null)
+            .base_handle(HandleType::ConstantInt32Array, numbers);
+
+        let blob = builder.build();
+        let reader = MetadataReader::new(&blob).unwrap();
+        let decode = |handle_type, offset| {
+            reader
+                .decode_constant(BaseHandle::from_offset(handle_type, offset))
+                .unwrap()
+        };
+
+        assert_eq!(
+            decode(HandleType::ConstantInt32Array, numbers),
+            ConstantValue::Array(vec![
+                ConstantValue::Int32(1),
+                ConstantValue::Int32(-2),
+                ConstantValue::Int32(3),
+            ])
+        );
+        assert_eq!(
+            decode(HandleType::ConstantStringArray, empty),
+            ConstantValue::Array(vec![])
+        );
+        assert_eq!(
+            decode(HandleType::ConstantHandleArray, mixed).to_string(),
+            r#"new[] { "hi", null, new[] { 1, -2, 3 } }"#
+        );
+    }
+}
@@ -1,5 +1,58 @@
+use std::fmt;
+
 use num_enum::{FromPrimitive, TryFromPrimitive};
 
+// === Type ===
+
+#[repr(transparent)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TypeAttributes(u32);
+
+impl TypeAttributes {
+    pub const fn new(raw: u32) -> Self {
+        Self(raw)
+    }
+
+    pub const fn raw(self) -> u32 {
+        self.0
+    }
+}
+
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, FromPrimitive)]
+pub enum TypeLayout {
+    #[default]
+    Auto = 0x00,
+    Sequential = 0x08,
+    Explicit = 0x10,
+}
+
+impl TypeAttributes {
+    pub const LAYOUT_MASK: u32 = 0x0018;
+
+    pub fn layout(self) -> TypeLayout {
+        TypeLayout::from_primitive((self.0 & Self::LAYOUT_MASK) as u8)
+    }
+}
+
+impl TypeAttributes {
+    pub const INTERFACE: u32 = 0x0020;
+    pub const ABSTRACT: u32 = 0x0080;
+    pub const SEALED: u32 = 0x0100;
+
+    pub fn is_interface(self) -> bool {
+        self.0 & Self::INTERFACE != 0
+    }
+
+    pub fn is_abstract(self) -> bool {
+        self.0 & Self::ABSTRACT != 0
+    }
+
+    pub fn is_sealed(self) -> bool {
+        self.0 & Self::SEALED != 0
+    }
+}
+
 // === Method ===
 
 #[repr(transparent)]
@@ -65,6 +118,10 @@ impl MethodAttributes {
         self.0 & Self::STATIC != 0
     }
 
+    pub fn is_final(self) -> bool {
+        self.0 & Self::FINAL != 0
+    }
+
     pub fn is_virtual(self) -> bool {
         self.0 & Self::VIRTUAL != 0
     }
@@ -86,20 +143,347 @@ impl MethodAttributes {
     }
 }
 
+// === Method Impl ===
+
+#[repr(transparent)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct MethodImplAttributes(u32);
+
+impl MethodImplAttributes {
+    pub const fn new(raw: u32) -> Self {
+        Self(raw)
+    }
+
+    pub const fn raw(self) -> u32 {
+        self.0
+    }
+}
+
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, FromPrimitive)]
+pub enum MethodCodeType {
+    #[default]
+    IL = 0x0,
+    Native = 0x1,
+    Optil = 0x2,
+    Runtime = 0x3,
+}
+
+impl MethodImplAttributes {
+    pub const CODE_TYPE_MASK: u32 = 0x0003;
+
+    pub fn code_type(self) -> MethodCodeType {
+        MethodCodeType::from_primitive((self.0 & Self::CODE_TYPE_MASK) as u8)
+    }
+}
+
+impl MethodImplAttributes {
+    pub const UNMANAGED: u32 = 0x0004;
+    pub const NO_INLINING: u32 = 0x0008;
+    pub const FORWARD_REF: u32 = 0x0010;
+    pub const SYNCHRONIZED: u32 = 0x0020;
+    pub const NO_OPTIMIZATION: u32 = 0x0040;
+    pub const PRESERVE_SIG: u32 = 0x0080;
+    pub const AGGRESSIVE_INLINING: u32 = 0x0100;
+    pub const AGGRESSIVE_OPTIMIZATION: u32 = 0x0200;
+    pub const INTERNAL_CALL: u32 = 0x1000;
+
+    pub fn is_unmanaged(self) -> bool {
+        self.0 & Self::UNMANAGED != 0
+    }
+
+    pub fn is_internal_call(self) -> bool {
+        self.0 & Self::INTERNAL_CALL != 0
+    }
+
+    pub fn is_synchronized(self) -> bool {
+        self.0 & Self::SYNCHRONIZED != 0
+    }
+
+    pub fn is_no_inlining(self) -> bool {
+        self.0 & Self::NO_INLINING != 0
+    }
+
+    pub fn is_aggressive_inlining(self) -> bool {
+        self.0 & Self::AGGRESSIVE_INLINING != 0
+    }
+
+    /// The names of the `MethodImplOptions` that are set, as they would appear in a `[MethodImpl]` attribute
+    pub fn options(self) -> Vec<&'static str> {
+        [
+            (Self::UNMANAGED, "Unmanaged"),
+            (Self::NO_INLINING, "NoInlining"),
+            (Self::FORWARD_REF, "ForwardRef"),
+            (Self::SYNCHRONIZED, "Synchronized"),
+            (Self::NO_OPTIMIZATION, "NoOptimization"),
+            (Self::PRESERVE_SIG, "PreserveSig"),
+            (Self::AGGRESSIVE_INLINING, "AggressiveInlining"),
+            (Self::AGGRESSIVE_OPTIMIZATION, "AggressiveOptimization"),
+            (Self::INTERNAL_CALL, "InternalCall"),
+        ]
+        .into_iter()
+        .filter(|&(flag, _)| self.0 & flag != 0)
+        .map(|(_, name)| name)
+        .collect()
+    }
+}
+
+// === Field ===
+
+#[repr(transparent)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct FieldAttributes(u32);
+
+impl FieldAttributes {
+    pub const fn new(raw: u32) -> Self {
+        Self(raw)
+    }
+
+    pub const fn raw(self) -> u32 {
+        self.0
+    }
+}
+
+impl FieldAttributes {
+    pub const STATIC: u32 = 0x0010;
+    pub const INIT_ONLY: u32 = 0x0020;
+    pub const LITERAL: u32 = 0x0040;
+
+    pub fn is_static(self) -> bool {
+        self.0 & Self::STATIC != 0
+    }
+
+    pub fn is_init_only(self) -> bool {
+        self.0 & Self::INIT_ONLY != 0
+    }
+
+    pub fn is_literal(self) -> bool {
+        self.0 & Self::LITERAL != 0
+    }
+}
+
+// === Method Semantics ===
+
+#[repr(transparent)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct MethodSemanticsAttributes(u32);
+
+impl MethodSemanticsAttributes {
+    pub const fn new(raw: u32) -> Self {
+        Self(raw)
+    }
+
+    pub const fn raw(self) -> u32 {
+        self.0
+    }
+}
+
+impl MethodSemanticsAttributes {
+    pub const SETTER: u32 = 0x0001;
+    pub const GETTER: u32 = 0x0002;
+    pub const OTHER: u32 = 0x0004;
+    pub const ADD_ON: u32 = 0x0008;
+    pub const REMOVE_ON: u32 = 0x0010;
+    pub const FIRE: u32 = 0x0020;
+
+    pub fn is_setter(self) -> bool {
+        self.0 & Self::SETTER != 0
+    }
+
+    pub fn is_getter(self) -> bool {
+        self.0 & Self::GETTER != 0
+    }
+
+    pub fn is_add_on(self) -> bool {
+        self.0 & Self::ADD_ON != 0
+    }
+
+    pub fn is_remove_on(self) -> bool {
+        self.0 & Self::REMOVE_ON != 0
+    }
+
+    pub fn is_fire(self) -> bool {
+        self.0 & Self::FIRE != 0
+    }
+}
+
 // === Method Signature ===
 
+#[repr(transparent)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CallingConventionFlags(u8);
+
+impl CallingConventionFlags {
+    pub const fn new(raw: u8) -> Self {
+        Self(raw)
+    }
+
+    pub const fn raw(self) -> u8 {
+        self.0
+    }
+}
+
+/// The kind of call in the lower bits of [`CallingConventionFlags`]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive)]
 #[repr(u8)]
 pub enum SignatureCallingConvention {
     #[default]
     Default = 0x00,
-    HasThis = 0x20,
-    ExplicitThis = 0x40,
-    Vararg = 0x05,
     Cdecl = 0x01,
     StdCall = 0x02,
     ThisCall = 0x03,
     FastCall = 0x04,
+    Vararg = 0x05,
     Unmanaged = 0x09,
-    UnmanagedCallingConventionMask = 0x0F,
+}
+
+/// The native calling convention of an unmanaged function pointer or P/Invoke
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnmanagedCallingConvention {
+    /// The default of the platform, or the one given by the modifiers of an `unmanaged` function pointer
+    Platform,
+    Cdecl,
+    StdCall,
+    ThisCall,
+    FastCall,
+}
+
+impl CallingConventionFlags {
+    pub const KIND_MASK: u8 = 0x0F;
+    pub const HAS_THIS: u8 = 0x20;
+    pub const EXPLICIT_THIS: u8 = 0x40;
+
+    pub fn kind(self) -> SignatureCallingConvention {
+        SignatureCallingConvention::from_primitive(self.0 & Self::KIND_MASK)
+    }
+
+    pub fn has_this(self) -> bool {
+        self.0 & Self::HAS_THIS != 0
+    }
+
+    pub fn explicit_this(self) -> bool {
+        self.0 & Self::EXPLICIT_THIS != 0
+    }
+
+    /// The native calling convention, for signatures of unmanaged function pointers
+    pub fn unmanaged(self) -> Option<UnmanagedCallingConvention> {
+        let convention = match self.kind() {
+            SignatureCallingConvention::Cdecl => UnmanagedCallingConvention::Cdecl,
+            SignatureCallingConvention::StdCall => UnmanagedCallingConvention::StdCall,
+            SignatureCallingConvention::ThisCall => UnmanagedCallingConvention::ThisCall,
+            SignatureCallingConvention::FastCall => UnmanagedCallingConvention::FastCall,
+            SignatureCallingConvention::Unmanaged => UnmanagedCallingConvention::Platform,
+            _ => return None,
+        };
+
+        Some(convention)
+    }
+}
+
+impl fmt::Display for CallingConventionFlags {
+    /// Formats the flags followed by the kind, e.g. `HasThis Vararg`, leaving out a `Default` kind after a flag
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+
+        if self.has_this() {
+            parts.push("HasThis".to_string());
+        }
+
+        if self.explicit_this() {
+            parts.push("ExplicitThis".to_string());
+        }
+
+        if parts.is_empty() || self.kind() != SignatureCallingConvention::Default {
+            parts.push(format!("{:?}", self.kind()));
+        }
+
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+impl UnmanagedCallingConvention {
+    /// Decodes a `System.Runtime.InteropServices.CallingConvention`, as used by `[DllImport]`
+    pub fn from_interop(value: i64) -> Option<Self> {
+        let convention = match value {
+            1 => Self::Platform, // Winapi
+            2 => Self::Cdecl,
+            3 => Self::StdCall,
+            4 => Self::ThisCall,
+            5 => Self::FastCall,
+            _ => return None,
+        };
+
+        Some(convention)
+    }
+
+    /// Decodes the name of a `CallConv*` type, as used by `[UnmanagedCallConv]` and function pointer modifiers
+    pub fn from_call_conv_type(name: &str) -> Option<Self> {
+        let convention = match name.rsplit('.').next()? {
+            "CallConvCdecl" => Self::Cdecl,
+            "CallConvStdcall" => Self::StdCall,
+            "CallConvThiscall" => Self::ThisCall,
+            "CallConvFastcall" => Self::FastCall,
+            _ => return None,
+        };
+
+        Some(convention)
+    }
+}
+
+impl fmt::Display for UnmanagedCallingConvention {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Platform => "winapi",
+            Self::Cdecl => "cdecl",
+            Self::StdCall => "stdcall",
+            Self::ThisCall => "thiscall",
+            Self::FastCall => "fastcall",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::embedded_meta::flags::{
+        CallingConventionFlags, SignatureCallingConvention, UnmanagedCallingConvention,
+    };
+
+    #[test]
+    fn calling_convention_bits_are_decoded_separately() {
+        // An instance method, `instance explicit` and a generic method, which sets 0x10 outside of the kind
+        let instance = CallingConventionFlags::new(0x20);
+        assert_eq!(instance.kind(), SignatureCallingConvention::Default);
+        assert!(instance.has_this());
+        assert!(!instance.explicit_this());
+
+        let explicit = CallingConventionFlags::new(0x60);
+        assert!(explicit.has_this());
+        assert!(explicit.explicit_this());
+
+        let generic = CallingConventionFlags::new(0x30 | 0x05);
+        assert_eq!(generic.kind(), SignatureCallingConvention::Vararg);
+        assert!(generic.has_this());
+        assert!(!generic.explicit_this());
+
+        // Kinds without a variant fall back to the default
+        assert_eq!(
+            CallingConventionFlags::new(0x07).kind(),
+            SignatureCallingConvention::Default
+        );
+    }
+
+    #[test]
+    fn unmanaged_calling_conventions_come_from_the_kind() {
+        let unmanaged = |raw| CallingConventionFlags::new(raw).unmanaged();
+
+        assert_eq!(unmanaged(0x01), Some(UnmanagedCallingConvention::Cdecl));
+        assert_eq!(unmanaged(0x02), Some(UnmanagedCallingConvention::StdCall));
+        assert_eq!(unmanaged(0x23), Some(UnmanagedCallingConvention::ThisCall));
+        assert_eq!(unmanaged(0x04), Some(UnmanagedCallingConvention::FastCall));
+        assert_eq!(unmanaged(0x09), Some(UnmanagedCallingConvention::Platform));
+        assert_eq!(unmanaged(0x00), None);
+        assert_eq!(unmanaged(0x05), None);
+    }
 }
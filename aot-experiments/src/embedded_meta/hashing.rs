@@ -0,0 +1,82 @@
+//! The type hashing algorithms of the NativeAOT type system (`TypeHashingAlgorithms`)
+//!
+//! The compiler stores these in every MethodTable, and uses them to key the reflection hashtables.
+
+fn rotate_left(value: i32, bits: u32) -> i32 {
+    (value as u32).rotate_left(bits) as i32
+}
+
+/// Hashes a type name, which is computed over its UTF-16 code units
+pub fn compute_name_hashcode(name: &str) -> i32 {
+    let mut hash1 = 0x6DA3B944_i32;
+    let mut hash2 = 0_i32;
+
+    let units = name.encode_utf16().collect::<Vec<_>>();
+
+    for pair in units.chunks(2) {
+        hash1 = hash1.wrapping_add(rotate_left(hash1, 5)) ^ pair[0] as i32;
+
+        if let Some(&unit) = pair.get(1) {
+            hash2 = hash2.wrapping_add(rotate_left(hash2, 5)) ^ unit as i32;
+        }
+    }
+
+    hash1 = hash1.wrapping_add(rotate_left(hash1, 8));
+    hash2 = hash2.wrapping_add(rotate_left(hash2, 8));
+
+    hash1 ^ hash2
+}
+
+pub fn compute_nested_type_hashcode(
+    enclosing_type_hashcode: i32,
+    nested_type_name_hash: i32,
+) -> i32 {
+    enclosing_type_hashcode.wrapping_add(rotate_left(enclosing_type_hashcode, 11))
+        ^ nested_type_name_hash
+}
+
+pub fn compute_generic_instance_hashcode(
+    generic_definition_hashcode: i32,
+    generic_argument_hashcodes: &[i32],
+) -> i32 {
+    let mut hashcode = generic_definition_hashcode;
+
+    for &argument in generic_argument_hashcodes {
+        hashcode = hashcode.wrapping_add(rotate_left(hashcode, 13)) ^ argument;
+    }
+
+    hashcode.wrapping_add(rotate_left(hashcode, 15))
+}
+
+/// Arrays are hashed like instantiations of `System.Array<T>`, or `System.MDArrayRank{n}<T>` for
+/// multi-dimensional arrays. A `rank` of `None` means a single-dimensional, zero-based array.
+pub fn compute_array_type_hashcode(element_type_hashcode: i32, rank: Option<u32>) -> i32 {
+    let definition = match rank {
+        None => compute_name_hashcode("System.Array`1"),
+        Some(rank) => compute_name_hashcode(&format!("System.MDArrayRank{rank}`1")),
+    };
+
+    compute_generic_instance_hashcode(definition, &[element_type_hashcode])
+}
+
+pub fn compute_pointer_type_hashcode(pointee_type_hashcode: i32) -> i32 {
+    pointee_type_hashcode.wrapping_add(rotate_left(pointee_type_hashcode, 5)) ^ 0x12D0
+}
+
+pub fn compute_byref_type_hashcode(parameter_type_hashcode: i32) -> i32 {
+    parameter_type_hashcode.wrapping_add(rotate_left(parameter_type_hashcode, 7)) ^ 0x4C
+}
+
+#[cfg(test)]
+mod tests {
+    use super::compute_name_hashcode;
+
+    #[test]
+    fn name_hashcode_matches_runtime_constant() {
+        // Hardcoded by the runtime as the hashcode of single-dimensional arrays
+        assert_eq!(
+            compute_name_hashcode("System.Array`1"),
+            0xD5313557_u32 as i32
+        );
+    }
+}
@@ -1,23 +1,38 @@
+pub mod assembly;
+pub mod attributes;
 pub mod collections;
+pub mod constants;
 pub mod flags;
 pub mod handles;
+pub mod hashing;
+pub mod scan;
 pub mod utils;
+pub mod visitor;
 
 use crate::{
     embedded_meta::{
         collections::{
             ByteCollection, CustomAttributeHandleCollection, EventHandleCollection,
             FieldHandleCollection, GenericParameterHandleCollection, HandleCollection,
-            MethodHandleCollection, NamespaceDefinitionHandleCollection, ParameterHandleCollection,
+            MethodHandleCollection, MethodSemanticsHandleCollection, NamedArgumentHandleCollection,
+            NamespaceDefinitionHandleCollection, ParameterHandleCollection,
             PropertyHandleCollection, ScopeDefinitionHandleCollection,
             TypeDefinitionHandleCollection, TypeForwarderHandleCollection,
         },
-        flags::{MethodAttributes, SignatureCallingConvention},
+        flags::{
+            CallingConventionFlags, FieldAttributes, MethodAttributes, MethodImplAttributes,
+            MethodSemanticsAttributes, TypeAttributes,
+        },
         handles::{
-            BaseHandle, ByReferenceSignatureHandle, ConstantStringValueHandle, FieldHandle,
-            FieldSignatureHandle, GenericParameterHandle, MethodHandle, MethodSignatureHandle,
-            MethodTypeVariableSignatureHandle, NamespaceDefinitionHandle, QualifiedMethodHandle,
-            ScopeDefinitionHandle, TypeDefinitionHandle, TypeInstantiationSignatureHandle,
+            ArraySignatureHandle, BaseHandle, ByReferenceSignatureHandle,
+            ConstantStringValueHandle, CustomAttributeHandle, EventHandle, FieldHandle,
+            FieldSignatureHandle, GenericParameterHandle, HandleType, MemberReferenceHandle,
+            MethodHandle, MethodSemanticsHandle, MethodSignatureHandle,
+            MethodTypeVariableSignatureHandle, ModifiedTypeHandle, NamedArgumentHandle,
+            NamespaceDefinitionHandle, NamespaceReferenceHandle, PointerSignatureHandle,
+            PropertyHandle, PropertySignatureHandle, QualifiedFieldHandle, QualifiedMethodHandle,
+            SZArraySignatureHandle, ScopeDefinitionHandle, ScopeReferenceHandle,
+            TypeDefinitionHandle, TypeInstantiationSignatureHandle, TypeReferenceHandle,
             TypeSpecificationHandle, TypeVariableSignatureHandle,
         },
     },
@@ -47,10 +62,18 @@ macro_rules! impl_handle {
 
         impl<'a> $name<'a> {
             pub fn new(reader: $crate::embedded_meta::MetadataReader<'a>, handle: $handle) -> $crate::error::Result<Self> {
-                let mut offset = handle.offset() as usize;
+                reader.validate_offset($crate::embedded_meta::handles::HandleType::$name, handle.offset())?;
+
+                let mut cursor = $crate::native_format::reader::Cursor::new(
+                    reader.stream_reader,
+                    handle.offset() as usize,
+                );
 
                 $(
-                    let $field = reader.stream_reader.read::<$ty>(&mut offset)?;
+                    let field_offset = cursor.offset();
+                    let $field = cursor
+                        .read::<$ty>()
+                        .map_err(|err| err.at(field_offset, concat!(stringify!($name), ".", stringify!($field))))?;
                 )*
 
                 Ok(Self {
@@ -94,7 +117,12 @@ pub struct MetadataReader<'a> {
 
 impl<'a> MetadataReader<'a> {
     pub fn new(data: &'a [u8]) -> Result<Self> {
-        let stream_reader = NativeReader::new(data)?;
+        Self::with_strict_strings(data, false)
+    }
+
+    /// Like [`Self::new`], but fails on strings that aren't valid UTF-8 when `strict_strings` is set
+    pub fn with_strict_strings(data: &'a [u8], strict_strings: bool) -> Result<Self> {
+        let stream_reader = NativeReader::new(data)?.with_strict_strings(strict_strings);
         let header = MetadataHeader::decode(stream_reader)?;
 
         Ok(Self {
@@ -106,6 +134,36 @@ impl<'a> MetadataReader<'a> {
     pub fn header(&self) -> MetadataHeader<'a> {
         self.header
     }
+
+    /// Checks that a record of the given type at `offset` would start within the metadata blob
+    ///
+    /// A wild handle would otherwise only fail on reading its first field, without telling that the handle is bad.
+    pub fn validate_offset(&self, handle_type: HandleType, offset: u32) -> Result<()> {
+        if offset as usize >= self.stream_reader.len() {
+            return Err(AotError::InvalidMetaHandleOffset {
+                handle_type,
+                offset,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Decodes the string at `blob_offset`, which is an offset into the metadata blob rather than a handle value
+    pub fn read_string_at(&self, blob_offset: u32) -> Result<String> {
+        // Anything past the 25 bits of a handle's offset would wrap around
+        if blob_offset > 0x01FFFFFF {
+            return Err(AotError::InvalidMetaHandleOffset {
+                handle_type: HandleType::ConstantStringValue,
+                offset: blob_offset,
+            });
+        }
+
+        let handle = BaseHandle::from_offset(HandleType::ConstantStringValue, blob_offset)
+            .to_handle::<ConstantStringValueHandle>()?;
+
+        Ok(handle.to_data(*self)?.value)
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -154,6 +212,17 @@ impl_handle!(ScopeDefinition, ScopeDefinitionHandle, {
     module_custom_attributes: CustomAttributeHandleCollection<'a>,
 });
 
+impl_handle!(ScopeReference, ScopeReferenceHandle, {
+    flags: u32,
+    name: ConstantStringValueHandle,
+    major_version: u16,
+    minor_version: u16,
+    build_number: u16,
+    revision_number: u16,
+    public_key_or_token: ByteCollection<'a>,
+    culture: ConstantStringValueHandle,
+});
+
 impl_handle!(
     ConstantStringValue,
     ConstantStringValueHandle,
@@ -169,7 +238,7 @@ impl_handle!(NamespaceDefinition, NamespaceDefinitionHandle, {
 });
 
 impl_handle!(TypeDefinition, TypeDefinitionHandle, {
-    flags: u32,
+    flags: TypeAttributes,
     base_type: BaseHandle,
     namespace_definition: NamespaceDefinitionHandle,
     name: ConstantStringValueHandle,
@@ -188,7 +257,7 @@ impl_handle!(TypeDefinition, TypeDefinitionHandle, {
 
 impl_handle!(Method, MethodHandle, {
     flags: MethodAttributes,
-    impl_flags: u32,
+    impl_flags: MethodImplAttributes,
     name: ConstantStringValueHandle,
     signature: MethodSignatureHandle,
     parameters: ParameterHandleCollection<'a>,
@@ -197,7 +266,7 @@ impl_handle!(Method, MethodHandle, {
 });
 
 impl_handle!(Field, FieldHandle, {
-    flags: u32,
+    flags: FieldAttributes,
     name: ConstantStringValueHandle,
     signature: FieldSignatureHandle,
     default_value: BaseHandle,
@@ -210,13 +279,80 @@ impl_handle!(FieldSignature, FieldSignatureHandle, {
 });
 
 impl_handle!(MethodSignature, MethodSignatureHandle, {
-    calling_convention: SignatureCallingConvention,
+    calling_convention: CallingConventionFlags,
     generic_parameter_count: i32,
     return_type: BaseHandle,
     parameters: HandleCollection<'a>,
     var_arg_parameters: HandleCollection<'a>
 });
 
+impl_handle!(Property, PropertyHandle, {
+    flags: u16,
+    name: ConstantStringValueHandle,
+    signature: PropertySignatureHandle,
+    method_semantics: MethodSemanticsHandleCollection<'a>,
+    default_value: BaseHandle,
+    custom_attributes: CustomAttributeHandleCollection<'a>
+});
+
+impl_handle!(PropertySignature, PropertySignatureHandle, {
+    calling_convention: CallingConventionFlags,
+    type_handle: BaseHandle,
+    parameters: HandleCollection<'a>
+});
+
+impl_handle!(Event, EventHandle, {
+    flags: u16,
+    name: ConstantStringValueHandle,
+    type_handle: BaseHandle,
+    method_semantics: MethodSemanticsHandleCollection<'a>,
+    custom_attributes: CustomAttributeHandleCollection<'a>
+});
+
+impl_handle!(MethodSemantics, MethodSemanticsHandle, {
+    attributes: MethodSemanticsAttributes,
+    method: MethodHandle
+});
+
+impl_handle!(CustomAttribute, CustomAttributeHandle, {
+    constructor: BaseHandle,
+    fixed_arguments: HandleCollection<'a>,
+    named_arguments: NamedArgumentHandleCollection<'a>
+});
+
+impl_handle!(NamedArgument, NamedArgumentHandle, {
+    kind: u8,
+    name: ConstantStringValueHandle,
+    type_handle: BaseHandle,
+    value: BaseHandle
+});
+
+impl_handle!(MemberReference, MemberReferenceHandle, {
+    parent: BaseHandle,
+    name: ConstantStringValueHandle,
+    signature: BaseHandle
+});
+
+impl_handle!(TypeReference, TypeReferenceHandle, {
+    parent_namespace_or_type: BaseHandle,
+    type_name: ConstantStringValueHandle
+});
+
+impl_handle!(NamespaceReference, NamespaceReferenceHandle, {
+    parent_scope_or_namespace: BaseHandle,
+    name: ConstantStringValueHandle
+});
+
+impl_handle!(QualifiedField, QualifiedFieldHandle, {
+    field: FieldHandle,
+    enclosing_type: TypeDefinitionHandle
+});
+
+impl_handle!(QualifiedMethod, QualifiedMethodHandle, {
+    method: MethodHandle,
+    enclosing_type: TypeDefinitionHandle
+});
+
 impl_handle!(TypeSpecification, TypeSpecificationHandle, {
     signature: BaseHandle
 });
@@ -230,6 +366,27 @@ impl_handle!(ByReferenceSignature, ByReferenceSignatureHandle, {
     type_handle: BaseHandle
 });
 
+// A `modreq` or `modopt`, e.g. `modreq(InAttribute)` on the return type of a `ref readonly` method
+impl_handle!(ModifiedType, ModifiedTypeHandle, {
+    is_optional: bool,
+    modifier_type: BaseHandle,
+    type_handle: BaseHandle
+});
+
+// Followed by the sizes and lower bounds of the dimensions
+impl_handle!(ArraySignature, ArraySignatureHandle, {
+    element_type: BaseHandle,
+    rank: i32,
+});
+
+impl_handle!(SZArraySignature, SZArraySignatureHandle, {
+    element_type: BaseHandle
+});
+
+impl_handle!(PointerSignature, PointerSignatureHandle, {
+    type_handle: BaseHandle
+});
+
 impl_handle!(MethodTypeVariableSignature, MethodTypeVariableSignatureHandle, {
     number: i32
 });
@@ -246,3 +403,51 @@ impl_handle!(GenericParameter, GenericParameterHandle, {
     constraints: HandleCollection<'a>,
     custom_attributes: CustomAttributeHandleCollection<'a>,
 });
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        embedded_meta::{
+            MetadataReader,
+            handles::{Handle, HandleType, TypeDefinitionHandle},
+        },
+        error::AotError,
+        test_utils::minimal_metadata,
+    };
+
+    #[test]
+    fn read_string_at_decodes_raw_offsets() {
+        let (blob, _) = minimal_metadata();
+        let reader = MetadataReader::new(&blob).unwrap();
+        let typ = reader.find_type("Game.Player").unwrap().unwrap();
+
+        assert_eq!(reader.read_string_at(typ.name.offset()).unwrap(), "Player");
+        assert!(matches!(
+            reader.read_string_at(blob.len() as u32),
+            Err(AotError::InvalidMetaHandleOffset { .. })
+        ));
+        assert!(matches!(
+            reader.read_string_at(0x0200_0000 | typ.name.offset()),
+            Err(AotError::InvalidMetaHandleOffset { .. })
+        ));
+    }
+
+    #[test]
+    fn to_data_rejects_offsets_past_the_blob() {
+        let (blob, offsets) = minimal_metadata();
+        let reader = MetadataReader::new(&blob).unwrap();
+        let handle = |offset: u32| {
+            TypeDefinitionHandle::from_value((HandleType::TypeDefinition as u32) << 25 | offset)
+                .unwrap()
+        };
+
+        assert!(handle(offsets.typ).to_data(reader).is_ok());
+        assert!(matches!(
+            handle(blob.len() as u32 + 0x100).to_data(reader),
+            Err(AotError::InvalidMetaHandleOffset {
+                handle_type: HandleType::TypeDefinition,
+                offset
+            }) if offset == blob.len() as u32 + 0x100
+        ));
+    }
+}
@@ -0,0 +1,137 @@
+use crate::{
+    embedded_meta::{
+        Field, MetadataReader, Method, NamespaceDefinition, ScopeDefinition, TypeDefinition,
+        handles::{BaseHandle, ConstantStringValueHandle, HandleType},
+        visitor::HandleVisitor,
+    },
+    error::Result,
+};
+
+impl<'a> MetadataReader<'a> {
+    /// Every offset in the metadata stream that looks like the start of a record of the given kind
+    ///
+    /// This is a heuristic scan rather than a structured walk: the stream doesn't say which record starts where, so
+    /// every offset is decoded as a record of `handle_type` and kept if its names and references decode. It finds
+    /// records that aren't reachable from a scope, like types missing from the namespace graph that
+    /// [`ScopeDefinition::get_all_types`] would miss, but it can also report offsets that merely happen to decode.
+    ///
+    /// Only scopes, namespaces, types, methods and fields can be scanned for, other kinds yield nothing.
+    pub fn scan_handles(&self, handle_type: HandleType) -> impl Iterator<Item = BaseHandle> + 'a {
+        let reader = *self;
+
+        // The header starts with the signature, nothing can be stored there
+        (4..reader.stream_reader.len() as u32)
+            .map(move |offset| BaseHandle::from_offset(handle_type, offset))
+            .filter(move |handle| {
+                handle
+                    .visit(reader, &mut PlausibleRecord { reader })
+                    .unwrap_or(false)
+            })
+    }
+}
+
+/// Checks whether a decoded record is likely to be real, see [`MetadataReader::scan_handles`]
+struct PlausibleRecord<'a> {
+    reader: MetadataReader<'a>,
+}
+
+impl<'a> PlausibleRecord<'a> {
+    /// Names are never empty, and almost never contain control characters
+    fn is_name(&self, handle: ConstantStringValueHandle) -> bool {
+        handle
+            .to_data(self.reader)
+            .is_ok_and(|name| !name.value.is_empty() && !name.value.chars().any(char::is_control))
+    }
+}
+
+impl<'a> HandleVisitor<'a> for PlausibleRecord<'a> {
+    type Output = bool;
+
+    fn visit_other(&mut self, _handle: BaseHandle) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn visit_scope_definition(&mut self, scope: ScopeDefinition<'a>) -> Result<bool> {
+        // The root namespace points back to its scope
+        let handle = BaseHandle::from_offset(HandleType::ScopeDefinition, scope.handle().offset());
+
+        Ok(self.is_name(scope.name)
+            && scope
+                .root_namespace_definition
+                .to_data(self.reader)
+                .is_ok_and(|root| root.parent_scope_or_namespace == handle))
+    }
+
+    fn visit_namespace_definition(&mut self, namespace: NamespaceDefinition<'a>) -> Result<bool> {
+        // Only the root namespace of a scope has no name
+        let parent = namespace.parent_scope_or_namespace.handle_type();
+
+        Ok(match parent {
+            Some(HandleType::ScopeDefinition) => namespace.name.is_nil(),
+            Some(HandleType::NamespaceDefinition) => self.is_name(namespace.name),
+            _ => false,
+        })
+    }
+
+    fn visit_type_definition(&mut self, typ: TypeDefinition<'a>) -> Result<bool> {
+        let base_type = typ.base_type.is_nil()
+            || matches!(
+                typ.base_type.handle_type(),
+                Some(
+                    HandleType::TypeDefinition
+                        | HandleType::TypeReference
+                        | HandleType::TypeSpecification
+                )
+            );
+
+        Ok(base_type
+            && self.is_name(typ.name)
+            && typ
+                .namespace_definition
+                .to_data(self.reader)
+                .is_ok_and(|namespace| {
+                    matches!(
+                        namespace.parent_scope_or_namespace.handle_type(),
+                        Some(HandleType::ScopeDefinition | HandleType::NamespaceDefinition)
+                    )
+                }))
+    }
+
+    fn visit_method(&mut self, method: Method<'a>) -> Result<bool> {
+        Ok(self.is_name(method.name) && method.signature.to_data(self.reader).is_ok())
+    }
+
+    fn visit_field(&mut self, field: Field<'a>) -> Result<bool> {
+        Ok(self.is_name(field.name) && field.signature.to_data(self.reader).is_ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        embedded_meta::{MetadataReader, handles::HandleType},
+        test_utils::minimal_metadata,
+    };
+
+    #[test]
+    fn scan_finds_records_by_kind() {
+        let (blob, offsets) = minimal_metadata();
+        let reader = MetadataReader::new(&blob).unwrap();
+        let scan = |handle_type| {
+            reader
+                .scan_handles(handle_type)
+                .map(|handle| handle.offset())
+                .collect::<Vec<_>>()
+        };
+
+        // The zeroes in the middle of the type happen to decode as a type with the same name too
+        assert!(scan(HandleType::TypeDefinition).contains(&offsets.typ));
+        assert_eq!(scan(HandleType::Method), [offsets.method]);
+        assert_eq!(scan(HandleType::ScopeDefinition), [offsets.scope]);
+        assert_eq!(
+            scan(HandleType::NamespaceDefinition),
+            [offsets.namespace, offsets.root_namespace]
+        );
+        assert!(scan(HandleType::ConstantStringValue).is_empty());
+    }
+}
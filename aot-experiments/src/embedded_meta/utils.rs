@@ -1,17 +1,121 @@
+#[cfg(feature = "native")]
+use crate::binary::headers::mt::ElementType;
 use crate::{
     embedded_meta::{
-        MetadataReader, NamespaceDefinition, ScopeDefinition, TypeDefinition,
-        handles::{GenericParameterHandle, Handle, NamespaceDefinitionHandle},
+        Event, Field, MetadataReader, Method, MethodSignature, NamespaceDefinition, Property,
+        ScopeDefinition, ScopeReference, TypeDefinition, TypeReference,
+        collections::MethodSemanticsHandleCollection,
+        flags::{MethodSemanticsAttributes, TypeLayout, VtableLayout},
+        handles::{
+            ArraySignatureHandle, BaseHandle, ByReferenceSignatureHandle, GenericParameterHandle,
+            Handle, MethodTypeVariableSignatureHandle, ModifiedTypeHandle,
+            NamespaceDefinitionHandle, NamespaceReferenceHandle, PointerSignatureHandle,
+            SZArraySignatureHandle, ScopeDefinitionHandle, ScopeReferenceHandle,
+            TypeDefinitionHandle, TypeInstantiationSignatureHandle, TypeReferenceHandle,
+            TypeSpecificationHandle, TypeVariableSignatureHandle,
+        },
+        hashing::{compute_name_hashcode, compute_nested_type_hashcode},
     },
-    error::Result,
+    error::{AotError, Result},
 };
 
 use super::handles::HandleType;
 
+// Helper functions for the MetadataReader
+impl<'a> MetadataReader<'a> {
+    /// Finds the program entrypoint, erroring if more than one scope defines one
+    pub fn entrypoint(&self) -> Result<Option<(TypeDefinition<'a>, Method<'a>)>> {
+        let mut entrypoint = None;
+
+        for scope in self
+            .header()
+            .scope_definitions()
+            .iter()?
+            .flatten()
+            .flat_map(|hdl| hdl.to_data(*self))
+        {
+            if scope.entrypoint.is_nil() {
+                continue;
+            }
+
+            if entrypoint.is_some() {
+                return Err(AotError::AmbiguousEntrypoint);
+            }
+
+            let qualified = scope.entrypoint.to_data(*self)?;
+            let typ = qualified.enclosing_type.to_data(*self)?;
+            let method = qualified.method.to_data(*self)?;
+
+            entrypoint = Some((typ, method));
+        }
+
+        Ok(entrypoint)
+    }
+
+    /// Finds a type by its full name (e.g. `HytaleClient.Program`) in any of the scopes
+    pub fn find_type(&self, full_name: &str) -> Result<Option<TypeDefinition<'a>>> {
+        for scope in self
+            .header()
+            .scope_definitions()
+            .iter()?
+            .flatten()
+            .flat_map(|hdl| hdl.to_data(*self))
+        {
+            let Ok(root) = scope.root_namespace_definition.to_data(*self) else {
+                continue;
+            };
+
+            if let Some(typ) = root.find_type(full_name) {
+                return Ok(Some(typ));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Gets told about the records a metadata traversal decodes, e.g. to render progress
+///
+/// Passed to the `_with_observer` variants of the traversals, like [`ScopeDefinition::get_all_types_with_observer`].
+/// `()` observes nothing.
+pub trait DecodeObserver<'a> {
+    fn on_scope(&mut self, _scope: &ScopeDefinition<'a>) {}
+
+    fn on_type(&mut self, _typ: &TypeDefinition<'a>) {}
+}
+
+impl<'a> DecodeObserver<'a> for () {}
+
 // Helper functions for ScopeDefinitions
 impl<'a> ScopeDefinition<'a> {
     pub fn get_all_types(&self) -> Result<Vec<TypeDefinition<'a>>> {
+        self.get_all_types_with_observer(&mut ())
+    }
+
+    /// Like [`Self::get_all_types`], telling the observer about the scope and every type it decodes
+    pub fn get_all_types_with_observer(
+        &self,
+        observer: &mut impl DecodeObserver<'a>,
+    ) -> Result<Vec<TypeDefinition<'a>>> {
         let mut types = vec![];
+
+        observer.on_scope(self);
+
+        for typ in self
+            .type_handles()
+            .into_iter()
+            .flat_map(|hdl| hdl.to_data(self.reader))
+        {
+            observer.on_type(&typ);
+            types.push(typ);
+        }
+
+        Ok(types)
+    }
+
+    /// The handles of the types [`Self::get_all_types`] returns, in the same order, without decoding the types
+    pub fn type_handles(&self) -> Vec<TypeDefinitionHandle> {
+        let mut handles = vec![];
         let mut stack = vec![];
 
         stack.push(self.root_namespace_definition);
@@ -29,16 +133,80 @@ impl<'a> ScopeDefinition<'a> {
                 continue;
             };
 
-            types.extend(type_iter.flatten().flat_map(|hdl| hdl.to_data(self.reader)));
+            handles.extend(type_iter.flatten());
             stack.extend(ns_iter.flatten());
         }
 
-        Ok(types)
+        handles
+    }
+
+    /// The `<Module>` type, holding the module-level functions and fields of the scope
+    ///
+    /// It normally isn't listed under any namespace, so `get_all_types` doesn't return it.
+    pub fn module_type(&self) -> Result<Option<TypeDefinition<'a>>> {
+        if self.global_module_type.is_nil() {
+            return Ok(None);
+        }
+
+        self.global_module_type.to_data(self.reader).map(Some)
+    }
+}
+
+// Helper functions for TypeReferences
+impl<'a> TypeReference<'a> {
+    /// The scope reference (assembly) this type is expected to be found in, following enclosing types and namespaces
+    pub fn owning_scope(&self) -> Result<ScopeReference<'a>> {
+        let mut parent = self.parent_namespace_or_type;
+
+        loop {
+            match parent.handle_type() {
+                Some(HandleType::ScopeReference) => {
+                    return parent
+                        .to_handle::<ScopeReferenceHandle>()?
+                        .to_data(self.reader);
+                }
+                Some(HandleType::NamespaceReference) => {
+                    parent = parent
+                        .to_handle::<NamespaceReferenceHandle>()?
+                        .to_data(self.reader)?
+                        .parent_scope_or_namespace;
+                }
+                Some(HandleType::TypeReference) => {
+                    parent = parent
+                        .to_handle::<TypeReferenceHandle>()?
+                        .to_data(self.reader)?
+                        .parent_namespace_or_type;
+                }
+                _ => return Err(AotError::InvalidMetaHandle),
+            }
+        }
     }
 }
 
 // Helper functions for NamespaceDefinitions
 impl<'a> NamespaceDefinition<'a> {
+    /// The scope (assembly) this namespace belongs to, found by following the parents up to the root namespace
+    pub fn owning_scope(&self) -> Result<ScopeDefinition<'a>> {
+        let mut parent = self.parent_scope_or_namespace;
+
+        loop {
+            match parent.handle_type() {
+                Some(HandleType::ScopeDefinition) => {
+                    return parent
+                        .to_handle::<ScopeDefinitionHandle>()?
+                        .to_data(self.reader);
+                }
+                Some(HandleType::NamespaceDefinition) => {
+                    parent = parent
+                        .to_handle::<NamespaceDefinitionHandle>()?
+                        .to_data(self.reader)?
+                        .parent_scope_or_namespace;
+                }
+                _ => return Err(AotError::InvalidMetaHandle),
+            }
+        }
+    }
+
     pub fn find_type(&self, name: &str) -> Option<TypeDefinition<'a>> {
         let mut segments = name.split(".").peekable();
         let mut current_ns = self.handle;
@@ -93,6 +261,23 @@ impl<'a> TypeDefinition<'a> {
     pub fn get_full_name(&self) -> Result<String> {
         let type_name = self.name.to_data(self.reader)?.value;
 
+        Ok(format!("{}.{type_name}", self.namespace_name()?))
+    }
+
+    /// The scope (assembly) that defines this type, see [`NamespaceDefinition::owning_scope`]
+    pub fn owning_scope(&self) -> Result<ScopeDefinition<'a>> {
+        // Nested types can leave their namespace to the enclosing type
+        if self.namespace_definition.is_nil() && !self.enclosing_type.is_nil() {
+            return self.enclosing_type.to_data(self.reader)?.owning_scope();
+        }
+
+        self.namespace_definition
+            .to_data(self.reader)?
+            .owning_scope()
+    }
+
+    /// The dotted name of the namespace this type is declared in, empty for the root namespace
+    pub fn namespace_name(&self) -> Result<String> {
         // Enumerate over namespaces
         let mut ns_handle = self.namespace_definition.to_base();
         let mut ns_names = Vec::new();
@@ -114,10 +299,184 @@ impl<'a> TypeDefinition<'a> {
             ns_handle = namespace.parent_scope_or_namespace;
         }
 
-        Ok(format!(
-            "{}.{type_name}",
-            ns_names.into_iter().rev().collect::<Vec<_>>().join("."),
-        ))
+        Ok(ns_names.into_iter().rev().collect::<Vec<_>>().join("."))
+    }
+
+    /// Computes the hashcode the compiler stores in the MethodTable of this type
+    ///
+    /// Only valid for the type definition itself, instantiations of generic types hash their type arguments too
+    /// (see [`compute_generic_instance_hashcode`](super::hashing::compute_generic_instance_hashcode)).
+    pub fn runtime_hashcode(&self) -> Result<i32> {
+        let name = self.name.to_data(self.reader)?.value;
+
+        // Nested types are hashed by their own name, combined with the hashcode of the enclosing type
+        if !self.enclosing_type.is_nil() {
+            let enclosing_type = self.enclosing_type.to_data(self.reader)?;
+
+            return Ok(compute_nested_type_hashcode(
+                enclosing_type.runtime_hashcode()?,
+                compute_name_hashcode(&name),
+            ));
+        }
+
+        let namespace = self.namespace_name()?;
+        if namespace.is_empty() {
+            Ok(compute_name_hashcode(&name))
+        } else {
+            Ok(compute_name_hashcode(&format!("{namespace}.{name}")))
+        }
+    }
+
+    /// The declared size and packing of a type with a sequential or explicit layout
+    ///
+    /// Returns `None` for auto-layout types, where the size is computed by the compiler instead.
+    pub fn declared_layout(&self) -> Option<DeclaredLayout> {
+        let kind = self.flags.layout();
+        if kind == TypeLayout::Auto {
+            return None;
+        }
+
+        Some(DeclaredLayout {
+            kind,
+            size: (self.size != 0).then_some(self.size),
+            packing_size: (self.packing_size != 0).then_some(self.packing_size),
+        })
+    }
+
+    /// Walks the base types of this type, starting at its direct base type
+    ///
+    /// Stops at the first base type that isn't defined in this metadata (e.g. a `TypeReference`)
+    pub fn base_chain(&self) -> BaseChain<'a> {
+        BaseChain {
+            reader: self.reader,
+            next: self.base_type,
+            remaining: BaseChain::MAX_DEPTH,
+        }
+    }
+
+    pub fn is_subclass_of(&self, full_name: &str) -> bool {
+        self.base_chain()
+            .any(|base| base.get_full_name().is_ok_and(|name| name == full_name))
+    }
+
+    pub fn is_delegate(&self) -> bool {
+        self.is_subclass_of("System.MulticastDelegate")
+    }
+
+    /// Whether this is an enum, checked by name as `System.Enum` is usually a reference into another scope
+    pub fn is_enum(&self) -> bool {
+        !self.base_type.is_nil()
+            && canonical_type_name(self.reader, self.base_type)
+                .is_ok_and(|name| name == "System.Enum")
+    }
+
+    /// Whether this is a struct, checked by name like [`Self::is_enum`]
+    pub fn is_value_type(&self) -> bool {
+        !self.base_type.is_nil()
+            && canonical_type_name(self.reader, self.base_type)
+                .is_ok_and(|name| name == "System.ValueType")
+    }
+
+    /// The integral type of an enum, which is the type of its `value__` instance field
+    #[cfg(feature = "native")]
+    pub fn enum_underlying_type(&self) -> Option<ElementType> {
+        if !self.is_enum() {
+            return None;
+        }
+
+        let field = self.find_field("value__").ok()??;
+        let signature = field.signature.to_data(self.reader).ok()?;
+
+        ElementType::from_primitive_name(
+            &canonical_type_name(self.reader, signature.type_handle).ok()?,
+        )
+    }
+
+    pub fn find_method(&self, name: &str) -> Result<Option<Method<'a>>> {
+        for method in self
+            .methods
+            .iter()?
+            .flatten()
+            .flat_map(|hdl| hdl.to_data(self.reader))
+        {
+            if method.name.to_data(self.reader)?.value == name {
+                return Ok(Some(method));
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub fn find_field(&self, name: &str) -> Result<Option<Field<'a>>> {
+        for field in self
+            .fields
+            .iter()?
+            .flatten()
+            .flat_map(|hdl| hdl.to_data(self.reader))
+        {
+            if field.name.to_data(self.reader)?.value == name {
+                return Ok(Some(field));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Replays the vtable slot assignment of this type on its metadata, returning the method in every slot
+    ///
+    /// Base types come first, `NewSlot` methods append a slot and `ReuseSlot` methods take over the slot of the base
+    /// method with the same name and signature. Sealed methods that don't override anything go to the sealed vtable,
+    /// and generic virtual methods never get a slot. Returns `None` when a base type isn't defined in this metadata.
+    ///
+    /// The compiler drops slots that are never called, so the result may be longer than the vtable of the MethodTable.
+    pub fn virtual_slots(&self) -> Result<Option<Vec<Method<'a>>>> {
+        let mut hierarchy = self.base_chain().collect::<Vec<_>>();
+        // The chain stops early at base types that aren't defined here, like a `TypeReference`
+        if !hierarchy.last().unwrap_or(self).base_type.is_nil() {
+            return Ok(None);
+        }
+
+        hierarchy.reverse();
+        hierarchy.push(self.clone());
+
+        let mut slots: Vec<(String, Method<'a>)> = Vec::new();
+
+        for typ in &hierarchy {
+            for method in typ
+                .methods
+                .iter()?
+                .flatten()
+                .flat_map(|hdl| hdl.to_data(self.reader))
+            {
+                if !method.flags.is_virtual() || method.flags.is_static() {
+                    continue;
+                }
+
+                let signature = method.signature.to_data(self.reader)?;
+                if signature.generic_parameter_count != 0 {
+                    continue;
+                }
+
+                let key = format!(
+                    "{} {}",
+                    method.name.to_data(self.reader)?.value,
+                    signature.canonical_string()?
+                );
+
+                let overridden = match method.flags.vtable_layout() {
+                    VtableLayout::ReuseSlot => slots.iter().position(|(name, _)| *name == key),
+                    VtableLayout::NewSlot => None,
+                };
+
+                match overridden {
+                    Some(slot) => slots[slot].1 = method,
+                    None if method.flags.is_final() => {}
+                    None => slots.push((key, method)),
+                }
+            }
+        }
+
+        Ok(Some(slots.into_iter().map(|(_, method)| method).collect()))
     }
 
     pub fn get_full_name_with_generics(&self) -> Result<String> {
@@ -131,7 +490,7 @@ impl<'a> TypeDefinition<'a> {
                     let name = param.name.to_data(self.reader)?;
                     acc.push(name.value);
 
-                    Ok::<_, anyhow::Error>(acc)
+                    Ok::<_, AotError>(acc)
                 })
                 .ok()?;
 
@@ -145,3 +504,783 @@ impl<'a> TypeDefinition<'a> {
         Ok(format!("{full_name}{}", generics.as_deref().unwrap_or("")))
     }
 }
+
+// Helper functions for BaseHandles
+impl BaseHandle {
+    /// Unwraps instantiations, byrefs, arrays and pointers down to the (open generic) type definition, e.g. `List<>`
+    /// for `ref List<int>[]`
+    ///
+    /// References to types of other scopes are resolved by name. Returns `None` for generic variables, function
+    /// pointers and types that aren't defined in this metadata.
+    pub fn resolve_to_type_definition(
+        self,
+        reader: MetadataReader<'_>,
+    ) -> Option<TypeDefinitionHandle> {
+        let mut handle = self;
+
+        for _ in 0..MAX_SIGNATURE_DEPTH {
+            handle = match handle.handle_type()? {
+                HandleType::TypeDefinition => return handle.to_handle().ok(),
+                HandleType::TypeReference => {
+                    let full_name = handle
+                        .to_handle::<TypeReferenceHandle>()
+                        .ok()?
+                        .to_data(reader)
+                        .ok()?
+                        .get_full_name()
+                        .ok()?;
+
+                    return Some(reader.find_type(&full_name).ok()??.handle());
+                }
+                HandleType::TypeSpecification => {
+                    handle
+                        .to_handle::<TypeSpecificationHandle>()
+                        .ok()?
+                        .to_data(reader)
+                        .ok()?
+                        .signature
+                }
+                HandleType::TypeInstantiationSignature => {
+                    handle
+                        .to_handle::<TypeInstantiationSignatureHandle>()
+                        .ok()?
+                        .to_data(reader)
+                        .ok()?
+                        .generic_type
+                }
+                HandleType::ByReferenceSignature => {
+                    handle
+                        .to_handle::<ByReferenceSignatureHandle>()
+                        .ok()?
+                        .to_data(reader)
+                        .ok()?
+                        .type_handle
+                }
+                HandleType::SZArraySignature => {
+                    handle
+                        .to_handle::<SZArraySignatureHandle>()
+                        .ok()?
+                        .to_data(reader)
+                        .ok()?
+                        .element_type
+                }
+                HandleType::ArraySignature => {
+                    handle
+                        .to_handle::<ArraySignatureHandle>()
+                        .ok()?
+                        .to_data(reader)
+                        .ok()?
+                        .element_type
+                }
+                HandleType::PointerSignature => {
+                    handle
+                        .to_handle::<PointerSignatureHandle>()
+                        .ok()?
+                        .to_data(reader)
+                        .ok()?
+                        .type_handle
+                }
+                HandleType::ModifiedType => {
+                    handle
+                        .to_handle::<ModifiedTypeHandle>()
+                        .ok()?
+                        .to_data(reader)
+                        .ok()?
+                        .type_handle
+                }
+                _ => return None,
+            };
+        }
+
+        None
+    }
+}
+
+// Helper functions for MethodSignatures
+impl<'a> MethodSignature<'a> {
+    /// A normalized form of the signature that doesn't depend on the reader, e.g. `HasThis`1 System.Void(!!0, !0&)`
+    ///
+    /// Types are written by their full name, and generic parameters by their index (`!n` for type parameters, `!!n`
+    /// for method parameters), so overrides and duplicates of a method share the same string.
+    pub fn canonical_string(&self) -> Result<String> {
+        let mut parameters = Vec::new();
+        for handle in self.parameters.iter()? {
+            parameters.push(canonical_type_name(self.reader, handle?)?);
+        }
+
+        let generic_arity = match self.generic_parameter_count {
+            0 => String::new(),
+            count => format!("`{count}"),
+        };
+
+        Ok(format!(
+            "{}{generic_arity} {}({})",
+            self.calling_convention,
+            canonical_type_name(self.reader, self.return_type)?,
+            parameters.join(", ")
+        ))
+    }
+}
+
+/// The name of a type or signature, independent of the reader it's read from
+///
+/// Nested types are written after their enclosing type, separated by a `+` like in reflection.
+pub fn canonical_type_name(reader: MetadataReader<'_>, handle: BaseHandle) -> Result<String> {
+    canonical_type_name_at(reader, handle, 0)
+}
+
+// Guards against cycles in corrupt signatures, which would otherwise never end or overflow the stack
+const MAX_SIGNATURE_DEPTH: usize = 64;
+
+fn canonical_type_name_at(
+    reader: MetadataReader<'_>,
+    handle: BaseHandle,
+    depth: usize,
+) -> Result<String> {
+    if depth >= MAX_SIGNATURE_DEPTH {
+        return Err(AotError::BadImage);
+    }
+
+    let nested = |handle| canonical_type_name_at(reader, handle, depth + 1);
+
+    let name = match handle.handle_type() {
+        _ if handle.is_nil() => "System.Void".to_string(),
+        Some(HandleType::TypeDefinition) => {
+            let typ = handle
+                .to_handle::<TypeDefinitionHandle>()?
+                .to_data(reader)?;
+
+            if typ.enclosing_type.is_nil() {
+                typ.get_full_name()?
+            } else {
+                format!(
+                    "{}+{}",
+                    nested(typ.enclosing_type.to_base())?,
+                    typ.name.to_data(reader)?.value
+                )
+            }
+        }
+        Some(HandleType::TypeReference) => {
+            let typ = handle.to_handle::<TypeReferenceHandle>()?.to_data(reader)?;
+
+            if typ.parent_namespace_or_type.handle_type() == Some(HandleType::TypeReference) {
+                format!(
+                    "{}+{}",
+                    nested(typ.parent_namespace_or_type)?,
+                    typ.type_name.to_data(reader)?.value
+                )
+            } else {
+                typ.get_full_name()?
+            }
+        }
+        Some(HandleType::TypeSpecification) => nested(
+            handle
+                .to_handle::<TypeSpecificationHandle>()?
+                .to_data(reader)?
+                .signature,
+        )?,
+        Some(HandleType::TypeInstantiationSignature) => {
+            let instantiation = handle
+                .to_handle::<TypeInstantiationSignatureHandle>()?
+                .to_data(reader)?;
+
+            let mut arguments = Vec::new();
+            for argument in instantiation.generic_args.iter()? {
+                arguments.push(nested(argument?)?);
+            }
+
+            format!(
+                "{}<{}>",
+                nested(instantiation.generic_type)?,
+                arguments.join(", ")
+            )
+        }
+        Some(HandleType::ByReferenceSignature) => format!(
+            "{}&",
+            nested(
+                handle
+                    .to_handle::<ByReferenceSignatureHandle>()?
+                    .to_data(reader)?
+                    .type_handle
+            )?
+        ),
+        Some(HandleType::SZArraySignature) => format!(
+            "{}[]",
+            nested(
+                handle
+                    .to_handle::<SZArraySignatureHandle>()?
+                    .to_data(reader)?
+                    .element_type
+            )?
+        ),
+        Some(HandleType::ArraySignature) => {
+            let array = handle
+                .to_handle::<ArraySignatureHandle>()?
+                .to_data(reader)?;
+
+            format!(
+                "{}[{}]",
+                nested(array.element_type)?,
+                ",".repeat(array.rank.max(1) as usize - 1)
+            )
+        }
+        Some(HandleType::PointerSignature) => format!(
+            "{}*",
+            nested(
+                handle
+                    .to_handle::<PointerSignatureHandle>()?
+                    .to_data(reader)?
+                    .type_handle
+            )?
+        ),
+        // Modifiers are part of the identity of a signature, and are written the way ILAsm does
+        Some(HandleType::ModifiedType) => {
+            let modified = handle.to_handle::<ModifiedTypeHandle>()?.to_data(reader)?;
+
+            format!(
+                "{} {}({})",
+                nested(modified.type_handle)?,
+                if modified.is_optional {
+                    "modopt"
+                } else {
+                    "modreq"
+                },
+                nested(modified.modifier_type)?
+            )
+        }
+        Some(HandleType::TypeVariableSignature) => format!(
+            "!{}",
+            handle
+                .to_handle::<TypeVariableSignatureHandle>()?
+                .to_data(reader)?
+                .number
+        ),
+        Some(HandleType::MethodTypeVariableSignature) => format!(
+            "!!{}",
+            handle
+                .to_handle::<MethodTypeVariableSignatureHandle>()?
+                .to_data(reader)?
+                .number
+        ),
+        handle_type => format!("{:?}", handle_type.unwrap_or(HandleType::Invalid)),
+    };
+
+    Ok(name)
+}
+
+impl<'a> Method<'a> {
+    /// Whether this is a default implementation of an interface method, i.e. a non-abstract virtual method on an
+    /// interface
+    pub fn is_default_interface_method(&self, declaring_type: &TypeDefinition<'a>) -> bool {
+        declaring_type.flags.is_interface()
+            && self.flags.is_virtual()
+            && !self.flags.is_abstract()
+            && !self.flags.is_static()
+    }
+}
+
+impl<'a> Property<'a> {
+    pub fn getter(&self) -> Result<Option<Method<'a>>> {
+        semantic_method(self.reader, self.method_semantics, |attributes| {
+            attributes.is_getter()
+        })
+    }
+
+    pub fn setter(&self) -> Result<Option<Method<'a>>> {
+        semantic_method(self.reader, self.method_semantics, |attributes| {
+            attributes.is_setter()
+        })
+    }
+}
+
+impl<'a> Event<'a> {
+    pub fn add(&self) -> Result<Option<Method<'a>>> {
+        semantic_method(self.reader, self.method_semantics, |attributes| {
+            attributes.is_add_on()
+        })
+    }
+
+    pub fn remove(&self) -> Result<Option<Method<'a>>> {
+        semantic_method(self.reader, self.method_semantics, |attributes| {
+            attributes.is_remove_on()
+        })
+    }
+
+    /// C# never emits a raise method, so this is only set for events declared in other languages
+    pub fn raise(&self) -> Result<Option<Method<'a>>> {
+        semantic_method(self.reader, self.method_semantics, |attributes| {
+            attributes.is_fire()
+        })
+    }
+}
+
+/// The first accessor of a property or event with the given semantics
+fn semantic_method<'a>(
+    reader: MetadataReader<'a>,
+    semantics: MethodSemanticsHandleCollection<'a>,
+    matches: impl Fn(MethodSemanticsAttributes) -> bool,
+) -> Result<Option<Method<'a>>> {
+    for handle in semantics.iter()? {
+        let semantics = handle?.to_data(reader)?;
+
+        if matches(semantics.attributes) {
+            return semantics.method.to_data(reader).map(Some);
+        }
+    }
+
+    Ok(None)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeclaredLayout {
+    pub kind: TypeLayout,
+    /// `None` when the size is left to the compiler (e.g. a `StructLayout` without a `Size`)
+    pub size: Option<u32>,
+    /// `None` when the default packing is used
+    pub packing_size: Option<u16>,
+}
+
+pub struct BaseChain<'a> {
+    reader: MetadataReader<'a>,
+    next: BaseHandle,
+    remaining: usize,
+}
+
+impl BaseChain<'_> {
+    // Guards against cycles in corrupt metadata
+    const MAX_DEPTH: usize = 256;
+}
+
+impl<'a> Iterator for BaseChain<'a> {
+    type Item = TypeDefinition<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next.is_nil() || self.remaining == 0 {
+            return None;
+        }
+
+        self.remaining -= 1;
+
+        let handle = match self.next.handle_type()? {
+            HandleType::TypeDefinition => self.next.to_handle::<TypeDefinitionHandle>().ok()?,
+            // Generic base types, e.g. `List<T>`
+            HandleType::TypeSpecification => {
+                let spec = self
+                    .next
+                    .to_handle::<TypeSpecificationHandle>()
+                    .ok()?
+                    .to_data(self.reader)
+                    .ok()?;
+
+                spec.signature
+                    .to_handle::<TypeInstantiationSignatureHandle>()
+                    .ok()?
+                    .to_data(self.reader)
+                    .ok()?
+                    .generic_type
+                    .to_handle::<TypeDefinitionHandle>()
+                    .ok()?
+            }
+            _ => return None,
+        };
+
+        let typ = handle.to_data(self.reader).ok()?;
+        self.next = typ.base_type;
+
+        Some(typ)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "native")]
+    use crate::binary::headers::mt::ElementType;
+    use crate::{
+        embedded_meta::{
+            MetadataReader,
+            handles::{BaseHandle, HandleType, MethodSignatureHandle, TypeDefinitionHandle},
+            hashing::compute_name_hashcode,
+            utils::canonical_type_name,
+        },
+        test_utils::{MetadataBuilder, minimal_metadata},
+    };
+
+    #[test]
+    fn minimal_metadata_decodes() {
+        let (blob, offsets) = minimal_metadata();
+        let reader = MetadataReader::new(&blob).unwrap();
+
+        let scopes = reader
+            .header()
+            .scope_definitions()
+            .iter()
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(scopes.len(), 1);
+        assert_eq!(scopes[0].offset(), offsets.scope);
+
+        let scope = scopes[0].to_data(reader).unwrap();
+        assert_eq!(scope.name.to_data(reader).unwrap().value, "Hytale.Test");
+        assert_eq!(
+            (
+                scope.major_version,
+                scope.minor_version,
+                scope.build_number,
+                scope.revision_number
+            ),
+            (1, 2, 3, 4)
+        );
+
+        let types = scope.get_all_types().unwrap();
+        assert_eq!(types.len(), 1);
+        assert_eq!(types[0].handle().offset(), offsets.typ);
+        assert_eq!(types[0].get_full_name().unwrap(), "Game.Player");
+
+        let method = types[0].find_method("Jump").unwrap().unwrap();
+        assert_eq!(method.handle().offset(), offsets.method);
+
+        let signature = method.signature.to_data(reader).unwrap();
+        assert!(signature.return_type.is_nil());
+        assert_eq!(signature.parameters.count().unwrap(), 0);
+        assert!(signature.parameters.is_empty().unwrap());
+        assert!(!types[0].methods.is_empty().unwrap());
+    }
+
+    #[test]
+    fn owning_scope_follows_the_namespaces() {
+        let (blob, offsets) = minimal_metadata();
+        let reader = MetadataReader::new(&blob).unwrap();
+
+        let typ = reader.find_type("Game.Player").unwrap().unwrap();
+        assert_eq!(typ.owning_scope().unwrap().handle().offset(), offsets.scope);
+
+        // The root namespace is parented to the scope directly
+        let root = typ
+            .owning_scope()
+            .unwrap()
+            .root_namespace_definition
+            .to_data(reader)
+            .unwrap();
+        assert_eq!(
+            root.owning_scope().unwrap().handle().offset(),
+            offsets.scope
+        );
+    }
+
+    #[test]
+    fn find_type_walks_namespaces() {
+        let (blob, offsets) = minimal_metadata();
+        let reader = MetadataReader::new(&blob).unwrap();
+
+        let typ = reader.find_type("Game.Player").unwrap().unwrap();
+        assert_eq!(typ.handle().offset(), offsets.typ);
+
+        assert!(reader.find_type("Game.Enemy").unwrap().is_none());
+        assert!(reader.find_type("Player").unwrap().is_none());
+    }
+
+    #[test]
+    fn type_handles_match_the_decoded_types() {
+        let (blob, offsets) = minimal_metadata();
+        let reader = MetadataReader::new(&blob).unwrap();
+
+        let typ = reader.find_type("Game.Player").unwrap().unwrap();
+        let scope = typ.owning_scope().unwrap();
+        let handles = scope.type_handles();
+
+        assert_eq!(
+            handles.iter().map(|hdl| hdl.offset()).collect::<Vec<_>>(),
+            [offsets.typ]
+        );
+        assert_eq!(
+            scope
+                .get_all_types()
+                .unwrap()
+                .iter()
+                .map(|typ| typ.handle())
+                .collect::<Vec<_>>(),
+            handles
+        );
+    }
+
+    #[test]
+    fn runtime_hashcode_hashes_full_name() {
+        let (blob, _) = minimal_metadata();
+        let reader = MetadataReader::new(&blob).unwrap();
+
+        let typ = reader.find_type("Game.Player").unwrap().unwrap();
+        assert_eq!(
+            typ.runtime_hashcode().unwrap(),
+            compute_name_hashcode("Game.Player")
+        );
+    }
+
+    #[test]
+    fn resolve_to_type_definition_unwraps_signatures() {
+        let mut builder = MetadataBuilder::new();
+        builder.empty_collection(); // Header without scopes
+
+        // `ref T[]` where T is a type definition at 0x1234, which is never decoded
+        let array = builder.offset();
+        builder.base_handle(HandleType::TypeDefinition, 0x1234);
+
+        let by_ref = builder.offset();
+        builder.base_handle(HandleType::SZArraySignature, array);
+
+        let spec = builder.offset();
+        builder.base_handle(HandleType::ByReferenceSignature, by_ref);
+
+        let variable = builder.offset();
+        builder.signed(0);
+
+        let blob = builder.build();
+        let reader = MetadataReader::new(&blob).unwrap();
+
+        let resolved = BaseHandle::from_offset(HandleType::TypeSpecification, spec)
+            .resolve_to_type_definition(reader)
+            .unwrap();
+        assert_eq!(resolved.offset(), 0x1234);
+
+        assert!(
+            BaseHandle::from_offset(HandleType::TypeVariableSignature, variable)
+                .resolve_to_type_definition(reader)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn canonical_string_numbers_generic_parameters() {
+        let mut builder = MetadataBuilder::new();
+        builder.empty_collection(); // Header without scopes
+
+        let method_variable = builder.offset();
+        builder.signed(0);
+
+        let type_variable = builder.offset();
+        builder.signed(1);
+
+        let array = builder.offset();
+        builder.base_handle(HandleType::TypeVariableSignature, type_variable);
+
+        // `!!0 M<T>(!1[])`
+        let signature = builder.offset();
+        builder
+            .byte(0x20) // HasThis
+            .signed(1)
+            .base_handle(HandleType::MethodTypeVariableSignature, method_variable)
+            .unsigned(1)
+            .base_handle(HandleType::SZArraySignature, array)
+            .empty_collection();
+
+        let blob = builder.build();
+        let reader = MetadataReader::new(&blob).unwrap();
+
+        let canonical = BaseHandle::from_offset(HandleType::MethodSignature, signature)
+            .to_handle::<MethodSignatureHandle>()
+            .unwrap()
+            .to_data(reader)
+            .unwrap()
+            .canonical_string()
+            .unwrap();
+        assert_eq!(canonical, "HasThis`1 !!0(!1[])");
+    }
+
+    #[test]
+    fn canonical_type_name_writes_modifiers() {
+        let mut builder = MetadataBuilder::new();
+        builder.empty_collection(); // Header without scopes
+
+        let namespace = builder.namespace_reference("System.Runtime.InteropServices");
+        let modifier =
+            builder.type_reference(HandleType::NamespaceReference, namespace, "InAttribute");
+
+        let variable = builder.offset();
+        builder.signed(0);
+
+        let by_ref = builder.offset();
+        builder.base_handle(HandleType::TypeVariableSignature, variable);
+
+        // `ref readonly T`
+        let modified = builder.offset();
+        builder
+            .byte(0)
+            .base_handle(HandleType::TypeReference, modifier)
+            .base_handle(HandleType::ByReferenceSignature, by_ref);
+
+        let blob = builder.build();
+        let reader = MetadataReader::new(&blob).unwrap();
+
+        let handle = BaseHandle::from_offset(HandleType::ModifiedType, modified);
+        assert_eq!(
+            canonical_type_name(reader, handle).unwrap(),
+            "!0& modreq(System.Runtime.InteropServices.InAttribute)"
+        );
+        assert!(handle.resolve_to_type_definition(reader).is_none());
+    }
+
+    #[test]
+    fn canonical_type_name_includes_the_enclosing_type() {
+        let mut builder = MetadataBuilder::new();
+        builder.empty_collection(); // Header without scopes
+
+        let namespace = builder.namespace_reference("Game");
+        let outer = builder.type_reference(HandleType::NamespaceReference, namespace, "Outer");
+        let other = builder.type_reference(HandleType::NamespaceReference, namespace, "Other");
+
+        // Both nested types are called `Inner`
+        let outer_inner = builder.type_reference(HandleType::TypeReference, outer, "Inner");
+        let other_inner = builder.type_reference(HandleType::TypeReference, other, "Inner");
+
+        let blob = builder.build();
+        let reader = MetadataReader::new(&blob).unwrap();
+
+        let name = |offset| {
+            canonical_type_name(
+                reader,
+                BaseHandle::from_offset(HandleType::TypeReference, offset),
+            )
+            .unwrap()
+        };
+        assert_eq!(name(outer), "Game.Outer");
+        assert_eq!(name(outer_inner), "Game.Outer+Inner");
+        assert_eq!(name(other_inner), "Game.Other+Inner");
+    }
+
+    #[test]
+    fn cyclic_signatures_are_rejected() {
+        let mut builder = MetadataBuilder::new();
+        builder.empty_collection(); // Header without scopes
+
+        // An array of itself
+        let array = builder.offset();
+        builder.base_handle(HandleType::SZArraySignature, array);
+
+        let blob = builder.build();
+        let reader = MetadataReader::new(&blob).unwrap();
+
+        let handle = BaseHandle::from_offset(HandleType::SZArraySignature, array);
+        assert!(canonical_type_name(reader, handle).is_err());
+        assert!(handle.resolve_to_type_definition(reader).is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn enums_are_based_on_the_type_of_value__() {
+        let mut builder = MetadataBuilder::new();
+        builder.empty_collection(); // Header without scopes
+
+        let namespace = builder.namespace_reference("System");
+        let [enum_ref, int_ref] = ["Enum", "Int32"]
+            .map(|name| builder.type_reference(HandleType::NamespaceReference, namespace, name));
+        let field_name = builder.add_string("value__");
+        let type_name = builder.add_string("Color");
+
+        let signature = builder.offset();
+        builder.base_handle(HandleType::TypeReference, int_ref);
+
+        let field = builder.offset();
+        builder
+            .unsigned(0x0606) // Public | SpecialName | RTSpecialName
+            .handle(field_name)
+            .handle(signature)
+            .base_handle(HandleType::Null, 0)
+            .unsigned(0)
+            .empty_collection();
+
+        let typ = builder.offset();
+        builder
+            .unsigned(0x0101) // Public | Sealed
+            .base_handle(HandleType::TypeReference, enum_ref)
+            .handle(0)
+            .handle(type_name)
+            .unsigned(0)
+            .unsigned(0)
+            .handle(0)
+            .empty_collection()
+            .empty_collection()
+            .handles(&[field])
+            .empty_collection()
+            .empty_collection()
+            .empty_collection()
+            .empty_collection()
+            .empty_collection();
+
+        let blob = builder.build();
+        let reader = MetadataReader::new(&blob).unwrap();
+        let typ = BaseHandle::from_offset(HandleType::TypeDefinition, typ)
+            .to_handle::<TypeDefinitionHandle>()
+            .unwrap()
+            .to_data(reader)
+            .unwrap();
+
+        assert!(typ.is_enum());
+        assert_eq!(typ.enum_underlying_type(), Some(ElementType::Int32));
+        assert_eq!(ElementType::Int32.keyword(), Some("int"));
+
+        let (blob, offsets) = minimal_metadata();
+        let reader = MetadataReader::new(&blob).unwrap();
+        let player = BaseHandle::from_offset(HandleType::TypeDefinition, offsets.typ)
+            .to_handle::<TypeDefinitionHandle>()
+            .unwrap()
+            .to_data(reader)
+            .unwrap();
+
+        assert_eq!(player.enum_underlying_type(), None);
+    }
+
+    #[test]
+    fn failed_reads_name_the_field() {
+        let (blob, _) = minimal_metadata();
+        let reader = MetadataReader::new(&blob).unwrap();
+
+        // Starts on the last byte, which only holds the first field
+        let offset = blob.len() as u32 - 1;
+        let err = BaseHandle::from_offset(HandleType::MethodSignature, offset)
+            .to_handle::<MethodSignatureHandle>()
+            .unwrap()
+            .to_data(reader)
+            .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Failed reading MethodSignature.generic_parameter_count at offset {:#x}",
+                offset + 1
+            )
+        );
+    }
+
+    #[test]
+    fn handles_are_ordered_by_type_then_offset() {
+        let mut handles = vec![
+            BaseHandle::from_offset(HandleType::TypeDefinition, 0x10),
+            BaseHandle::from_offset(HandleType::Method, 0x20),
+            BaseHandle::from_offset(HandleType::TypeDefinition, 0x08),
+        ];
+        handles.sort();
+
+        assert_eq!(
+            handles,
+            [
+                BaseHandle::from_offset(HandleType::Method, 0x20),
+                BaseHandle::from_offset(HandleType::TypeDefinition, 0x08),
+                BaseHandle::from_offset(HandleType::TypeDefinition, 0x10),
+            ]
+        );
+    }
+
+    #[test]
+    fn typed_handles_reject_other_handle_types() {
+        let handle = BaseHandle::from_offset(HandleType::Method, 0x10);
+
+        assert!(handle.to_handle::<TypeDefinitionHandle>().is_err());
+        assert_eq!(
+            BaseHandle::from_offset(HandleType::TypeDefinition, 0x10)
+                .to_handle::<TypeDefinitionHandle>()
+                .unwrap()
+                .offset(),
+            0x10
+        );
+    }
+}
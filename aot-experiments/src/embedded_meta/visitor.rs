@@ -0,0 +1,144 @@
+use paste::paste;
+
+use crate::{
+    embedded_meta::{
+        ArraySignature, ByReferenceSignature, ConstantStringValue, CustomAttribute, Event, Field,
+        FieldSignature, GenericParameter, MemberReference, MetadataReader, Method, MethodSemantics,
+        MethodSignature, MethodTypeVariableSignature, ModifiedType, NamedArgument,
+        NamespaceDefinition, NamespaceReference, PointerSignature, Property, PropertySignature,
+        QualifiedField, QualifiedMethod, SZArraySignature, ScopeDefinition, ScopeReference,
+        TypeDefinition, TypeInstantiationSignature, TypeReference, TypeSpecification,
+        TypeVariableSignature,
+        handles::{
+            ArraySignatureHandle, BaseHandle, ByReferenceSignatureHandle,
+            ConstantStringValueHandle, CustomAttributeHandle, EventHandle, FieldHandle,
+            FieldSignatureHandle, GenericParameterHandle, Handle, HandleType,
+            MemberReferenceHandle, MethodHandle, MethodSemanticsHandle, MethodSignatureHandle,
+            MethodTypeVariableSignatureHandle, ModifiedTypeHandle, NamedArgumentHandle,
+            NamespaceDefinitionHandle, NamespaceReferenceHandle, PointerSignatureHandle,
+            PropertyHandle, PropertySignatureHandle, QualifiedFieldHandle, QualifiedMethodHandle,
+            SZArraySignatureHandle, ScopeDefinitionHandle, ScopeReferenceHandle,
+            TypeDefinitionHandle, TypeInstantiationSignatureHandle, TypeReferenceHandle,
+            TypeSpecificationHandle, TypeVariableSignatureHandle,
+        },
+    },
+    error::Result,
+};
+
+macro_rules! define_visitor {
+    ($($name:ident => $visit:ident),* $(,)?) => {
+        paste! {
+            /// Visits the record a [`BaseHandle`] refers to, see [`BaseHandle::visit`]
+            ///
+            /// Every record type has a method, which falls back to [`HandleVisitor::visit_other`] unless overridden.
+            pub trait HandleVisitor<'a> {
+                type Output;
+
+                /// Called for the handle types the visitor doesn't handle, and for handles of an unknown type
+                fn visit_other(&mut self, handle: BaseHandle) -> Result<Self::Output>;
+
+                $(
+                    fn $visit(&mut self, record: $name<'a>) -> Result<Self::Output> {
+                        self.visit_other(BaseHandle::from_raw(record.handle().to_value()))
+                    }
+                )*
+            }
+
+            impl BaseHandle {
+                /// Decodes the record this handle refers to, and passes it to the matching method of the visitor
+                pub fn visit<'a, V: HandleVisitor<'a>>(
+                    self,
+                    reader: MetadataReader<'a>,
+                    visitor: &mut V,
+                ) -> Result<V::Output> {
+                    match self.handle_type() {
+                        $(
+                            Some(HandleType::$name) => {
+                                let record = self.to_handle::<[<$name Handle>]>()?.to_data(reader)?;
+                                visitor.$visit(record)
+                            }
+                        )*
+                        _ => visitor.visit_other(self),
+                    }
+                }
+            }
+        }
+    };
+}
+
+define_visitor! {
+    ArraySignature => visit_array_signature,
+    ByReferenceSignature => visit_by_reference_signature,
+    ConstantStringValue => visit_constant_string_value,
+    CustomAttribute => visit_custom_attribute,
+    Event => visit_event,
+    Field => visit_field,
+    FieldSignature => visit_field_signature,
+    GenericParameter => visit_generic_parameter,
+    MemberReference => visit_member_reference,
+    Method => visit_method,
+    MethodSemantics => visit_method_semantics,
+    MethodSignature => visit_method_signature,
+    MethodTypeVariableSignature => visit_method_type_variable_signature,
+    ModifiedType => visit_modified_type,
+    NamedArgument => visit_named_argument,
+    NamespaceDefinition => visit_namespace_definition,
+    NamespaceReference => visit_namespace_reference,
+    PointerSignature => visit_pointer_signature,
+    Property => visit_property,
+    PropertySignature => visit_property_signature,
+    QualifiedField => visit_qualified_field,
+    QualifiedMethod => visit_qualified_method,
+    SZArraySignature => visit_sz_array_signature,
+    ScopeDefinition => visit_scope_definition,
+    ScopeReference => visit_scope_reference,
+    TypeDefinition => visit_type_definition,
+    TypeInstantiationSignature => visit_type_instantiation_signature,
+    TypeReference => visit_type_reference,
+    TypeSpecification => visit_type_specification,
+    TypeVariableSignature => visit_type_variable_signature,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        embedded_meta::{
+            MetadataReader, TypeDefinition,
+            handles::{BaseHandle, HandleType},
+            visitor::HandleVisitor,
+        },
+        error::Result,
+        test_utils::minimal_metadata,
+    };
+
+    struct NameVisitor;
+
+    impl<'a> HandleVisitor<'a> for NameVisitor {
+        type Output = String;
+
+        fn visit_other(&mut self, handle: BaseHandle) -> Result<String> {
+            Ok(format!("{:?}", handle.handle_type()))
+        }
+
+        fn visit_type_definition(&mut self, typ: TypeDefinition<'a>) -> Result<String> {
+            typ.get_full_name()
+        }
+    }
+
+    #[test]
+    fn visit_dispatches_on_the_handle_type() {
+        let (blob, offsets) = minimal_metadata();
+        let reader = MetadataReader::new(&blob).unwrap();
+        let visit = |handle_type, offset| {
+            BaseHandle::from_offset(handle_type, offset)
+                .visit(reader, &mut NameVisitor)
+                .unwrap()
+        };
+
+        assert_eq!(
+            visit(HandleType::TypeDefinition, offsets.typ),
+            "Game.Player"
+        );
+        assert_eq!(visit(HandleType::Method, offsets.method), "Some(Method)");
+    }
+}
@@ -1,12 +1,91 @@
 use thiserror::Error;
 
+#[cfg(feature = "native")]
+use crate::binary::headers::rtr::ReadyToRunSectionType;
+use crate::embedded_meta::handles::HandleType;
+
 #[derive(Error, Debug)]
 pub enum AotError {
     #[error("This image/blob is corrupt or malformed")]
     BadImage,
 
+    /// Like [`Self::BadImage`], but with the location of the value that failed to decode
+    #[error("Failed reading {context} at offset {offset:#x}")]
+    BadRead {
+        offset: usize,
+        context: &'static str,
+    },
+
     #[error("The value for the metadata handle is invalid")]
     InvalidMetaHandle,
+
+    /// Like [`Self::InvalidMetaHandle`], for a handle whose record would start past the end of the metadata
+    #[error("{handle_type:?} handle at offset {offset:#x} is outside of the metadata")]
+    InvalidMetaHandleOffset {
+        handle_type: HandleType,
+        offset: u32,
+    },
+
+    #[error("More than one scope defines an entrypoint")]
+    AmbiguousEntrypoint,
+
+    /// A signature contains a handle the type namer has no representation for
+    #[error("Unsupported {handle_type:?} handle {handle:#x} in signature")]
+    UnsupportedHandle {
+        handle_type: HandleType,
+        handle: u32,
+    },
+
+    #[cfg(feature = "native")]
+    #[error(transparent)]
+    Pe(#[from] pelite::Error),
+
+    #[cfg(feature = "native")]
+    #[error(transparent)]
+    Read(#[from] binary_rw::BinaryError),
+
+    /// A section, blob or table the operation depends on isn't in the image, e.g. a `TypeMap`
+    #[error("Image is missing {0}")]
+    MissingTable(&'static str),
+
+    #[error("Unable to locate ReadyToRun header")]
+    RtrHeaderNotFound,
+
+    #[error("Invalid ReadyToRunHeader signature {0:#x}")]
+    BadRtrSignature(u32),
+
+    #[error("Invalid ReadyToRun section count {0}")]
+    BadSectionCount(u16),
+
+    #[cfg(feature = "native")]
+    #[error("Section {section_type:?} has an invalid range {start:#x}..{end:#x}")]
+    BadSectionRange {
+        section_type: ReadyToRunSectionType,
+        start: u64,
+        end: u64,
+    },
+
+    #[error("Failed to parse section {index}: {source}")]
+    BadSection { index: usize, source: Box<AotError> },
+
+    #[error("Invalid MethodTable: {0}")]
+    BadMethodTable(&'static str),
+
+    #[error("MethodTable not found or present in binary")]
+    MethodTableNotFound,
+
+    #[error("Metadata is present but failed to parse: {0}")]
+    BadMetadata(Box<AotError>),
+}
+
+impl AotError {
+    /// Attaches the location of a failed read, replacing the location of any read nested in it
+    pub fn at(self, offset: usize, context: &'static str) -> Self {
+        match self {
+            Self::BadImage | Self::BadRead { .. } => Self::BadRead { offset, context },
+            err => err,
+        }
+    }
 }
 
 pub type Result<T> = ::core::result::Result<T, AotError>;
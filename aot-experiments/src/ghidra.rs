@@ -0,0 +1,153 @@
+//! The definition `python/ghidra_structs.py` creates Ghidra structures from, for value types with a declared layout
+
+use serde::Serialize;
+
+use crate::{
+    embedded_meta::{flags::TypeLayout, utils::DeclaredLayout},
+    model::FieldModel,
+};
+
+#[derive(Serialize, Default)]
+pub struct GhidraDefinition {
+    structs: Vec<Struct>,
+}
+
+#[derive(Serialize)]
+struct Struct {
+    /// The namespaces and the name of the type, which become the category path of the structure
+    name: Vec<String>,
+
+    /// Whether the fields are placed at their offset, otherwise they're laid out in declaration order
+    explicit: bool,
+
+    /// `None` leaves the size to the fields
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<u32>,
+
+    /// `None` for the default packing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    packing: Option<u16>,
+
+    fields: Vec<Field>,
+}
+
+#[derive(Serialize)]
+struct Field {
+    name: String,
+    type_name: String,
+
+    /// The `FieldOffset` of the field, only known for explicit layout
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offset: Option<u32>,
+}
+
+impl GhidraDefinition {
+    /// Adds a structure for the instance fields of a type, static fields are left out
+    pub fn create_struct<S: AsRef<str>>(
+        &mut self,
+        name: S,
+        layout: DeclaredLayout,
+        fields: &[FieldModel],
+    ) {
+        let explicit = layout.kind == TypeLayout::Explicit;
+
+        let fields = fields
+            .iter()
+            .filter(|field| !field.is_static)
+            .map(|field| Field {
+                name: field.name.clone(),
+                type_name: field.type_name.clone(),
+                offset: explicit.then_some(field.offset),
+            })
+            .collect();
+
+        self.structs.push(Struct {
+            name: name.as_ref().split(".").map(str::to_string).collect(),
+            explicit,
+            size: layout.size,
+            packing: layout.packing_size,
+            fields,
+        });
+    }
+
+    pub fn struct_count(&self) -> usize {
+        self.structs.len()
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        embedded_meta::{flags::TypeLayout, utils::DeclaredLayout},
+        ghidra::GhidraDefinition,
+        model::FieldModel,
+    };
+
+    fn field(name: &str, type_name: &str, is_static: bool, offset: u32) -> FieldModel {
+        FieldModel {
+            name: name.to_string(),
+            type_name: type_name.to_string(),
+            is_static,
+            offset,
+        }
+    }
+
+    #[test]
+    fn structs_carry_their_layout() {
+        let mut definition = GhidraDefinition::default();
+
+        definition.create_struct(
+            "Game.Color",
+            DeclaredLayout {
+                kind: TypeLayout::Explicit,
+                size: Some(4),
+                packing_size: None,
+            },
+            &[
+                field("Packed", "System.UInt32", false, 0),
+                field("R", "System.Byte", false, 3),
+                field("White", "Game.Color", true, 0),
+            ],
+        );
+        definition.create_struct(
+            "Game.Header",
+            DeclaredLayout {
+                kind: TypeLayout::Sequential,
+                size: None,
+                packing_size: Some(1),
+            },
+            &[field("Magic", "System.UInt16", false, 0)],
+        );
+
+        let value = serde_json::from_str::<serde_json::Value>(&definition.to_json().unwrap());
+
+        assert_eq!(
+            value.unwrap(),
+            json!({
+                "structs": [
+                    {
+                        "name": ["Game", "Color"],
+                        "explicit": true,
+                        "size": 4,
+                        "fields": [
+                            { "name": "Packed", "type_name": "System.UInt32", "offset": 0 },
+                            { "name": "R", "type_name": "System.Byte", "offset": 3 },
+                        ],
+                    },
+                    {
+                        "name": ["Game", "Header"],
+                        "explicit": false,
+                        "packing": 1,
+                        "fields": [{ "name": "Magic", "type_name": "System.UInt16" }],
+                    },
+                ],
+            })
+        );
+    }
+}
@@ -1,9 +1,15 @@
+use std::collections::{HashMap, HashSet};
+
 use serde::Serialize;
 
 #[derive(Serialize, Default)]
 pub struct HytaleDefinition {
     mt_structs: Vec<MtStruct>,
     functions: Vec<Function>,
+
+    /// Named data, like the TypeManagerIndirection cell
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    globals: Vec<Global>,
 }
 
 #[derive(Serialize)]
@@ -12,6 +18,10 @@ struct MtStruct {
     vtables: u16,
     ifaces: u16,
     address: u64,
+
+    /// The names of the vtable slots, only filled in by [`HytaleDefinition::name_vtable_slots`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vtable_slots: Option<Vec<String>>,
 }
 
 #[derive(Serialize)]
@@ -20,6 +30,64 @@ struct Function {
     address: u64,
 }
 
+#[derive(Serialize)]
+struct Global {
+    name: String,
+    address: u64,
+}
+
+/// A [`HytaleDefinition`] where every name is an index into `strings`, which keeps the namespaces shared by many
+/// structs from being repeated
+#[derive(Serialize, Default)]
+pub struct InternedDefinition {
+    strings: Vec<String>,
+    mt_structs: Vec<InternedMtStruct>,
+    functions: Vec<InternedFunction>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    globals: Vec<InternedGlobal>,
+
+    #[serde(skip)]
+    indices: HashMap<String, u32>,
+}
+
+#[derive(Serialize)]
+struct InternedMtStruct {
+    name: Vec<u32>,
+    vtables: u16,
+    ifaces: u16,
+    address: u64,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vtable_slots: Option<Vec<u32>>,
+}
+
+#[derive(Serialize)]
+struct InternedFunction {
+    name: u32,
+    address: u64,
+}
+
+#[derive(Serialize)]
+struct InternedGlobal {
+    name: u32,
+    address: u64,
+}
+
+impl InternedDefinition {
+    fn intern(&mut self, string: &str) -> u32 {
+        if let Some(&index) = self.indices.get(string) {
+            return index;
+        }
+
+        let index = self.strings.len() as u32;
+        self.strings.push(string.to_string());
+        self.indices.insert(string.to_string(), index);
+
+        index
+    }
+}
+
 impl HytaleDefinition {
     pub fn create_mt_struct<S: AsRef<str>>(
         &mut self,
@@ -36,6 +104,7 @@ impl HytaleDefinition {
             vtables,
             ifaces,
             address,
+            vtable_slots: None,
         })
     }
 
@@ -45,4 +114,124 @@ impl HytaleDefinition {
             address,
         });
     }
+
+    pub fn create_global<S: Into<String>>(&mut self, address: u64, name: S) {
+        self.globals.push(Global {
+            name: name.into(),
+            address,
+        });
+    }
+
+    pub fn mt_struct_count(&self) -> usize {
+        self.mt_structs.len()
+    }
+
+    pub fn function_count(&self) -> usize {
+        self.functions.len()
+    }
+
+    /// Serializes the definition, with inline names or with names interned into a string table
+    pub fn to_json(&self, interned: bool) -> serde_json::Result<String> {
+        if interned {
+            serde_json::to_string(&self.interned())
+        } else {
+            serde_json::to_string(self)
+        }
+    }
+
+    /// Moves every name into a shared string table, see [`InternedDefinition`]
+    pub fn interned(&self) -> InternedDefinition {
+        let mut interned = InternedDefinition::default();
+
+        for mt in &self.mt_structs {
+            let name = mt.name.iter().map(|part| interned.intern(part)).collect();
+            let vtable_slots = mt
+                .vtable_slots
+                .as_ref()
+                .map(|slots| slots.iter().map(|slot| interned.intern(slot)).collect());
+
+            interned.mt_structs.push(InternedMtStruct {
+                name,
+                vtables: mt.vtables,
+                ifaces: mt.ifaces,
+                address: mt.address,
+                vtable_slots,
+            });
+        }
+
+        for function in &self.functions {
+            let name = interned.intern(&function.name);
+
+            interned.functions.push(InternedFunction {
+                name,
+                address: function.address,
+            });
+        }
+
+        for global in &self.globals {
+            let name = interned.intern(&global.name);
+
+            interned.globals.push(InternedGlobal {
+                name,
+                address: global.address,
+            });
+        }
+
+        interned
+    }
+
+    /// Names the vtable slots of every MethodTable struct after the function they point to
+    ///
+    /// `vtables` maps the address of a MethodTable to its vtable. Slots pointing to a function without a name are
+    /// named after their index.
+    pub fn name_vtable_slots(&mut self, vtables: &HashMap<u64, &[u64]>) {
+        let names = self
+            .functions
+            .iter()
+            .map(|function| (function.address, function.name.as_str()))
+            .collect::<HashMap<_, _>>();
+
+        for mt in &mut self.mt_structs {
+            let Some(vtable) = vtables.get(&mt.address) else {
+                continue;
+            };
+
+            // Member names have to be unique, while slots can share a target (e.g. inherited abstract methods)
+            let mut used = HashSet::new();
+            let slots = vtable
+                .iter()
+                .enumerate()
+                .map(|(index, target)| match names.get(target) {
+                    Some(&name) if used.insert(name) => name.to_string(),
+                    Some(name) => format!("{name}_{index}"),
+                    None => format!("slot_{index}"),
+                })
+                .collect();
+
+            mt.vtable_slots = Some(slots);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ida::HytaleDefinition;
+
+    #[test]
+    fn interning_shares_namespace_parts() {
+        let mut definition = HytaleDefinition::default();
+        definition.create_mt_struct(0x1000, "HytaleClient.Foo", 1, 0);
+        definition.create_mt_struct(0x2000, "HytaleClient.Bar", 1, 0);
+        definition.create_function(0x3000, "HytaleClient.Foo.Run");
+
+        let interned = definition.interned();
+
+        assert_eq!(
+            interned.strings,
+            ["HytaleClient", "Foo", "Bar", "HytaleClient_Foo_Run"]
+        );
+        assert_eq!(interned.mt_structs[0].name, [0, 1]);
+        assert_eq!(interned.mt_structs[1].name, [0, 2]);
+        assert_eq!(interned.functions[0].name, 3);
+    }
 }
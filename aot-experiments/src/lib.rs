@@ -0,0 +1,13 @@
+#![allow(unused)] // Shush
+
+#[cfg(feature = "native")]
+pub mod binary;
+pub mod embedded_meta;
+pub mod error;
+pub mod native_format;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(any(test, feature = "test-utils"))]
+#[doc(hidden)]
+pub mod test_utils;
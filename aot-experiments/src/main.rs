@@ -1,78 +1,537 @@
 #![allow(unused)] // Shush
 
-mod binary;
-mod embedded_meta;
-mod error;
+mod diff;
+mod ghidra;
 mod ida;
-mod native_format;
-
-use std::{collections::HashMap, path::PathBuf};
+mod model;
+mod naming;
+mod style;
+
+#[cfg(test)]
+mod test_utils;
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, hash_map::Entry},
+    path::{Path, PathBuf},
+    sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Instant,
+};
 
-use anyhow::Result;
-use clap::Parser;
-use pelite::pe64::{Pe, PeFile};
+use anyhow::{Result, bail};
+use aot_blobs::{binary, embedded_meta, error, native_format};
+use clap::{ArgAction, CommandFactory, Parser, ValueEnum};
+use log::{LevelFilter, debug, error, info, warn};
+use pelite::pe64::{Pe, PeFile, Rva, Va};
+use serde::Serialize;
 
 use crate::{
-    binary::{NativeAotBinary, headers::rtr::ReflectionMapBlob},
+    binary::{
+        NativeAotBinary, ScanSections,
+        field_access::{FieldStorage, StaticKind},
+        generics::ConstructedGenericType,
+        headers::{
+            mt::{CANONICAL_TYPE_NAME, ElementType, MethodTable},
+            rtr::{ReflectionMapBlob, format_guid},
+        },
+        invoke_map::{GenericInstantiation, InvokeMap},
+        type_manager::TYPE_MANAGER_INDIRECTION_NAME,
+    },
     embedded_meta::{
-        MetadataReader, Method, TypeDefinition, TypeInstantiationSignature, TypeSpecification,
-        flags::MethodMemberAccess,
+        ArraySignature, ByReferenceSignature, Event, Field, MetadataReader, Method,
+        MethodSignature, ModifiedType, PointerSignature, Property, SZArraySignature,
+        ScopeDefinition, TypeDefinition, TypeInstantiationSignature, TypeSpecification,
+        assembly::AssemblyName,
+        attributes::AttributeTarget,
+        flags::{MethodCodeType, TypeLayout, UnmanagedCallingConvention},
         handles::{
-            BaseHandle, ByReferenceSignatureHandle, HandleType, MethodHandle,
-            MethodTypeVariableSignatureHandle, TypeDefinitionHandle,
-            TypeInstantiationSignatureHandle, TypeSpecificationHandle, TypeVariableSignatureHandle,
+            BaseHandle, Handle, HandleType, MethodHandle, TypeDefinitionHandle, TypeReferenceHandle,
         },
+        utils::{DeclaredLayout, DecodeObserver, canonical_type_name},
+        visitor::HandleVisitor,
+    },
+    naming::{
+        ParentInfo, get_parameter_list, get_return_type_name, get_type_name_from_handle,
+        member_access_name, name_or,
     },
+    native_format::View,
+    style::Style,
 };
 
 #[derive(Parser, Debug)]
 #[command(version, about)]
 struct Args {
-    /// Path to Hytale executable
-    file: PathBuf,
+    /// Path to Hytale executable, needed by every command but `completions` and `man`
+    file: Option<PathBuf>,
+
+    /// Print more diagnostics, can be repeated
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    verbose: u8,
+
+    /// Print fewer diagnostics, can be repeated
+    #[arg(short, long, action = ArgAction::Count, global = true, conflicts_with = "verbose")]
+    quiet: u8,
+
+    /// Comma separated sections to scan for the RTR header and MethodTables
+    #[arg(long, global = true, value_delimiter = ',', default_values = NativeAotBinary::CANDIDATE_DATA_SECTIONS)]
+    scan_sections: Vec<String>,
+
+    /// Scan every section of the image, regardless of its name
+    #[arg(long, global = true, conflicts_with = "scan_sections")]
+    scan_all_sections: bool,
+
+    /// Print addresses as file offsets instead of RVAs, for patching the binary
+    #[arg(long, global = true)]
+    file_offset: bool,
+
+    /// Fail on handle types the type namer doesn't model, instead of printing their kind
+    #[arg(long, global = true)]
+    strict_signatures: bool,
+
+    /// Follow every type and method `GetTypes` and `GetType` print with its raw metadata handle, like `@0x0e000012`,
+    /// and add it as `token` to the JSON models
+    #[arg(long, global = true)]
+    with_tokens: bool,
+
+    /// Fail on metadata strings that aren't valid UTF-8, instead of replacing the invalid bytes
+    #[arg(long, global = true)]
+    strict_strings: bool,
 
     /// Command
     #[command(subcommand)]
     command: Command,
 }
 
+impl Args {
+    fn scan_sections(&self) -> ScanSections {
+        if self.scan_all_sections {
+            ScanSections::All
+        } else {
+            ScanSections::Named(self.scan_sections.clone())
+        }
+    }
+
+    fn log_level(&self) -> LevelFilter {
+        match self.verbose as i8 - self.quiet as i8 {
+            ..=-3 => LevelFilter::Off,
+            -2 => LevelFilter::Error,
+            -1 => LevelFilter::Warn,
+            0 => LevelFilter::Info,
+            1 => LevelFilter::Debug,
+            2.. => LevelFilter::Trace,
+        }
+    }
+}
+
 #[derive(Parser, Debug)]
 enum Command {
     /// List all assemblies compiled into this NativeAOT binary
     GetAssemblies,
 
     /// List all types and metadata surrounding it
-    GetTypes,
+    GetTypes {
+        /// Always colorize the output, even when not writing to a terminal
+        #[arg(long, conflicts_with = "no_color")]
+        color: bool,
+
+        /// Never colorize the output
+        #[arg(long)]
+        no_color: bool,
+
+        /// Look up method addresses on demand instead of loading the entire InvokeMap up front, which leaves out the
+        /// generic instantiations of methods
+        #[arg(long)]
+        low_memory: bool,
+
+        /// Stop after printing this many types
+        #[arg(long)]
+        limit: Option<usize>,
+
+        /// Skip this many types before printing, for paging through the output with `--limit`
+        #[arg(long, default_value_t = 0)]
+        skip: usize,
+
+        /// Only list the types of this assembly, e.g. `HytaleClient`
+        #[arg(long)]
+        assembly: Option<String>,
+
+        /// Leave out compiler generated types, like async state machines and closures
+        #[arg(long, conflicts_with = "only_generated")]
+        hide_generated: bool,
+
+        /// Only list compiler generated types
+        #[arg(long)]
+        only_generated: bool,
+
+        /// How to write the types
+        #[arg(long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+
+    /// Print a single type and its members
+    GetType {
+        /// Full name of the type, e.g. `Namespace.Type`
+        name: String,
+
+        /// Also list the members inherited from base types
+        #[arg(long)]
+        inherited: bool,
+
+        /// Compare the layout declared by `[StructLayout]` and `[FieldOffset]` with the one in the metadata
+        #[arg(long)]
+        layout: bool,
+
+        /// Always colorize the output, even when not writing to a terminal
+        #[arg(long, conflicts_with = "no_color")]
+        color: bool,
+
+        /// Never colorize the output
+        #[arg(long)]
+        no_color: bool,
+    },
+
+    /// Print the program entrypoint and its RVA
+    GetEntryPoint,
+
+    /// Print the ReadyToRun header and the table of its sections
+    DumpHeader,
+
+    /// Find the storage of a field: its offset for instance fields, or its address for static fields
+    FindField {
+        /// Full name of the field, e.g. `Namespace.Type.Field`
+        name: String,
+    },
+
+    /// List every static field in the FieldAccessMap, with the kind of storage it lives in
+    ///
+    /// Types with a lazily run static constructor are preceded by their cctor context and the cctor it runs.
+    DumpStatics,
+
+    /// List the string literals the compiler preallocated in the FrozenObjectRegion
+    DumpFrozenStrings,
+
+    /// List the static constructors and module initializers that run at startup, in the order they run
+    ListInitializers,
+
+    /// Map the metadata handle of every type and method to its name, for tools that work with tokens instead of
+    /// addresses
+    DumpTokenMap {
+        /// Write the map as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Graph which assemblies reference which, through the base types, interfaces, fields and method signatures of
+    /// their types, as Graphviz DOT
+    DumpAssemblyGraph {
+        /// Write the graph as JSON instead
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// List every type, method, field and property carrying an attribute
+    FindByAttribute {
+        /// Name of the attribute, e.g. `Obsolete` or `System.ObsoleteAttribute`
+        name: String,
+    },
+
+    /// List every P/Invoke and the native function it imports, including the module-level ones declared on `<Module>`
+    FindPInvokes,
+
+    /// List every field, method, property, base type and interface implementation referring to a type
+    FindReferences {
+        /// Full name of the type, e.g. `Namespace.Type`
+        name: String,
+    },
+
+    /// Compare the types of this binary against a newer version of it
+    Diff {
+        /// Path to the newer Hytale executable
+        new: PathBuf,
+
+        /// Write the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Hex dump the MethodTable of a type, annotating the header fields, vtable slots and interfaces
+    DumpMt {
+        /// Full name of the type, e.g. `Namespace.Type`
+        name: String,
+    },
+
+    /// Dump the machine code of a single function
+    DumpFunction {
+        /// Full name of the method, e.g. `Namespace.Type.Method`, or the RVA of the function
+        function: String,
+
+        /// Write the raw bytes to this file instead of printing a hex dump
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Name an address after the closest function or MethodTable at or before it, e.g. `Namespace.Type.Method+0x1c`
+    Symbolize {
+        /// The VA to name, in hex
+        va: String,
+    },
+
+    /// Tell whether each address is the start of a MethodTable, a function or a vtable thunk, e.g. for the pointer
+    /// fields of a struct
+    Classify {
+        /// The VAs to classify, in hex
+        #[arg(required = true)]
+        addresses: Vec<String>,
+    },
+
+    /// Report the MethodTables that can't be named from the TypeMap or the GenericsHashtable, for finding gaps in the
+    /// parsing
+    ListUnnamedTables {
+        /// How many of the unnamed tables to print
+        #[arg(long, default_value_t = 20)]
+        sample: usize,
+    },
 
     /// TODO
     CreateMetadataTree,
 
-    DumpIDA,
+    DumpIDA {
+        /// Name every vtable slot of the MethodTable structs after the method it points to, instead of leaving them
+        /// as an array of pointers
+        #[arg(long)]
+        detailed_vtables: bool,
+
+        /// Only define the types and methods of this assembly, e.g. `HytaleClient`
+        #[arg(long)]
+        assembly: Option<String>,
+
+        /// Leave out compiler generated types, like async state machines and closures
+        #[arg(long, conflicts_with = "only_generated")]
+        hide_generated: bool,
+
+        /// Only define compiler generated types
+        #[arg(long)]
+        only_generated: bool,
+
+        /// Write names as indices into a shared `strings` table, which needs an import script that supports it
+        #[arg(long)]
+        interned: bool,
+
+        /// File to write the definition to
+        #[arg(short, long, default_value = "hytale_def.json")]
+        output: PathBuf,
+
+        /// Only report how many MethodTable structs and functions would be defined, without writing the definition
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Write the value types with a declared layout as structures for `ghidra_structs.py`, with their size, packing
+    /// and, for explicit layout, field offsets
+    DumpGhidra {
+        /// Only define the structs of this assembly, e.g. `HytaleClient`
+        #[arg(long)]
+        assembly: Option<String>,
+
+        /// File to write the definition to
+        #[arg(short, long, default_value = "hytale_structs.json")]
+        output: PathBuf,
+    },
+
+    /// Run DumpIDA for every executable in the directory given as the input file
+    DumpIDABatch {
+        /// Directory to write a `<name>.json` definition to for every Hytale executable
+        out_dir: PathBuf,
+
+        /// Write names as indices into a shared `strings` table, like `dump-ida --interned`
+        #[arg(long)]
+        interned: bool,
+    },
+
+    /// Print completions for the given shell
+    #[command(hide = true)]
+    Completions { shell: clap_complete::Shell },
+
+    /// Print the man page
+    #[command(hide = true)]
+    Man,
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
 
+    // Both are generated from the definitions above, and don't need an input file
+    match args.command {
+        Command::Completions { shell } => {
+            clap_complete::generate(
+                shell,
+                &mut Args::command(),
+                "aot-blobs",
+                &mut std::io::stdout(),
+            );
+            return Ok(());
+        }
+        Command::Man => {
+            clap_mangen::Man::new(Args::command()).render(&mut std::io::stdout())?;
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let Some(file) = args.file.as_deref() else {
+        bail!("Missing the path to the Hytale executable");
+    };
+
+    // RUST_LOG still takes precedence, for tracing specific modules
+    env_logger::Builder::new()
+        .filter_level(args.log_level())
+        .format_timestamp(None)
+        .parse_default_env()
+        .init();
+
+    let addresses = if args.file_offset {
+        AddressFormat::FileOffset
+    } else {
+        AddressFormat::Rva
+    };
+
+    let scan_sections = args.scan_sections();
+
+    // The input is a directory of executables, which are loaded one by one
+    if let Command::DumpIDABatch { out_dir, interned } = &args.command {
+        return dump_ida_batch(file, out_dir, *interned, scan_sections, args.strict_strings);
+    }
+
     // Parse input file
-    let data = std::fs::read(&args.file)?;
+    let data = std::fs::read(file)?;
     let pe = PeFile::from_bytes(&data)?;
-    let binary = NativeAotBinary::load_pe(pe)?;
+    let binary = NativeAotBinary::load_pe(pe, scan_sections.clone())?
+        .with_strict_strings(args.strict_strings);
 
     if let Err(why) = match args.command {
         Command::GetAssemblies => get_assemblies(binary),
-        Command::GetTypes => get_types(binary),
+        Command::GetTypes {
+            color,
+            no_color,
+            low_memory,
+            limit,
+            skip,
+            assembly,
+            hide_generated,
+            only_generated,
+            format,
+        } => get_types(
+            binary,
+            PrintOptions {
+                style: Style::from_flags(color, no_color),
+                addresses,
+                strict_signatures: args.strict_signatures,
+                with_tokens: args.with_tokens,
+            },
+            low_memory,
+            Page { skip, limit },
+            TypeFilter {
+                assembly: assembly.as_deref(),
+                generated: GeneratedTypes::from_flags(hide_generated, only_generated),
+            },
+            format,
+        ),
+        Command::GetType {
+            name,
+            inherited,
+            layout,
+            color,
+            no_color,
+        } => get_type(
+            binary,
+            &name,
+            PrintOptions {
+                style: Style::from_flags(color, no_color),
+                addresses,
+                strict_signatures: args.strict_signatures,
+                with_tokens: args.with_tokens,
+            },
+            inherited,
+            layout,
+        ),
+        Command::GetEntryPoint => get_entrypoint(binary, addresses),
+        Command::DumpHeader => dump_header(binary),
+        Command::FindField { name } => find_field(binary, &name, addresses, args.strict_signatures),
+        Command::FindByAttribute { name } => find_by_attribute(binary, &name),
+        Command::FindPInvokes => find_pinvokes(binary),
+        Command::FindReferences { name } => find_references(binary, &name),
+        Command::DumpStatics => dump_statics(binary, addresses),
+        Command::DumpFrozenStrings => dump_frozen_strings(binary, addresses),
+        Command::ListInitializers => list_initializers(binary, addresses),
+        Command::DumpTokenMap { json } => dump_token_map(binary, args.strict_signatures, json),
+        Command::DumpAssemblyGraph { json } => dump_assembly_graph(binary, json),
+        Command::Diff { new, json } => diff(
+            binary,
+            &new,
+            scan_sections,
+            args.strict_strings,
+            args.strict_signatures,
+            json,
+        ),
+        Command::DumpMt { name } => dump_mt(binary, &name),
+        Command::Symbolize { va } => symbolize(binary, &va),
+        Command::Classify { addresses } => classify(binary, &addresses),
+        Command::DumpFunction { function, output } => {
+            dump_function(binary, &function, output.as_deref())
+        }
+        Command::ListUnnamedTables { sample } => list_unnamed_tables(binary, sample),
         Command::CreateMetadataTree => create_metadata_tree(binary),
-        Command::DumpIDA => dump_ida(binary),
+        Command::DumpIDA {
+            detailed_vtables,
+            assembly,
+            hide_generated,
+            only_generated,
+            interned,
+            output,
+            dry_run,
+        } => dump_ida(
+            binary,
+            detailed_vtables,
+            interned,
+            TypeFilter {
+                assembly: assembly.as_deref(),
+                generated: GeneratedTypes::from_flags(hide_generated, only_generated),
+            },
+            (!dry_run).then_some(output.as_path()),
+        ),
+        Command::DumpGhidra { assembly, output } => {
+            dump_ghidra(binary, assembly.as_deref(), &output)
+        }
+        Command::DumpIDABatch { .. } => unreachable!("batches are dumped before loading the input"),
+        Command::Completions { .. } | Command::Man => {
+            unreachable!("handled before loading the input")
+        }
     } {
-        eprintln!("Error: {why}");
+        error!("{why}");
     }
 
     Ok(())
 }
 
+/// Loads the metadata for a command, warning about why it isn't available otherwise
+fn load_metadata<'a>(pe: &NativeAotBinary<'a>) -> Option<MetadataReader<'a>> {
+    match pe.rtr_header().metadata_result() {
+        Ok(Some(metadata)) => Some(metadata),
+        Ok(None) => {
+            warn!("Image is missing a metadata section");
+            None
+        }
+        Err(why) => {
+            warn!("{why}");
+            None
+        }
+    }
+}
+
 fn get_assemblies(pe: NativeAotBinary<'_>) -> Result<()> {
-    let Some(metadata) = pe.rtr_header().metadata() else {
-        eprintln!("Image is missing a metadata section");
+    let Some(metadata) = load_metadata(&pe) else {
         return Ok(());
     };
 
@@ -83,216 +542,2273 @@ fn get_assemblies(pe: NativeAotBinary<'_>) -> Result<()> {
         .flatten()
         .flat_map(|hdl| hdl.to_data(metadata))
     {
-        let Ok(name) = def.name.to_data(metadata) else {
+        let Ok(name) = def.assembly_name() else {
             continue;
         };
 
-        println!(
-            "{}, Version={}.{}.{}.{}",
-            name.value, def.major_version, def.minor_version, def.build_number, def.revision_number
-        );
+        println!("{name}");
+    }
+
+    Ok(())
+}
+
+fn get_types(
+    pe: NativeAotBinary<'_>,
+    options: PrintOptions,
+    low_memory: bool,
+    page: Page,
+    filter: TypeFilter<'_>,
+    format: OutputFormat,
+) -> Result<()> {
+    let Some(metadata) = load_metadata(&pe) else {
+        return Ok(());
+    };
+
+    let mut progress = DecodeProgress::new(metadata);
+    let mut types = Vec::new();
+    let mut total = 0;
+
+    for def in select_scopes(metadata, filter.assembly)? {
+        progress.on_scope(&def);
+
+        for handle in type_handles_with_module(&def)? {
+            let in_page = total >= page.skip && page.limit.is_none_or(|limit| types.len() < limit);
+
+            // Types outside of the page are only counted, which doesn't need them decoded unless they're filtered
+            if !in_page && filter.generated == GeneratedTypes::Show {
+                total += 1;
+                continue;
+            }
+
+            let Ok(typ) = handle.to_data(metadata) else {
+                continue;
+            };
+
+            progress.on_type(&typ);
+
+            if !filter.generated.includes(&typ)? {
+                continue;
+            }
+
+            total += 1;
+
+            if in_page {
+                types.push(typ);
+            }
+        }
+    }
+
+    let shown = types.len();
+
+    match format {
+        OutputFormat::Text => {
+            let printer = TypePrinter::new(&pe, metadata, options, low_memory)?;
+
+            for typ in &types {
+                printer.print_type(typ, false)?;
+            }
+        }
+        OutputFormat::Json | OutputFormat::Csv => {
+            let method_ptrs = get_method_pointers(&pe)?;
+            let models = types
+                .iter()
+                .map(|typ| {
+                    model::build_type_model(
+                        &pe,
+                        metadata,
+                        typ,
+                        &method_ptrs,
+                        options.strict_signatures,
+                        options.with_tokens,
+                    )
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            if format == OutputFormat::Json {
+                println!("{}", serde_json::to_string_pretty(&models)?);
+            } else {
+                print_type_models_csv(&models);
+            }
+        }
+    }
+
+    if shown < total {
+        // Only the text output has room for a note, it would make the others unparsable
+        if format == OutputFormat::Text {
+            println!("... (limited; {shown} of {total} types shown)");
+        } else {
+            info!("Limited; {shown} of {total} types shown");
+        }
+    }
+
+    Ok(())
+}
+
+/// How `GetTypes` and `GetType` print types and their members
+#[derive(Debug, Clone, Copy)]
+struct PrintOptions {
+    style: Style,
+    addresses: AddressFormat,
+    /// Set by `--strict-signatures`, see [`ParentInfo::strict`]
+    strict_signatures: bool,
+    /// Set by `--with-tokens`, see [`TypePrinter::token`]
+    with_tokens: bool,
+}
+
+/// The part of the types `GetTypes` shows, set by `--skip` and `--limit`
+#[derive(Debug, Clone, Copy, Default)]
+struct Page {
+    skip: usize,
+    limit: Option<usize>,
+}
+
+/// How `GetTypes` writes the types
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// Declarations like the ones `GetType` prints
+    #[default]
+    Text,
+    /// An array of type models
+    Json,
+    /// A row for every field and method, with the type declaring it
+    Csv,
+}
+
+/// Writes a row for every field and method, with the offset of fields and the RVA of methods as the location
+fn print_type_models_csv(models: &[model::TypeModel]) {
+    fn quote(value: &str) -> String {
+        if value.contains([',', '"', '\n']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_string()
+        }
+    }
+
+    println!("type,kind,name,signature,location");
+
+    for model in models {
+        let typ = quote(&model.name);
+
+        for field in &model.fields {
+            let location = if field.is_static {
+                "static".to_string()
+            } else {
+                format!("{:#x}", field.offset)
+            };
+
+            println!(
+                "{typ},field,{},{},{location}",
+                quote(&field.name),
+                quote(&field.type_name)
+            );
+        }
+
+        for method in &model.methods {
+            let location = method
+                .rva
+                .map(|rva| format!("{rva:#x}"))
+                .unwrap_or_default();
+
+            println!(
+                "{typ},method,{},{},{location}",
+                quote(&method.name),
+                quote(&method.signature())
+            );
+        }
+    }
+}
+
+/// Logs the progress of decoding the types of the scopes, at the debug level
+struct DecodeProgress<'a> {
+    metadata: MetadataReader<'a>,
+    start: Instant,
+    types: usize,
+}
+
+impl<'a> DecodeProgress<'a> {
+    /// How many types to decode between two updates
+    const INTERVAL: usize = 10_000;
+
+    fn new(metadata: MetadataReader<'a>) -> Self {
+        Self {
+            metadata,
+            start: Instant::now(),
+            types: 0,
+        }
+    }
+}
+
+impl<'a> DecodeObserver<'a> for DecodeProgress<'a> {
+    fn on_scope(&mut self, scope: &ScopeDefinition<'a>) {
+        if let Ok(name) = scope.name.to_data(self.metadata) {
+            debug!("Decoding the types of {}", name.value);
+        }
+    }
+
+    fn on_type(&mut self, _typ: &TypeDefinition<'a>) {
+        self.types += 1;
+
+        if self.types.is_multiple_of(Self::INTERVAL) {
+            let elapsed = self.start.elapsed().as_secs_f64();
+            debug!(
+                "Decoded {} types ({:.0} types/s)",
+                self.types,
+                self.types as f64 / elapsed.max(f64::EPSILON)
+            );
+        }
+    }
+}
+
+/// Restricts the types `GetTypes` and `DumpIDA` go through
+#[derive(Debug, Clone, Copy, Default)]
+struct TypeFilter<'s> {
+    /// Only the types of this assembly, see [`select_scopes`]
+    assembly: Option<&'s str>,
+    generated: GeneratedTypes,
+}
+
+/// Which types to include based on whether they're compiler generated, see [`TypeDefinition::is_compiler_generated`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum GeneratedTypes {
+    #[default]
+    Show,
+    Hide,
+    Only,
+}
+
+impl GeneratedTypes {
+    fn from_flags(hide_generated: bool, only_generated: bool) -> Self {
+        if only_generated {
+            Self::Only
+        } else if hide_generated {
+            Self::Hide
+        } else {
+            Self::Show
+        }
+    }
+
+    fn includes(self, typ: &TypeDefinition<'_>) -> error::Result<bool> {
+        Ok(match self {
+            Self::Show => true,
+            Self::Hide => !typ.is_compiler_generated()?,
+            Self::Only => typ.is_compiler_generated()?,
+        })
+    }
+}
+
+/// The scope definitions of the metadata, or only the one of `assembly` if given
+///
+/// Assemblies are matched by their simple name. Fails with the list of assemblies when none matches.
+fn select_scopes<'a>(
+    metadata: MetadataReader<'a>,
+    assembly: Option<&str>,
+) -> Result<Vec<ScopeDefinition<'a>>> {
+    let scopes = metadata
+        .header()
+        .scope_definitions()
+        .iter()?
+        .flatten()
+        .flat_map(|hdl| hdl.to_data(metadata))
+        .collect::<Vec<_>>();
+
+    let Some(assembly) = assembly else {
+        return Ok(scopes);
+    };
+
+    let names = scopes
+        .iter()
+        .map(|scope| Ok(scope.name.to_data(metadata)?.value))
+        .collect::<error::Result<Vec<_>>>()?;
+
+    let Some(index) = names.iter().position(|name| name == assembly) else {
+        bail!(
+            "Assembly '{assembly}' not found, available assemblies are: {}",
+            names.join(", ")
+        );
+    };
+
+    Ok(vec![scopes[index].clone()])
+}
+
+/// All types of a scope, followed by its `<Module>` type if it declares any members
+fn types_with_module<'a>(scope: &ScopeDefinition<'a>) -> Result<Vec<TypeDefinition<'a>>> {
+    let mut types = scope.get_all_types()?;
+
+    if let Some(module) = listed_module_type(scope)
+        && !types.iter().any(|typ| typ.handle() == module.handle())
+    {
+        types.push(module);
+    }
+
+    Ok(types)
+}
+
+/// The handles of the types [`types_with_module`] returns, only decoding the `<Module>` type
+fn type_handles_with_module(scope: &ScopeDefinition<'_>) -> Result<Vec<TypeDefinitionHandle>> {
+    let mut handles = scope.type_handles();
+
+    if let Some(module) = listed_module_type(scope)
+        && !handles.contains(&module.handle())
+    {
+        handles.push(module.handle());
+    }
+
+    Ok(handles)
+}
+
+/// The `<Module>` type of the scope when it declares any members
+///
+/// A module type that fails to decode is left out with a warning. One whose members fail to read is still listed, so
+/// the members that can be read are shown.
+fn listed_module_type<'a>(scope: &ScopeDefinition<'a>) -> Option<TypeDefinition<'a>> {
+    let module = match scope.module_type() {
+        Ok(module) => module?,
+        Err(why) => {
+            warn!("Skipping the <Module> type: {why}");
+            return None;
+        }
+    };
+
+    let is_empty =
+        module.methods.is_empty().unwrap_or(false) && module.fields.is_empty().unwrap_or(false);
+
+    (!is_empty).then_some(module)
+}
+
+fn get_type(
+    pe: NativeAotBinary<'_>,
+    name: &str,
+    options: PrintOptions,
+    inherited: bool,
+    layout: bool,
+) -> Result<()> {
+    let Some(metadata) = load_metadata(&pe) else {
+        return Ok(());
+    };
+
+    let Some(typ) = metadata.find_type(name)? else {
+        bail!("Type '{name}' not found");
+    };
+
+    TypePrinter::new(&pe, metadata, options, false)?.print_type(&typ, inherited)?;
+
+    if layout {
+        print_layout(&typ, metadata)?;
+    }
+
+    Ok(())
+}
+
+/// Prints the layout declared by attributes next to the one recorded in the metadata, flagging the differences
+fn print_layout(typ: &TypeDefinition<'_>, metadata: MetadataReader<'_>) -> Result<()> {
+    let format_layout = |kind: Option<TypeLayout>, size: Option<u32>, packing_size: Option<u16>| {
+        let mut text = kind.map_or("Unknown".to_string(), |kind| format!("{kind:?}"));
+
+        if let Some(size) = size {
+            text.push_str(&format!(", Size: {size:#x}"));
+        }
+
+        if let Some(packing_size) = packing_size {
+            text.push_str(&format!(", Pack: {packing_size}"));
+        }
+
+        text
+    };
+
+    let declared = typ.declared_layout().unwrap_or(DeclaredLayout {
+        kind: TypeLayout::Auto,
+        size: None,
+        packing_size: None,
+    });
+    let mut mismatches = 0;
+
+    println!("Layout:");
+    println!(
+        " - Metadata: {}",
+        format_layout(Some(declared.kind), declared.size, declared.packing_size)
+    );
+
+    match typ.layout_attribute()? {
+        Some(attribute) => {
+            println!(
+                " - [StructLayout]: {}",
+                format_layout(attribute.kind, attribute.size, attribute.packing_size)
+            );
+
+            for (what, matches) in [
+                ("kind", attribute.kind == Some(declared.kind)),
+                ("size", attribute.size == declared.size),
+                (
+                    "packing size",
+                    attribute.packing_size == declared.packing_size,
+                ),
+            ] {
+                if !matches {
+                    warn!("The {what} declared by [StructLayout] doesn't match the metadata");
+                    mismatches += 1;
+                }
+            }
+        }
+        None => println!(" - [StructLayout]: not present"),
+    }
+
+    for field in typ
+        .fields
+        .iter()?
+        .flatten()
+        .flat_map(|hdl| hdl.to_data(metadata))
+        .filter(|field| !field.flags.is_static())
+    {
+        let name = field.name.to_data(metadata)?.value;
+
+        match field.offset_attribute()? {
+            Some(offset) => {
+                println!(
+                    "  * {name}: metadata +{:#x}, [FieldOffset] +{offset:#x}",
+                    field.offset
+                );
+
+                if offset != field.offset {
+                    warn!("The [FieldOffset] of {name} doesn't match the metadata");
+                    mismatches += 1;
+                }
+            }
+            None => println!("  * {name}: metadata +{:#x}", field.offset),
+        }
+    }
+
+    if mismatches > 0 {
+        warn!("Found {mismatches} layout mismatches");
+    }
+
+    Ok(())
+}
+
+/// Prints types the way `GetTypes` and `GetType` show them
+struct TypePrinter<'a, 'pe> {
+    pe: &'pe NativeAotBinary<'a>,
+    metadata: MetadataReader<'a>,
+    style: Style,
+    addresses: AddressFormat,
+    strict_signatures: bool,
+    with_tokens: bool,
+    method_ptrs: MethodPointers<'a>,
+    instantiations: HashMap<MethodHandle, Vec<GenericInstantiation>>,
+    type_handles: HashMap<Va, TypeDefinitionHandle>,
+}
+
+impl<'a, 'pe> TypePrinter<'a, 'pe> {
+    fn new(
+        pe: &'pe NativeAotBinary<'a>,
+        metadata: MetadataReader<'a>,
+        options: PrintOptions,
+        low_memory: bool,
+    ) -> Result<Self> {
+        // Find potential method pointers
+        let method_ptrs = if low_memory {
+            MethodPointers::on_demand(pe)?
+        } else {
+            MethodPointers::Prebuilt(get_method_pointers(pe)?)
+        };
+
+        // The instantiations are collected from the whole InvokeMap, which is what `--low-memory` avoids
+        let instantiations = if low_memory {
+            HashMap::new()
+        } else {
+            pe.invoke_map()?.generic_instantiations()?
+        };
+        let type_handles = if instantiations.is_empty() {
+            HashMap::new()
+        } else {
+            pe.type_map()?
+        };
+
+        Ok(Self {
+            pe,
+            metadata,
+            style: options.style,
+            addresses: options.addresses,
+            strict_signatures: options.strict_signatures,
+            with_tokens: options.with_tokens,
+            method_ptrs,
+            instantiations,
+            type_handles,
+        })
+    }
+
+    /// The raw handle of a type or method, printed after its declaration when `--with-tokens` is given
+    fn token(&self, handle: impl Handle) -> String {
+        if self.with_tokens {
+            self.style
+                .comment(&format!(" @{:#010x}", handle.to_value()))
+        } else {
+            String::new()
+        }
+    }
+
+    /// Prints a type and its members
+    ///
+    /// With `inherited`, the members of every base type are merged in as well. Members that are overridden or
+    /// hidden further down the hierarchy are only printed for the most derived type declaring them.
+    fn print_type(&self, typ: &TypeDefinition<'a>, inherited: bool) -> Result<()> {
+        let (metadata, style) = (self.metadata, self.style);
+        let type_name = style.declaration(&typ.get_full_name_with_generics()?);
+        let token = self.token(typ.handle());
+
+        // Delegates are printed using their Invoke signature rather than their compiler generated methods
+        if typ.is_delegate()
+            && let Some(invoke) = typ.find_method("Invoke")?
+            && let Ok(signature) = invoke.signature.to_data(metadata)
+        {
+            let parent = ParentInfo::both(&invoke, typ).strict(self.strict_signatures);
+            let return_type = get_return_type_name(&signature, parent, metadata)?;
+            let params = get_parameter_list(&signature, parent, metadata, style)?;
+
+            println!(
+                "{} {} {type_name}({params}){token}",
+                style.access("delegate"),
+                style.type_name(&return_type)
+            );
+            return Ok(());
+        }
+
+        let mut keywords = Vec::new();
+        if typ.is_readonly() {
+            keywords.push("readonly");
+        }
+
+        if typ.is_by_ref_like() {
+            keywords.push("ref struct");
+        }
+
+        let type_name = if keywords.is_empty() {
+            type_name
+        } else {
+            format!("{} {type_name}", style.access(&keywords.join(" ")))
+        };
+
+        // Enums are printed like they're declared, `value__` only holds the value of the constants
+        let underlying_type = typ.enum_underlying_type().and_then(ElementType::keyword);
+
+        if let Some(keyword) = underlying_type {
+            println!(
+                "{} {type_name} : {}{token}",
+                style.access("enum"),
+                style.type_name(keyword)
+            );
+        } else if !typ.base_type.is_nil() {
+            let base_name = get_type_name_from_handle(
+                typ.base_type,
+                ParentInfo::typ(typ).strict(self.strict_signatures),
+                metadata,
+            )?;
+
+            println!("{type_name} ({}){token}", style.type_name(&base_name));
+        } else {
+            println!("{type_name}{token}");
+        }
+
+        // Only meaningful for types that don't let the compiler decide on the layout
+        if let Some(layout) = typ.declared_layout()
+            && (layout.size.is_some() || layout.packing_size.is_some())
+        {
+            print!(" - Layout: {:?}", layout.kind);
+
+            if let Some(size) = layout.size {
+                print!(", Size: {}", style.address(&format!("{size:#x}")));
+            }
+
+            if let Some(packing_size) = layout.packing_size {
+                print!(", Pack: {packing_size}");
+            }
+
+            println!();
+        }
+
+        let mut declaring_types = vec![typ.clone()];
+        if inherited {
+            declaring_types.extend(typ.base_chain());
+        }
+
+        let mut fields = Vec::new();
+        let mut properties = Vec::new();
+        let mut events = Vec::new();
+        let mut methods = Vec::new();
+        let mut seen = HashSet::new();
+
+        for declaring_type in &declaring_types {
+            let origin = if declaring_type.handle() == typ.handle() {
+                None
+            } else {
+                Some(declaring_type.get_full_name_with_generics()?)
+            };
+
+            if let Ok(iter) = declaring_type.fields.iter() {
+                let mut declared_fields = iter
+                    .flatten()
+                    .flat_map(|hdl| hdl.to_data(metadata))
+                    .collect::<Vec<_>>();
+
+                // Types with a declared layout are listed in memory order, static fields last
+                if declaring_type.declared_layout().is_some() {
+                    declared_fields.sort_by_key(|field| (field.flags.is_static(), field.offset));
+                }
+
+                for field in declared_fields {
+                    let name = field.name.to_data(metadata)?.value;
+                    if underlying_type.is_some() && name == "value__" && !field.flags.is_static() {
+                        continue;
+                    }
+
+                    let line = self.format_field(declaring_type, &field)?;
+
+                    if seen.insert(format!("field {name}")) {
+                        fields.push((line, origin.clone()));
+                    }
+                }
+            }
+
+            if let Ok(iter) = declaring_type.properties.iter() {
+                for property in iter.flatten().flat_map(|hdl| hdl.to_data(metadata)) {
+                    let Some(line) = self.format_property(declaring_type, &property)? else {
+                        continue;
+                    };
+                    let name = property.name.to_data(metadata)?.value;
+
+                    if seen.insert(format!("property {name}")) {
+                        properties.push((line, origin.clone()));
+                    }
+                }
+            }
+
+            if let Ok(iter) = declaring_type.events.iter() {
+                for event in iter.flatten().flat_map(|hdl| hdl.to_data(metadata)) {
+                    let line = self.format_event(declaring_type, &event)?;
+                    let name = event.name.to_data(metadata)?.value;
+
+                    if seen.insert(format!("event {name}")) {
+                        events.push((line, origin.clone()));
+                    }
+                }
+            }
+
+            if let Ok(iter) = declaring_type.methods.iter() {
+                for method in iter.flatten().flat_map(|hdl| hdl.to_data(metadata)) {
+                    let Some((signature, line)) = self.format_method(declaring_type, &method)?
+                    else {
+                        continue;
+                    };
+
+                    // Overrides and `new` methods share the signature of the base method they replace
+                    if seen.insert(format!("method {signature}")) {
+                        methods.push((line, origin.clone()));
+                    }
+                }
+            }
+        }
+
+        for (title, members) in [
+            ("Fields", fields),
+            ("Properties", properties),
+            ("Events", events),
+            ("Methods", methods),
+        ] {
+            if members.is_empty() {
+                continue;
+            }
+
+            println!(" - {title}:");
+            for (line, origin) in members {
+                let origin = origin
+                    .map(|origin| style.comment(&format!(" [from {origin}]")))
+                    .unwrap_or_default();
+
+                println!("  * {}{origin}{}", line.declaration, line.trailer);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn format_field(&self, typ: &TypeDefinition<'a>, field: &Field<'a>) -> Result<MemberLine> {
+        let name = field.name.to_data(self.metadata)?.value;
+        let signature = field.signature.to_data(self.metadata)?;
+
+        let type_name = get_type_name_from_handle(
+            signature.type_handle,
+            ParentInfo::typ(typ).strict(self.strict_signatures),
+            self.metadata,
+        );
+        let type_name = name_or(type_name, "Unknown TypeDefinition")?;
+
+        // The offsets of auto layout types are decided at runtime
+        let offset = if typ.declared_layout().is_some() && !field.flags.is_static() {
+            format!(
+                "{} ",
+                self.style.address(&format!("[+{:#x}]", field.offset))
+            )
+        } else {
+            String::new()
+        };
+
+        // Values of constants, enum members mostly
+        let value = if field.flags.is_literal() && !field.default_value.is_nil() {
+            self.metadata
+                .decode_constant(field.default_value)
+                .map(|value| format!(" = {value}"))
+                .unwrap_or_default()
+        } else {
+            String::new()
+        };
+
+        Ok(MemberLine {
+            declaration: format!(
+                "{offset}{name} ({}){value}",
+                self.style.type_name(&type_name)
+            ),
+            trailer: String::new(),
+        })
+    }
+
+    fn format_property(
+        &self,
+        typ: &TypeDefinition<'a>,
+        property: &Property<'a>,
+    ) -> Result<Option<MemberLine>> {
+        let Ok(signature) = property.signature.to_data(self.metadata) else {
+            return Ok(None);
+        };
+
+        let name = property.name.to_data(self.metadata)?.value;
+        let type_name = get_type_name_from_handle(
+            signature.type_handle,
+            ParentInfo::typ(typ).strict(self.strict_signatures),
+            self.metadata,
+        )
+        .unwrap_or_else(|_| "Unknown TypeDefinition".to_string());
+
+        let (declaration, trailer) = self.format_accessors(
+            typ,
+            [("get", property.getter()?), ("set", property.setter()?)],
+        );
+
+        Ok(Some(MemberLine {
+            declaration: format!(
+                "{} {name} {{{declaration} }}",
+                self.style.type_name(&type_name)
+            ),
+            trailer,
+        }))
+    }
+
+    fn format_event(&self, typ: &TypeDefinition<'a>, event: &Event<'a>) -> Result<MemberLine> {
+        let name = event.name.to_data(self.metadata)?.value;
+        let type_name = get_type_name_from_handle(
+            event.type_handle,
+            ParentInfo::typ(typ).strict(self.strict_signatures),
+            self.metadata,
+        )
+        .unwrap_or_else(|_| "Unknown TypeDefinition".to_string());
+
+        let (declaration, trailer) = self.format_accessors(
+            typ,
+            [
+                ("add", event.add()?),
+                ("remove", event.remove()?),
+                ("raise", event.raise()?),
+            ],
+        );
+
+        Ok(MemberLine {
+            declaration: format!(
+                "{} {} {name} {{{declaration} }}",
+                self.style.access("event"),
+                self.style.type_name(&type_name)
+            ),
+            trailer,
+        })
+    }
+
+    /// Lists the accessors of a property or event like ` get; set;`, with the address of each in the trailer
+    fn format_accessors<const N: usize>(
+        &self,
+        typ: &TypeDefinition<'a>,
+        accessors: [(&str, Option<Method<'a>>); N],
+    ) -> (String, String) {
+        let mut declaration = String::new();
+        let mut trailer = String::new();
+
+        for (keyword, method) in accessors {
+            let Some(method) = method else {
+                continue;
+            };
+
+            declaration.push_str(&format!(" {keyword};"));
+
+            if let Some(va) = self.method_ptrs.get(method.handle(), typ.handle()) {
+                trailer.push_str(&self.style.comment(&format!(" {keyword}:")));
+                trailer.push_str(&format_address(self.pe, va, self.style, self.addresses));
+            }
+        }
+
+        if !trailer.is_empty() {
+            trailer.insert_str(0, &self.style.comment(" //"));
+        }
+
+        (declaration, trailer)
+    }
+
+    /// Formats a method together with its address and generic instantiations
+    ///
+    /// Also returns the signature of the method, used to tell overrides apart from overloads.
+    fn format_method(
+        &self,
+        typ: &TypeDefinition<'a>,
+        method: &Method<'a>,
+    ) -> Result<Option<(String, MemberLine)>> {
+        let (metadata, style) = (self.metadata, self.style);
+
+        let name = method.name.to_data(metadata)?.value;
+
+        let Ok(signature) = method.signature.to_data(metadata) else {
+            return Ok(None);
+        };
+
+        let generics = method.generic_parameters.iter().ok().and_then(|mut iter| {
+            let names = iter
+                .try_fold(Vec::new(), |mut acc, hdl| {
+                    let hdl = hdl?;
+                    let param = hdl.to_data(metadata)?;
+                    let name = param.name.to_data(metadata)?;
+                    acc.push(name.value);
+
+                    Ok::<_, anyhow::Error>(acc)
+                })
+                .ok()?;
+
+            if names.is_empty() {
+                return None;
+            }
+
+            Some(format!("<{}>", names.join(", ")))
+        });
+
+        let parent = ParentInfo::both(method, typ).strict(self.strict_signatures);
+        let return_type = get_return_type_name(&signature, parent, metadata)?;
+        let params = get_parameter_list(&signature, parent, metadata, Style::new(false))?;
+        let generics = generics.as_deref().unwrap_or("");
+
+        let mut declaration = String::new();
+
+        // Internal calls and runtime provided methods don't have an entrypoint of their own
+        let impl_flags = method.impl_flags;
+        let options = impl_flags.options();
+        if !options.is_empty() {
+            declaration.push_str(&format!(
+                "{} ",
+                style.comment(&format!("[MethodImpl({})]", options.join(", ")))
+            ));
+        }
+
+        match impl_flags.code_type() {
+            MethodCodeType::IL => {}
+            code_type => declaration.push_str(&format!(
+                "{} ",
+                style.comment(&format!("[{}]", format!("{code_type:?}").to_lowercase()))
+            )),
+        }
+
+        // Interface methods are abstract unless the interface provides a default implementation
+        if method.is_default_interface_method(typ) {
+            declaration.push_str(&format!("{} ", style.comment("[default]")));
+        }
+
+        let access = member_access_name(method.flags.member_access());
+        if !access.is_empty() {
+            declaration.push_str(&format!("{} ", style.access(access)));
+        }
+
+        declaration.push_str(&format!(
+            "{} {name}{generics}({})",
+            style.type_name(&return_type),
+            get_parameter_list(&signature, parent, metadata, style)?,
+        ));
+        declaration.push_str(&self.token(method.handle()));
+
+        let mut trailer = style.comment(" //");
+
+        if let Some(va) = self.method_ptrs.get(method.handle(), typ.handle()) {
+            trailer.push_str(&format_address(self.pe, va, style, self.addresses));
+        }
+
+        trailer.push_str(&style.comment(&format!(
+            " Conv: {}",
+            calling_convention_name(method, &signature)
+        )));
+
+        // Every instantiation of a generic method is compiled separately, except that reference types share `__Canon` code
+        for instance in self
+            .instantiations
+            .get(&method.handle())
+            .into_iter()
+            .flatten()
+        {
+            let args = get_type_names_from_mts(
+                self.pe,
+                &instance.generic_arguments,
+                &self.type_handles,
+                metadata,
+            );
+
+            trailer.push_str(&format!(
+                "\n    > {name}<{}>{}{}",
+                style.type_name(&args),
+                style.comment(" //"),
+                format_address(self.pe, instance.entrypoint, style, self.addresses)
+            ));
+        }
+
+        // Compared without the names of generic parameters, which overrides are free to rename
+        let key = match signature.canonical_string() {
+            Ok(canonical) => format!("{name} {canonical}"),
+            Err(_) => format!("{return_type} {name}{generics}({params})"),
+        };
+
+        Ok(Some((
+            key,
+            MemberLine {
+                declaration,
+                trailer,
+            },
+        )))
+    }
+}
+
+/// A printed member, split so the type declaring it can be inserted in between
+struct MemberLine {
+    declaration: String,
+    /// Address comments and anything else following the declaration
+    trailer: String,
+}
+
+fn get_entrypoint(pe: NativeAotBinary<'_>, addresses: AddressFormat) -> Result<()> {
+    let Some(metadata) = load_metadata(&pe) else {
+        return Ok(());
+    };
+
+    let Some((typ, method)) = metadata.entrypoint()? else {
+        warn!("Image does not define an entrypoint");
+        return Ok(());
+    };
+
+    let type_name = typ.get_full_name_with_generics()?;
+    let name = method.name.to_data(metadata)?.value;
+
+    let rva = find_method_rva(&pe, method.handle());
+
+    print!("{type_name}.{name}");
+
+    if let Some(rva) = rva {
+        print!(
+            " //{}",
+            format_address(&pe, pe.pe().rva_to_va(rva)?, Style::new(false), addresses)
+        );
+    }
+
+    println!();
+
+    Ok(())
+}
+
+fn dump_header(pe: NativeAotBinary<'_>) -> Result<()> {
+    let header = pe.rtr_header();
+
+    println!("Signature: {:?}", header.signature());
+    println!("Version: {}.{}", header.major_version, header.minor_version);
+    println!("Flags: {:#x}", header.flags);
+    println!(
+        "Sections: {} (entry size {:#x}, entry type {})",
+        header.number_of_sections, header.entry_size, header.entry_type
+    );
+
+    for section in &header.sections {
+        let (start, end) = (section.start.va(), section.end.va());
+
+        println!(
+            "  {:<40} flags {:#x} {start:#x}..{end:#x} ({:#x} bytes)",
+            format!("{:?}", section.section_type),
+            section.flags,
+            end.saturating_sub(start)
+        );
+    }
+
+    for (index, why) in &header.section_errors {
+        println!("  Section {index} skipped: {why}");
+    }
+
+    if let Some(type_manager) = pe.type_manager() {
+        let region = |va: Option<Va>| va.map_or("-".to_string(), |va| format!("{va:#x}"));
+
+        println!("TypeManager:");
+        println!("  Indirection: {:#x}", type_manager.indirection);
+        match type_manager.handle {
+            Some(0) => println!("  Handle: unset until the module is registered"),
+            Some(handle) => println!("  Handle: {handle:#x}"),
+            None => println!("  Handle: not backed by the file"),
+        }
+        println!("  Header: {:#x}", type_manager.header);
+        println!(
+            "  GCStaticRegion: {}",
+            region(type_manager.gc_static_region)
+        );
+        println!(
+            "  ThreadStaticRegion: {}",
+            region(type_manager.thread_static_region)
+        );
+    }
+
+    let mvids = header.manifest_assembly_mvids()?;
+    if !mvids.is_empty() {
+        println!("Manifest assembly MVIDs:");
+
+        for (index, mvid) in mvids.iter().enumerate() {
+            println!("  {index:>4} {}", format_guid(mvid));
+        }
+    }
+
+    Ok(())
+}
+
+/// Looks up the code of a single method, which has to be exposed to reflection
+fn find_method_rva(pe: &NativeAotBinary<'_>, method: MethodHandle) -> Option<Rva> {
+    get_method_pointers(pe)
+        .ok()
+        .and_then(|method_ptrs| method_ptrs.get(&method).copied())
+        .and_then(|va| pe.pe().va_to_rva(va).ok())
+}
+
+fn dump_function(pe: NativeAotBinary<'_>, function: &str, output: Option<&Path>) -> Result<()> {
+    let rva = match function.strip_prefix("0x") {
+        Some(hex) => Rva::from_str_radix(hex, 16)?,
+        None => {
+            let Some(metadata) = pe.rtr_header().metadata_result()? else {
+                bail!("Image is missing a metadata section");
+            };
+
+            let Some((type_name, method_name)) = function.rsplit_once('.') else {
+                bail!("Expected a method name in the form of `Namespace.Type.Method`, or an RVA");
+            };
+
+            let Some(typ) = metadata.find_type(type_name)? else {
+                bail!("Type '{type_name}' not found");
+            };
+
+            let Some(method) = typ.find_method(method_name)? else {
+                bail!("Method '{method_name}' not found on '{type_name}'");
+            };
+
+            let Some(rva) = find_method_rva(&pe, method.handle()) else {
+                bail!("'{function}' has no compiled code");
+            };
+
+            rva
+        }
+    };
+
+    let Some(bytes) = pe.function_bytes(rva) else {
+        bail!("Unable to size the function at {rva:#x}, the image needs a RuntimeFunctions table");
+    };
+
+    if let Some(path) = output {
+        std::fs::write(path, bytes)?;
+        info!("Wrote {} bytes to {}", bytes.len(), path.display());
+
+        return Ok(());
+    }
+
+    for (index, line) in bytes.chunks(16).enumerate() {
+        let hex = line
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        println!("{:08x}: {hex}", rva as usize + index * 16);
+    }
+
+    Ok(())
+}
+
+fn parse_va(va: &str) -> Result<Va> {
+    Ok(Va::from_str_radix(va.strip_prefix("0x").unwrap_or(va), 16)?)
+}
+
+fn symbolize(pe: NativeAotBinary<'_>, va: &str) -> Result<()> {
+    let va = parse_va(va)?;
+
+    // Built up front, so a missing table is reported instead of every address being unknown
+    pe.symbol_index()?;
+
+    match pe.symbolize(va) {
+        Some((name, 0)) => println!("{name}"),
+        Some((name, delta)) => println!("{name}+{delta:#x}"),
+        None => println!("<unknown>"),
+    }
+
+    Ok(())
+}
+
+fn classify(pe: NativeAotBinary<'_>, addresses: &[String]) -> Result<()> {
+    let addresses = addresses
+        .iter()
+        .map(|va| parse_va(va))
+        .collect::<Result<Vec<_>>>()?;
+
+    // Built up front, so a failing scan is reported instead of every address being unknown
+    pe.address_index()?;
+
+    for va in addresses {
+        let kind = format!("{:?}", pe.classify_address(va));
+
+        match pe.symbolize(va) {
+            Some((name, 0)) => println!("{va:#x} {kind:<12} {name}"),
+            Some((name, delta)) => println!("{va:#x} {kind:<12} {name}+{delta:#x}"),
+            None => println!("{va:#x} {kind}"),
+        }
+    }
+
+    Ok(())
+}
+
+fn dump_mt(pe: NativeAotBinary<'_>, name: &str) -> Result<()> {
+    let Some(metadata) = load_metadata(&pe) else {
+        return Ok(());
+    };
+
+    let Some(typ) = metadata.find_type(name)? else {
+        bail!("Type '{name}' not found");
+    };
+
+    let Some(va) = pe.find_method_table(&typ)? else {
+        bail!("'{name}' has no MethodTable in the TypeMap");
+    };
+
+    let mut view = View::new(pe.pe(), va);
+    let mt = MethodTable::parse(&mut view)?;
+
+    let Some(bytes) = View::new(pe.pe(), va)
+        .bytes()?
+        .get(..mt.size() as usize)
+        .map(<[u8]>::to_vec)
+    else {
+        bail!("The MethodTable of '{name}' runs past the end of its section");
+    };
+
+    let mut fields = vec![
+        (0x00, 4, "flags".to_string(), format!("{:#010x}", mt.flags)),
+        (
+            0x04,
+            4,
+            "base_size".to_string(),
+            format!("{:#x}", mt.base_size),
+        ),
+        (
+            0x08,
+            8,
+            "related_type".to_string(),
+            format!("{:#x}", mt.related_type_address),
+        ),
+        (
+            0x10,
+            2,
+            "vtable_count".to_string(),
+            mt.vtable_addresses.len().to_string(),
+        ),
+        (
+            0x12,
+            2,
+            "iface_count".to_string(),
+            mt.iface_addresses.len().to_string(),
+        ),
+        (
+            0x14,
+            4,
+            "hashcode".to_string(),
+            format!("{:#010x}", mt.hashcode),
+        ),
+    ];
+
+    let vtable_start = 0x18;
+    let iface_start = vtable_start + 8 * mt.vtable_addresses.len();
+    // Slots are named after the method they call, where it's known
+    let layout = pe.vtable_layout(&typ)?;
+    fields.extend(mt.vtable_addresses.iter().enumerate().map(|(slot, va)| {
+        let method = layout
+            .get(slot)
+            .and_then(|entry| entry.method.as_ref())
+            .and_then(|method| method.name.to_data(metadata).ok());
+
+        (
+            vtable_start + 8 * slot,
+            8,
+            format!("vtable[{slot}]"),
+            match method {
+                Some(name) => format!("{va:#x} {}", name.value),
+                None => format!("{va:#x}"),
+            },
+        )
+    }));
+    fields.extend(mt.iface_addresses.iter().enumerate().map(|(index, va)| {
+        (
+            iface_start + 8 * index,
+            8,
+            format!("interfaces[{index}]"),
+            format!("{va:#x}"),
+        )
+    }));
+
+    println!(
+        "{name} MethodTable at {va:#x} ({:?}, {:#x} bytes)",
+        mt.element_type,
+        bytes.len()
+    );
+
+    for (offset, size, field, value) in fields {
+        let hex = bytes[offset..offset + size]
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        println!("+{offset:#06x}: {hex:<23} {field} = {value}");
+    }
+
+    Ok(())
+}
+
+fn find_field(
+    pe: NativeAotBinary<'_>,
+    name: &str,
+    addresses: AddressFormat,
+    strict_signatures: bool,
+) -> Result<()> {
+    let Some(metadata) = load_metadata(&pe) else {
+        return Ok(());
+    };
+
+    let Some((type_name, field_name)) = name.rsplit_once('.') else {
+        bail!("Expected a field name in the form of `Namespace.Type.Field`");
+    };
+
+    let Some(typ) = metadata.find_type(type_name)? else {
+        bail!("Type '{type_name}' not found");
+    };
+
+    let Some(field) = typ.find_field(field_name)? else {
+        bail!("Field '{field_name}' not found on '{type_name}'");
+    };
+
+    let signature = field.signature.to_data(metadata)?;
+    let field_type = get_type_name_from_handle(
+        signature.type_handle,
+        ParentInfo::typ(&typ).strict(strict_signatures),
+        metadata,
+    );
+    let field_type = name_or(field_type, "Unknown TypeDefinition")?;
+
+    print!("{field_type} {name} //");
+
+    if !field.flags.is_static() {
+        // Instance field offsets are available from the metadata itself
+        println!(" Offset: {:#x}", field.offset);
+        return Ok(());
+    }
+
+    match pe.field_storage(field.handle())? {
+        Some(FieldStorage::Static { address }) => match addresses {
+            AddressFormat::Rva => println!(" VA: {address:#x}"),
+            AddressFormat::FileOffset => {
+                println!(
+                    "{}",
+                    format_address(&pe, address, Style::new(false), addresses)
+                )
+            }
+        },
+        Some(FieldStorage::StaticOffset { offset }) => {
+            println!(" Offset: {offset:#x} (from the statics base of the instantiation)")
+        }
+        Some(FieldStorage::ThreadStatic { offset, tls_offset }) => match tls_offset {
+            Some(tls_offset) => println!(" TLS offset: {tls_offset:#x} (thread static)"),
+            None => println!(" Offset: {offset:#x} (from the thread static block of the type)"),
+        },
+        Some(FieldStorage::Instance { offset }) => println!(" Offset: {offset:#x}"),
+        None => println!(" <not in FieldAccessMap>"),
+    }
+
+    Ok(())
+}
+
+fn dump_frozen_strings(pe: NativeAotBinary<'_>, addresses: AddressFormat) -> Result<()> {
+    let strings = pe.frozen_strings()?;
+    if strings.is_empty() {
+        warn!("Image has no frozen strings");
+    }
+
+    for (va, text) in strings {
+        println!(
+            "{} {text:?}",
+            format_address(&pe, va, Style::new(false), addresses)
+        );
+    }
+
+    Ok(())
+}
+
+fn list_initializers(pe: NativeAotBinary<'_>, addresses: AddressFormat) -> Result<()> {
+    if load_metadata(&pe).is_none() {
+        return Ok(());
+    }
+
+    let names = pe.method_names_by_address()?;
+
+    for (kind, functions) in [
+        ("cctor", pe.eager_cctors()?),
+        ("module initializer", pe.module_initializers()?),
+    ] {
+        for va in functions {
+            let name = names.get(&va).map_or("<unknown>", String::as_str);
+
+            println!(
+                "{kind} {name}{}",
+                format_address(&pe, va, Style::new(false), addresses)
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct TokenMapEntry {
+    /// The raw `BaseHandle` value, with the handle type in the upper bits
+    handle: u32,
+    /// The handle as it is stored in the metadata stream
+    token: u32,
+    kind: &'static str,
+    name: String,
+}
+
+fn dump_token_map(pe: NativeAotBinary<'_>, strict_signatures: bool, json: bool) -> Result<()> {
+    let Some(metadata) = load_metadata(&pe) else {
+        return Ok(());
+    };
+
+    let mut entries = Vec::new();
+    let mut push = |handle: BaseHandle, kind, name| {
+        entries.push(TokenMapEntry {
+            handle: handle.to_value(),
+            token: handle.to_token(),
+            kind,
+            name,
+        })
+    };
+
+    for def in metadata
+        .header()
+        .scope_definitions()
+        .iter()?
+        .flatten()
+        .flat_map(|hdl| hdl.to_data(metadata))
+    {
+        for typ in types_with_module(&def)? {
+            let type_name = typ.get_full_name_with_generics()?;
+            push(typ.handle().to_base(), "type", type_name.clone());
+
+            let Ok(iter) = typ.methods.iter() else {
+                continue;
+            };
+
+            // Methods include their signature, as overloads share a name
+            for method in iter.flatten().flat_map(|hdl| hdl.to_data(metadata)) {
+                let name = method.name.to_data(metadata)?.value;
+                let Ok(signature) = method.signature.to_data(metadata) else {
+                    push(
+                        method.handle().to_base(),
+                        "method",
+                        format!("{type_name}.{name}"),
+                    );
+                    continue;
+                };
+
+                let parent = ParentInfo::both(&method, &typ).strict(strict_signatures);
+                push(
+                    method.handle().to_base(),
+                    "method",
+                    format!(
+                        "{} {type_name}.{name}({})",
+                        get_return_type_name(&signature, parent, metadata)?,
+                        get_parameter_list(&signature, parent, metadata, Style::new(false))?
+                    ),
+                );
+            }
+        }
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+    } else {
+        for entry in entries {
+            println!(
+                "{:#010x} {:#010x} {} {}",
+                entry.handle, entry.token, entry.kind, entry.name
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct AssemblyGraph {
+    assemblies: Vec<AssemblyNode>,
+    edges: Vec<AssemblyEdge>,
+}
+
+#[derive(Serialize)]
+struct AssemblyNode {
+    name: String,
+    version: String,
+}
+
+#[derive(Serialize, PartialEq, Eq, PartialOrd, Ord)]
+struct AssemblyEdge {
+    from: String,
+    to: String,
+    /// The version of `to` that `from` was compiled against
+    version: String,
+}
+
+fn dump_assembly_graph(pe: NativeAotBinary<'_>, json: bool) -> Result<()> {
+    let Some(metadata) = load_metadata(&pe) else {
+        return Ok(());
+    };
+
+    let version = |name: &AssemblyName| {
+        let (major, minor, build, revision) = name.version;
+        format!("{major}.{minor}.{build}.{revision}")
+    };
+
+    let mut assemblies = Vec::new();
+    let mut edges = BTreeSet::new();
+
+    for def in metadata
+        .header()
+        .scope_definitions()
+        .iter()?
+        .flatten()
+        .flat_map(|hdl| hdl.to_data(metadata))
+    {
+        let assembly = def.assembly_name()?;
+        let mut references = ReferencedAssemblies {
+            reader: metadata,
+            found: HashSet::new(),
+        };
+
+        for typ in types_with_module(&def)? {
+            references.collect(typ.base_type);
+
+            for interface in typ.interfaces.iter()?.flatten() {
+                references.collect(interface);
+            }
+
+            for field in typ
+                .fields
+                .iter()?
+                .flatten()
+                .flat_map(|hdl| hdl.to_data(metadata))
+            {
+                if let Ok(signature) = field.signature.to_data(metadata) {
+                    references.collect(signature.type_handle);
+                }
+            }
+
+            for method in typ
+                .methods
+                .iter()?
+                .flatten()
+                .flat_map(|hdl| hdl.to_data(metadata))
+            {
+                let Ok(signature) = method.signature.to_data(metadata) else {
+                    continue;
+                };
+
+                references.collect(signature.return_type);
+
+                for param in signature.parameters.iter()?.flatten() {
+                    references.collect(param);
+                }
+            }
+        }
+
+        // References to the assembly's own types aren't edges
+        for referenced in references.found {
+            if referenced.name != assembly.name {
+                edges.insert(AssemblyEdge {
+                    from: assembly.name.clone(),
+                    version: version(&referenced),
+                    to: referenced.name,
+                });
+            }
+        }
+
+        assemblies.push(AssemblyNode {
+            version: version(&assembly),
+            name: assembly.name,
+        });
+    }
+
+    if json {
+        let graph = AssemblyGraph {
+            assemblies,
+            edges: edges.into_iter().collect(),
+        };
+
+        println!("{}", serde_json::to_string_pretty(&graph)?);
+    } else {
+        println!("digraph assemblies {{");
+
+        for assembly in assemblies {
+            println!(
+                "    {:?} [label=\"{} {}\"];",
+                assembly.name, assembly.name, assembly.version
+            );
+        }
+
+        for edge in edges {
+            println!(
+                "    {:?} -> {:?} [label={:?}];",
+                edge.from, edge.to, edge.version
+            );
+        }
+
+        println!("}}");
+    }
+
+    Ok(())
+}
+
+/// Collects the assemblies a signature refers to, through the scope of every type definition and reference in it
+struct ReferencedAssemblies<'a> {
+    reader: MetadataReader<'a>,
+    found: HashSet<AssemblyName>,
+}
+
+impl<'a> ReferencedAssemblies<'a> {
+    fn collect(&mut self, handle: BaseHandle) {
+        let reader = self.reader;
+        let scope = |leaf: BaseHandle| match leaf.handle_type() {
+            Some(HandleType::TypeDefinition) => leaf
+                .to_handle::<TypeDefinitionHandle>()?
+                .to_data(reader)?
+                .owning_scope()?
+                .assembly_name()
+                .map(Some),
+            Some(HandleType::TypeReference) => leaf
+                .to_handle::<TypeReferenceHandle>()?
+                .to_data(reader)?
+                .owning_scope()?
+                .assembly_name()
+                .map(Some),
+            _ => Ok(None),
+        };
+
+        // Types whose scope can't be decoded just don't add an edge
+        LeafTypes::walk(reader, handle, |leaf| {
+            if let Ok(Some(assembly)) = scope(leaf) {
+                self.found.insert(assembly);
+            }
+
+            false
+        });
+    }
+}
+
+fn dump_statics(pe: NativeAotBinary<'_>, addresses: AddressFormat) -> Result<()> {
+    let Some(metadata) = load_metadata(&pe) else {
+        return Ok(());
+    };
+
+    let type_handles = pe.type_map()?;
+    let contexts = match pe.cctor_contexts() {
+        Ok(contexts) => contexts
+            .into_iter()
+            .map(|context| (context.declaring_type, context))
+            .collect(),
+        Err(why) => {
+            warn!("Unable to read the CCtorContextMap: {why}");
+            HashMap::new()
+        }
+    };
+    let cctor_names = match pe.method_names_by_address() {
+        Ok(names) => names,
+        Err(why) => {
+            warn!("Unable to name the cctors: {why}");
+            HashMap::new()
+        }
+    };
+
+    // Grouped by type, so the cctor of a type is listed together with the statics it initializes
+    let mut types = BTreeMap::<Va, Vec<_>>::new();
+    for entry in pe.field_access_entries()? {
+        if let Some(kind) = entry.static_kind() {
+            types
+                .entry(entry.declaring_type)
+                .or_default()
+                .push((kind, entry));
+        }
+    }
+    for &declaring_type in contexts.keys() {
+        types.entry(declaring_type).or_default();
+    }
+
+    for (declaring_type, fields) in types {
+        let type_name = match type_handles.get(&declaring_type) {
+            Some(handle) => handle.to_data(metadata)?.get_full_name_with_generics()?,
+            None => format!("<MethodTable {declaring_type:#x}>"),
+        };
+        let context = contexts.get(&declaring_type);
+
+        if let Some(context) = context {
+            let cctor = match context.cctor {
+                Some(cctor) => format!(
+                    " {}{}",
+                    cctor_names.get(&cctor).map_or("<unknown>", String::as_str),
+                    format_address(&pe, cctor, Style::new(false), addresses)
+                ),
+                None => " <none>".to_string(),
+            };
+
+            println!(
+                "{:<12} {type_name}{} Cctor:{cctor}",
+                "CctorContext",
+                format_address(&pe, context.context, Style::new(false), addresses)
+            );
+        }
+
+        for (kind, entry) in fields {
+            let field_name = entry.field.to_data(metadata)?.name.to_data(metadata)?.value;
+
+            let storage = match entry.storage {
+                FieldStorage::Static { address } => {
+                    format_address(&pe, address, Style::new(false), addresses)
+                }
+                // Non-GC statics are relative to the base following the cctor context
+                FieldStorage::StaticOffset { offset } => match context {
+                    Some(context) if kind == StaticKind::NonGc => format_address(
+                        &pe,
+                        context.non_gc_statics() + offset as u64,
+                        Style::new(false),
+                        addresses,
+                    ),
+                    _ => format!(" Offset: {offset:#x}"),
+                },
+                FieldStorage::ThreadStatic {
+                    tls_offset: Some(tls_offset),
+                    ..
+                } => format!(" TLS offset: {tls_offset:#x}"),
+                FieldStorage::ThreadStatic { offset, .. } => format!(" Offset: {offset:#x}"),
+                FieldStorage::Instance { .. } => continue,
+            };
+
+            println!(
+                "{:<12} {type_name}.{field_name}{storage}",
+                format!("{kind:?}")
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn find_by_attribute(pe: NativeAotBinary<'_>, name: &str) -> Result<()> {
+    let Some(metadata) = load_metadata(&pe) else {
+        return Ok(());
+    };
+
+    for (target, attribute) in metadata.find_by_attribute(name)? {
+        match target {
+            AttributeTarget::Type(typ) => print!("type {}", typ.get_full_name_with_generics()?),
+            AttributeTarget::Method(typ, method) => print!(
+                "method {}::{}",
+                typ.get_full_name_with_generics()?,
+                method.name.to_data(metadata)?.value
+            ),
+            AttributeTarget::Field(typ, field) => print!(
+                "field {}::{}",
+                typ.get_full_name_with_generics()?,
+                field.name.to_data(metadata)?.value
+            ),
+            AttributeTarget::Property(typ, property) => print!(
+                "property {}::{}",
+                typ.get_full_name_with_generics()?,
+                property.name.to_data(metadata)?.value
+            ),
+        }
+
+        // Arguments that can't be decoded are left out rather than hiding the match
+        match attribute.format_arguments() {
+            Ok(arguments) if arguments != "()" => println!(" {arguments}"),
+            _ => println!(),
+        }
+    }
+
+    Ok(())
+}
+
+fn find_references(pe: NativeAotBinary<'_>, name: &str) -> Result<()> {
+    let Some(metadata) = load_metadata(&pe) else {
+        return Ok(());
+    };
+
+    let Some(target) = metadata.find_type(name)? else {
+        bail!("Type '{name}' not found");
+    };
+
+    let mut mentions = TypeMentions {
+        target: target.handle(),
+        reader: metadata,
+    };
+
+    // A member is only reported once, no matter how many of its parameters use the type
+    let mut references = Vec::new();
+    let mut seen = HashSet::new();
+    let mut report = |reference: String| {
+        if seen.insert(reference.clone()) {
+            references.push(reference);
+        }
+    };
+
+    for def in metadata
+        .header()
+        .scope_definitions()
+        .iter()?
+        .flatten()
+        .flat_map(|hdl| hdl.to_data(metadata))
+    {
+        for typ in types_with_module(&def)? {
+            let type_name = typ.get_full_name_with_generics()?;
+
+            if !typ.base_type.is_nil() && mentions.check(typ.base_type) {
+                report(format!("base type of {type_name}"));
+            }
+
+            for interface in typ.interfaces.iter()?.flatten() {
+                if mentions.check(interface) {
+                    report(format!("interface of {type_name}"));
+                }
+            }
+
+            for field in typ
+                .fields
+                .iter()?
+                .flatten()
+                .flat_map(|hdl| hdl.to_data(metadata))
+            {
+                let Ok(signature) = field.signature.to_data(metadata) else {
+                    continue;
+                };
+
+                if mentions.check(signature.type_handle) {
+                    report(format!(
+                        "field {type_name}::{}",
+                        field.name.to_data(metadata)?.value
+                    ));
+                }
+            }
+
+            for method in typ
+                .methods
+                .iter()?
+                .flatten()
+                .flat_map(|hdl| hdl.to_data(metadata))
+            {
+                let Ok(signature) = method.signature.to_data(metadata) else {
+                    continue;
+                };
+
+                let uses_type = mentions.check(signature.return_type)
+                    || signature
+                        .parameters
+                        .iter()?
+                        .flatten()
+                        .any(|param| mentions.check(param));
+
+                if uses_type {
+                    report(format!(
+                        "method {type_name}::{}",
+                        method.name.to_data(metadata)?.value
+                    ));
+                }
+            }
+
+            for property in typ
+                .properties
+                .iter()?
+                .flatten()
+                .flat_map(|hdl| hdl.to_data(metadata))
+            {
+                let Ok(signature) = property.signature.to_data(metadata) else {
+                    continue;
+                };
+
+                if mentions.check(signature.type_handle) {
+                    report(format!(
+                        "property {type_name}::{}",
+                        property.name.to_data(metadata)?.value
+                    ));
+                }
+            }
+        }
+    }
+
+    for reference in references {
+        println!("{reference}");
+    }
+
+    Ok(())
+}
+
+/// Whether a type signature mentions a type definition, either directly or as one of its generic arguments
+struct TypeMentions<'a> {
+    target: TypeDefinitionHandle,
+    reader: MetadataReader<'a>,
+}
+
+impl<'a> TypeMentions<'a> {
+    fn check(&mut self, handle: BaseHandle) -> bool {
+        // Type definitions and references resolve directly, the other leaves can't mention a type
+        LeafTypes::walk(self.reader, handle, |leaf| {
+            leaf.resolve_to_type_definition(self.reader) == Some(self.target)
+        })
+    }
+}
+
+/// Walks a type signature down to the types it is built from, like the element type of an array or the arguments
+/// of a generic instantiation
+///
+/// Every leaf, which is any handle that isn't one of these signatures, is passed to `on_leaf` until it returns
+/// `true`. Signatures that fail to decode are skipped.
+struct LeafTypes<'a, F> {
+    reader: MetadataReader<'a>,
+    on_leaf: F,
+}
+
+impl<'a, F: FnMut(BaseHandle) -> bool> LeafTypes<'a, F> {
+    /// Whether `on_leaf` returned `true` for any of the leaves of `handle`
+    fn walk(reader: MetadataReader<'a>, handle: BaseHandle, on_leaf: F) -> bool {
+        LeafTypes { reader, on_leaf }.visit(handle)
+    }
+
+    fn visit(&mut self, handle: BaseHandle) -> bool {
+        handle.visit(self.reader, self).unwrap_or(false)
+    }
+}
+
+impl<'a, F: FnMut(BaseHandle) -> bool> HandleVisitor<'a> for LeafTypes<'a, F> {
+    type Output = bool;
+
+    fn visit_other(&mut self, handle: BaseHandle) -> error::Result<bool> {
+        Ok((self.on_leaf)(handle))
+    }
+
+    fn visit_type_specification(&mut self, typespec: TypeSpecification<'a>) -> error::Result<bool> {
+        Ok(self.visit(typespec.signature))
+    }
+
+    fn visit_type_instantiation_signature(
+        &mut self,
+        typeinst: TypeInstantiationSignature<'a>,
+    ) -> error::Result<bool> {
+        Ok(self.visit(typeinst.generic_type)
+            || typeinst
+                .generic_args
+                .iter()?
+                .flatten()
+                .any(|arg| self.visit(arg)))
+    }
+
+    fn visit_by_reference_signature(
+        &mut self,
+        refsig: ByReferenceSignature<'a>,
+    ) -> error::Result<bool> {
+        Ok(self.visit(refsig.type_handle))
+    }
+
+    fn visit_sz_array_signature(&mut self, array: SZArraySignature<'a>) -> error::Result<bool> {
+        Ok(self.visit(array.element_type))
+    }
+
+    fn visit_array_signature(&mut self, array: ArraySignature<'a>) -> error::Result<bool> {
+        Ok(self.visit(array.element_type))
+    }
+
+    fn visit_pointer_signature(&mut self, pointer: PointerSignature<'a>) -> error::Result<bool> {
+        Ok(self.visit(pointer.type_handle))
+    }
+
+    fn visit_modified_type(&mut self, modified: ModifiedType<'a>) -> error::Result<bool> {
+        Ok(self.visit(modified.type_handle))
+    }
+}
+
+fn find_pinvokes(pe: NativeAotBinary<'_>) -> Result<()> {
+    let Some(metadata) = load_metadata(&pe) else {
+        return Ok(());
+    };
+
+    for def in metadata
+        .header()
+        .scope_definitions()
+        .iter()?
+        .flatten()
+        .flat_map(|hdl| hdl.to_data(metadata))
+    {
+        for typ in types_with_module(&def)? {
+            let Ok(iter) = typ.methods.iter() else {
+                continue;
+            };
+
+            for method in iter.flatten().flat_map(|hdl| hdl.to_data(metadata)) {
+                // `[LibraryImport]` methods are regular methods wrapping a generated P/Invoke
+                let import = method.pinvoke_import()?;
+                if !method.flags.is_pinvoke() && import.is_none() {
+                    continue;
+                }
+
+                let mut line = format!(
+                    "method {}::{}",
+                    typ.get_full_name_with_generics()?,
+                    method.name.to_data(metadata)?.value
+                );
+
+                if let Some(import) = import {
+                    let module = import.module.as_deref().unwrap_or("?");
+                    line.push_str(&format!(" => {module}!{}", import.entry_point));
+
+                    let mut options = Vec::new();
+                    if let Some(exact_spelling) = import.exact_spelling {
+                        options.push(format!("ExactSpelling = {exact_spelling}"));
+                    }
+
+                    if let Some(char_set) = import.char_set {
+                        options.push(format!("CharSet = {char_set}"));
+                    }
+
+                    if let Some(convention) = import.calling_convention {
+                        options.push(format!("CallingConvention = {convention}"));
+                    }
+
+                    if !options.is_empty() {
+                        line.push_str(&format!(" ({})", options.join(", ")));
+                    }
+                }
+
+                println!("{line}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn diff(
+    old: NativeAotBinary<'_>,
+    new_path: &Path,
+    scan_sections: ScanSections,
+    strict_strings: bool,
+    strict_signatures: bool,
+    json: bool,
+) -> Result<()> {
+    let data = std::fs::read(new_path)?;
+    let new = NativeAotBinary::load_pe(PeFile::from_bytes(&data)?, scan_sections)?
+        .with_strict_strings(strict_strings);
+
+    let mut report = diff::diff_types(
+        &model::build_type_models(&old, strict_signatures)?,
+        &model::build_type_models(&new, strict_signatures)?,
+    );
+
+    // Composite images identify their component assemblies by MVID, which only stays the same for identical builds
+    let old_mvids = old.rtr_header().manifest_assembly_mvids()?;
+    let new_mvids = new.rtr_header().manifest_assembly_mvids()?;
+
+    if !old_mvids.is_empty() && !new_mvids.is_empty() {
+        report.components = diff::match_components(&old_mvids, &new_mvids);
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        diff::print_report(&report, Style::from_flags(false, false));
     }
 
     Ok(())
 }
 
-fn get_types(pe: NativeAotBinary<'_>) -> Result<()> {
-    struct MethodDef<'a> {
-        method: Method<'a>,
-        parent: TypeDefinition<'a>,
+fn list_unnamed_tables(pe: NativeAotBinary<'_>, sample: usize) -> Result<()> {
+    let method_tables = pe.scan_method_tables()?;
+    let type_map = pe.type_map()?;
+    let constructed = pe
+        .constructed_generic_types(&type_map)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|typ| typ.method_table)
+        .collect::<HashSet<_>>();
+
+    let unnamed = method_tables
+        .iter()
+        .filter(|mt| !type_map.contains_key(&mt.view.va()) && !constructed.contains(&mt.view.va()))
+        .collect::<Vec<_>>();
+
+    println!(
+        "Named {}/{} MethodTables",
+        method_tables.len() - unnamed.len(),
+        method_tables.len()
+    );
+
+    let mut by_element_type = HashMap::<_, usize>::new();
+    for mt in &unnamed {
+        *by_element_type
+            .entry(format!("{:?}", mt.element_type))
+            .or_default() += 1;
     }
 
-    let Some(metadata) = pe.rtr_header().metadata() else {
-        eprintln!("Image is missing a metadata section");
-        return Ok(());
-    };
+    let mut by_element_type = by_element_type.into_iter().collect::<Vec<_>>();
+    by_element_type.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
 
-    let Some(invoke_table) = pe.rtr_header().blob_hashtable(ReflectionMapBlob::InvokeMap) else {
-        eprintln!("Image is missing an invoke table");
-        return Ok(());
-    };
+    println!("Unnamed by element type:");
+    for (element_type, count) in by_element_type {
+        println!("  {element_type}: {count}");
+    }
 
-    let Some(fixups) = pe.rtr_header().common_fixups_table() else {
-        eprintln!("Image is missing a common fixups table");
+    if sample == 0 || unnamed.is_empty() {
         return Ok(());
-    };
+    }
 
-    // Step 1.
-    // Find potential method pointers
-    let mut method_ptrs = HashMap::new();
+    println!("Sample:");
+    for mt in unnamed.iter().take(sample) {
+        println!(
+            "  {:#x} {:?}, Base: {:#x}, Hashcode: {:#010x}",
+            mt.view.va(),
+            mt.element_type,
+            mt.related_type_address,
+            mt.hashcode
+        );
+    }
 
-    for mut parser in invoke_table.enumerate_all()? {
-        let invoke_flags = parser.get_unsigned()?;
-        let meta_handle = BaseHandle::from_raw(parser.get_unsigned()?);
-        let _entry_type = parser.get_unsigned()?;
-        let fixup_idx = parser.get_unsigned()?;
+    Ok(())
+}
 
-        if (invoke_flags & 32) == 0 {
-            continue;
-        }
+fn create_metadata_tree(pe: NativeAotBinary<'_>) -> Result<()> {
+    if load_metadata(&pe).is_none() {
+        return Ok(());
+    }
 
-        let Ok(method_handle) = meta_handle.to_handle::<MethodHandle>() else {
-            continue;
-        };
+    // metadata.header().scope_definitions()
 
-        let Some(va) = fixups.get_va_from_index(fixup_idx) else {
-            continue;
-        };
+    Ok(())
+}
 
-        method_ptrs.insert(method_handle, va);
-    }
+/// The names of the methods a type declares more than once, which [`overloaded_method_name`] adds a suffix to
+fn overloaded_names(
+    typ: &TypeDefinition<'_>,
+    metadata: MetadataReader<'_>,
+) -> Result<HashSet<String>> {
+    let mut counts = HashMap::new();
 
-    for def in metadata
-        .header()
-        .scope_definitions()
+    for method in typ
+        .methods
         .iter()?
         .flatten()
         .flat_map(|hdl| hdl.to_data(metadata))
     {
-        let types = def.get_all_types()?;
-
-        for typ in types {
-            let type_name = typ.get_full_name_with_generics()?;
-
-            if !typ.base_type.is_nil() {
-                let base_name =
-                    get_type_name_from_handle(typ.base_type, ParentInfo::typ(&typ), metadata)?;
+        if let Ok(name) = method.name.to_data(metadata) {
+            *counts.entry(name.value).or_insert(0) += 1;
+        }
+    }
 
-                println!("{type_name} ({base_name})");
-            } else {
-                println!("{type_name}");
-            }
+    Ok(counts
+        .into_iter()
+        .filter(|&(_, count)| count > 1)
+        .map(|(name, _)| name)
+        .collect())
+}
 
-            // Print fields
-            if matches!(typ.fields.count(), Ok(n) if n > 0) {
-                let Ok(iter) = typ.fields.iter() else {
-                    continue;
-                };
+/// The name of a method, suffixed with its parameter types (e.g. `Method_Int32_String`) when the type declares other
+/// methods with the same name, so overloads don't end up with the same label
+fn overloaded_method_name(
+    method: &Method<'_>,
+    overloaded: &HashSet<String>,
+    metadata: MetadataReader<'_>,
+) -> Result<String> {
+    let name = method.name.to_data(metadata)?.value;
 
-                println!(" - Fields:");
-                for field in iter.flatten().flat_map(|hdl| hdl.to_data(metadata)) {
-                    let name = field.name.to_data(metadata)?.value;
-                    let signature = field.signature.to_data(metadata)?;
+    if !overloaded.contains(&name) {
+        return Ok(name);
+    }
 
-                    let type_name = get_type_name_from_handle(
-                        signature.type_handle,
-                        ParentInfo::typ(&typ),
-                        metadata,
-                    )
-                    .unwrap_or_else(|_| "Unknown TypeDefinition".to_string());
+    let signature = method.signature.to_data(metadata)?;
+    let mut suffix = String::new();
 
-                    println!("  * {name} ({type_name})");
-                }
-            }
+    // Generic methods can overload on their arity alone, e.g. `Get()` and `Get<T>()`
+    if signature.generic_parameter_count > 0 {
+        suffix.push_str(&format!("_{}", signature.generic_parameter_count));
+    }
 
-            // Print methods
-            if matches!(typ.methods.count(), Ok(n) if n > 0) {
-                let Ok(iter) = typ.methods.iter() else {
-                    continue;
-                };
+    for param in signature.parameters.iter()? {
+        let param_name = canonical_type_name(metadata, param?)?;
 
-                println!(" - Methods:");
-                for method in iter.flatten().flat_map(|hdl| hdl.to_data(metadata)) {
-                    let name = method.name.to_data(metadata)?.value;
-                    let flags = method.flags;
+        suffix.push('_');
+        suffix.push_str(&short_type_name(&param_name));
+    }
 
-                    let Ok(signature) = method.signature.to_data(metadata) else {
-                        continue;
-                    };
+    Ok(format!("{name}{suffix}"))
+}
 
-                    let generics = method.generic_parameters.iter().ok().and_then(|mut iter| {
-                        let names = iter
-                            .try_fold(Vec::new(), |mut acc, hdl| {
-                                let hdl = hdl?;
-                                let param = hdl.to_data(metadata)?;
-                                let name = param.name.to_data(metadata)?;
-                                acc.push(name.value);
-
-                                Ok::<_, anyhow::Error>(acc)
-                            })
-                            .ok()?;
-
-                        if names.is_empty() {
-                            return None;
-                        }
-
-                        Some(format!("<{}>", names.join(", ")))
-                    });
-
-                    let return_type = match signature.return_type {
-                        t if t.is_nil() => "void".to_string(),
-                        t => {
-                            get_type_name_from_handle(t, ParentInfo::both(&method, &typ), metadata)?
-                        }
-                    };
+/// Names a function for IDA as `Type.Method`, so it's a valid identifier once the dots are replaced
+///
+/// Backtick arities are stripped and generic parameters and arguments shortened, e.g. `List_T.Add` for the `Add` of
+/// ``System.Collections.Generic.List`1<T>``. The type arguments of a generic method instantiation follow the method
+/// name after a double underscore, like `Array.Empty__Int32`.
+fn ida_function_name(type_name: &str, method_name: &str, arguments: Option<&str>) -> String {
+    let (type_name, generics) = type_name.split_at(type_name.find('<').unwrap_or(type_name.len()));
+
+    let mut name = ida_identifier(type_name);
+    name.push_str(&short_type_name(generics));
+    name.push('.');
+    name.push_str(&ida_identifier(method_name));
+
+    if let Some(arguments) = arguments {
+        name.push('_');
+        name.push_str(&short_type_name(&format!("<{arguments}>")));
+    }
 
-                    print!("  * ");
+    name
+}
 
-                    let access = match flags.member_access() {
-                        MethodMemberAccess::Assembly => "internal ",
-                        MethodMemberAccess::FamAndAssem => "private protected ",
-                        MethodMemberAccess::FamOrAssem => "internal protected ",
-                        MethodMemberAccess::Family => "protected ",
-                        MethodMemberAccess::Private => "private ",
-                        MethodMemberAccess::PrivateScope => "",
-                        MethodMemberAccess::Public => "public ",
-                    };
+/// Strips backtick arities and replaces what IDA doesn't allow in names, keeping the separators `DumpIDA` replaces
+fn ida_identifier(name: &str) -> String {
+    let mut identifier = String::new();
+    let mut chars = name.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '`' => while chars.next_if(char::is_ascii_digit).is_some() {},
+            c if c.is_alphanumeric() || matches!(c, '_' | '.' | '|') => identifier.push(c),
+            // Nested types, and the angle brackets of compiler generated names like `<Main>b__0_0`
+            _ => identifier.push('_'),
+        }
+    }
 
-                    print!(
-                        "{access}{return_type} {name}{}(",
-                        generics.as_deref().unwrap_or("")
-                    );
+    identifier
+}
 
-                    if let Ok(iter) = signature.parameters.iter() {
-                        let params = iter
-                            .flatten()
-                            .map(|param| {
-                                // Turn this BaseHandle into a readable string
-                                get_type_name_from_handle(
-                                    param,
-                                    ParentInfo::both(&method, &typ),
-                                    metadata,
-                                )
-                                .unwrap_or_else(|_| "<unknown>".to_string())
-                            })
-                            .collect::<Vec<_>>()
-                            .join(", ");
-
-                        print!("{params}");
+/// Shortens a canonical type name into an identifier, e.g. `System.Collections.Generic.List<System.Int32>[]` into
+/// `List_Int32_Array`
+fn short_type_name(canonical: &str) -> String {
+    let mut short = String::new();
+    let mut segment = String::new();
+    let mut chars = canonical.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            // Drop the namespaces and declaring types
+            '.' | '+' => segment.clear(),
+            // Drop the generic arity, the arguments follow anyway
+            '`' => while chars.next_if(char::is_ascii_digit).is_some() {},
+            c if c.is_alphanumeric() || c == '_' => segment.push(c),
+            c => {
+                short.push_str(&segment);
+                segment.clear();
+
+                match c {
+                    '&' => short.push_str("_Ref"),
+                    '*' => short.push_str("_Ptr"),
+                    // Multidimensional arrays list a comma per extra dimension
+                    '[' => {
+                        while chars.next_if(|&c| c != ']').is_some() {}
+                        short.push_str("_Array");
                     }
-
-                    print!(") //");
-
-                    if let Some(&va) = method_ptrs.get(&method.handle()) {
-                        if let Ok(rva) = pe.pe().va_to_rva(va) {
-                            print!(" RVA: {rva:#x}");
-                        } else {
-                            print!(" VA: {va:#x}");
-                        }
+                    // Generic parameters, `!0` for the type and `!!0` for the method
+                    '!' => {
+                        chars.next_if_eq(&'!');
+                        short.push('T');
                     }
-
-                    print!(" Conv: {:?}", signature.calling_convention);
-                    println!();
+                    '<' | ',' if !short.ends_with('_') => short.push('_'),
+                    _ => {}
                 }
             }
         }
     }
 
-    Ok(())
+    short.push_str(&segment);
+    short
 }
 
-fn create_metadata_tree(pe: NativeAotBinary<'_>) -> Result<()> {
-    let Some(_metadata) = pe.rtr_header().metadata() else {
-        eprintln!("Image is missing a metadata section");
-        return Ok(());
+/// Names an array, pointer or byref MethodTable after its element type, like `Int32[]` or `Int32*`
+fn parameterized_type_name(mt: &MethodTable<'_>, names: &HashMap<Va, String>) -> Option<String> {
+    if !mt.element_type.is_parameterized() {
+        return None;
+    }
+
+    // Primitives are named by their element type, which doesn't need their MethodTable to be matched
+    let element_name = mt
+        .parameterized_element()
+        .and_then(|element| {
+            names
+                .get(&element.view.va())
+                .cloned()
+                .or_else(|| parameterized_type_name(&element, names))
+        })
+        .or_else(|| mt.primitive_element()?.primitive_name())?;
+
+    let suffix = match mt.element_type {
+        ElementType::SzArray => "[]".to_string(),
+        // Multi-dimensional arrays store the bounds and lower bounds of every dimension after the length
+        ElementType::Array => {
+            let rank = (mt.base_size.saturating_sub(0x18) / 8).max(1);
+            format!("[{}]", ",".repeat(rank as usize - 1))
+        }
+        ElementType::Pointer => "*".to_string(),
+        ElementType::ByRef => "&".to_string(),
+        _ => return None,
     };
 
-    // metadata.header().scope_definitions()
+    Some(format!("{element_name}{suffix}"))
+}
 
-    Ok(())
+/// Why [`build_ida_definition`] refused a binary, which batches skip instead of counting as a failure
+#[derive(Debug, thiserror::Error)]
+enum NotHytaleBinary {
+    #[error("Image is missing a metadata section")]
+    NoMetadata,
+    #[error(
+        "Assembly '{0}' is missing from target binary. Target binary might not be the Hytale Client."
+    )]
+    MissingAssembly(&'static str),
 }
 
-fn dump_ida(pe: NativeAotBinary<'_>) -> Result<()> {
+/// Builds the IDA definition of a Hytale binary, failing with [`NotHytaleBinary`] when it isn't the Hytale client
+fn build_ida_definition(
+    pe: &NativeAotBinary<'_>,
+    detailed_vtables: bool,
+    filter: TypeFilter<'_>,
+) -> Result<ida::HytaleDefinition> {
+    let TypeFilter {
+        assembly,
+        generated,
+    } = filter;
+
     // -- Check if this is a Hytale binary
     const REQUIRED_ASSEMBLIES: &[&str] = &[
         "Hytale.Nat",
@@ -303,9 +2819,8 @@ fn dump_ida(pe: NativeAotBinary<'_>) -> Result<()> {
         "HytaleClient.Interop",
     ];
 
-    let Some(metadata) = pe.rtr_header().metadata() else {
-        eprintln!("Image is missing a metadata section");
-        return Ok(());
+    let Some(metadata) = pe.rtr_header().metadata_result()? else {
+        return Err(NotHytaleBinary::NoMetadata.into());
     };
 
     let Ok(scopes) = metadata.header().scope_definitions().iter().map(|iter| {
@@ -315,42 +2830,89 @@ fn dump_ida(pe: NativeAotBinary<'_>) -> Result<()> {
             .map(|name| name.value)
             .collect::<Vec<_>>()
     }) else {
-        eprintln!("Unable to enumerate scope definitions");
-        return Ok(());
+        bail!("Unable to enumerate scope definitions");
     };
 
     for assembly in REQUIRED_ASSEMBLIES {
         if !scopes.iter().any(|scope| scope == assembly) {
-            eprintln!(
-                "Assembly '{assembly}' is missing from target binary. Target binary might not be the Hytale Client."
-            );
-            return Ok(());
+            return Err(NotHytaleBinary::MissingAssembly(assembly).into());
         }
     }
 
     // -- At this point we can be certain that the target binary is the Hytale client
 
+    // With an assembly given, only the types of its scope are defined
+    let scope = match assembly {
+        Some(assembly) => select_scopes(metadata, Some(assembly))?
+            .first()
+            .map(|scope| scope.handle()),
+        None => None,
+    };
+    // Types without a definition (e.g. constructed generics) can't be checked, and are only kept without filters
+    let include = |typ: Option<&TypeDefinition<'_>>| match typ {
+        Some(typ) => {
+            scope.is_none_or(|scope| {
+                typ.owning_scope()
+                    .is_ok_and(|owner| owner.handle() == scope)
+            }) && generated.includes(typ).unwrap_or(true)
+        }
+        None => scope.is_none() && generated != GeneratedTypes::Only,
+    };
+
     // Grab a few references we're going to need later
     let Some(fixups) = pe.rtr_header().common_fixups_table() else {
-        eprintln!("Missing CommonFixupsTable");
-        return Ok(());
+        bail!("Missing CommonFixupsTable");
     };
+    // Types whose fixup points outside of the image are left out, tell whether that's a few entries or the whole table
+    let bad_fixups = fixups.validate();
+    if let Some(&(index, va)) = bad_fixups.first() {
+        warn!(
+            "{}/{} CommonFixupsTable entries point outside of the image, e.g. entry {index} to {va:#x}",
+            bad_fixups.len(),
+            fixups.len()
+        );
+    }
     let Some(type_map) = pe.rtr_header().blob_hashtable(ReflectionMapBlob::TypeMap) else {
-        eprintln!("Missing TypeMap");
-        return Ok(());
-    };
-    let Some(invoke_map) = pe.rtr_header().blob_hashtable(ReflectionMapBlob::InvokeMap) else {
-        eprintln!("Missing InvokeMap");
-        return Ok(());
+        bail!("Missing TypeMap");
     };
+    let invoke_map = pe.invoke_map()?;
 
     // Get a list of method tables
     let method_tables = pe.scan_method_tables()?;
+    let type_handles = pe.type_map()?;
+
+    // Constructed generic types aren't in the TypeMap, and are named after their definition and type arguments instead
+    let generic_names = match pe.constructed_generic_types(&type_handles) {
+        Ok(types) => get_constructed_type_names(&types, &type_handles, metadata),
+        Err(why) => {
+            warn!("Unable to name constructed generic types: {why}");
+            HashMap::new()
+        }
+    };
+
+    // Newer images can name types in the ExternalTypeMap that are missing from the TypeMap
+    let external_names = match pe.external_type_map() {
+        Ok(groups) => groups
+            .into_iter()
+            .flat_map(|group| group.types)
+            .map(|(name, va)| (va, name))
+            .collect::<HashMap<_, _>>(),
+        Err(why) => {
+            warn!("Unable to read the ExternalTypeMap: {why}");
+            HashMap::new()
+        }
+    };
 
     let mut definition = ida::HytaleDefinition::default();
 
-    // Resolve method table names and define them
+    // Sanity check for the type hashing, every MethodTable in the TypeMap stores the hashcode of its definition
+    let (mut hashed, mut hashes_matched) = (0, 0);
+    let mut named_tables = 0;
+
+    // Resolve method table names
+    let mut table_names = Vec::new();
     for mt in &method_tables {
+        let mut declaring_type = None;
         let name = if let Ok(iter) = type_map.lookup(mt.hashcode as i32) {
             let mut name = None;
 
@@ -369,7 +2931,14 @@ fn dump_ida(pe: NativeAotBinary<'_>) -> Result<()> {
                         continue;
                     };
 
-                    name = Some(format!("{}_vtbl", type_def.get_full_name_with_generics()?));
+                    hashed += 1;
+                    if type_def.runtime_hashcode()? == mt.hashcode as i32 {
+                        hashes_matched += 1;
+                    }
+
+                    declaring_type = Some(type_def.clone());
+
+                    name = Some(type_def.get_full_name_with_generics()?);
                     break;
                 }
             }
@@ -379,7 +2948,44 @@ fn dump_ida(pe: NativeAotBinary<'_>) -> Result<()> {
             None
         };
 
-        let name = name.unwrap_or_else(|| format!("{:?}_{:x}_vtbl", mt.element_type, mt.view.va()));
+        if !include(declaring_type.as_ref()) {
+            continue;
+        }
+
+        let name = name
+            .or_else(|| {
+                generic_names
+                    .get(&mt.view.va())
+                    .or_else(|| external_names.get(&mt.view.va()))
+                    .cloned()
+            })
+            .or_else(|| mt.is_canonical().then(|| CANONICAL_TYPE_NAME.to_owned()));
+
+        table_names.push((mt, name));
+    }
+
+    // Arrays, pointers and byrefs are named after their element type, which can only be done once those are named
+    let names_by_va = table_names
+        .iter()
+        .filter_map(|(mt, name)| Some((mt.view.va(), name.clone()?)))
+        .collect::<HashMap<_, _>>();
+
+    // Define the method tables, the names are kept for the functions that are declared on constructed types
+    let mut constructed_names = HashMap::new();
+    for (mt, name) in table_names {
+        let name = name
+            .or_else(|| parameterized_type_name(mt, &names_by_va))
+            .or_else(|| mt.generic_instance_name(&names_by_va));
+
+        if let Some(name) = &name {
+            named_tables += 1;
+            constructed_names.insert(mt.view.va(), (mt, name.clone()));
+        }
+
+        let name = name.map_or_else(
+            || format!("{:?}_{:x}_vtbl", mt.element_type, mt.view.va()),
+            |name| format!("{name}_vtbl"),
+        );
 
         definition.create_mt_struct(
             mt.view.va(),
@@ -389,238 +2995,497 @@ fn dump_ida(pe: NativeAotBinary<'_>) -> Result<()> {
         );
     }
 
+    info!(
+        "Named {named_tables}/{} MethodTables, see ListUnnamedTables for the rest",
+        method_tables.len()
+    );
+    debug!("Computed hashcodes of {hashes_matched}/{hashed} named MethodTables match");
+
     // Resolve function names + pointers and define them
-    for mut parser in invoke_map.enumerate_all()? {
-        let flags = parser.get_unsigned()?;
-        let handle =
-            BaseHandle::from_raw(((HandleType::Method as u32) << 25) | parser.get_unsigned()?);
-        let method_handle = handle.to_handle::<MethodHandle>()?;
+    let mut named_functions = HashSet::new();
+    let mut overload_groups = HashMap::new();
 
-        let Ok(method_def) = method_handle.to_data(metadata) else {
-            continue;
-        };
+    for entry in invoke_map.entries()? {
+        let entry = entry?;
 
-        let Some(entry_type_mt) = fixups
-            .get_va_from_index(parser.get_unsigned()?)
-            .and_then(|mt_va| method_tables.iter().find(|mt| mt.view.va() == mt_va))
-        else {
+        let (Some(method_handle), Some(entrypoint_va)) = (entry.method, entry.entrypoint) else {
             continue;
         };
 
-        // Skip if no entrypoint
-        if flags & 32 == 0 {
+        let Ok(method_def) = method_handle.to_data(metadata) else {
             continue;
-        }
+        };
 
         // Find type name
-        let Ok(iter) = type_map.lookup(entry_type_mt.hashcode as i32) else {
+        let Some(declaring_va) = entry.declaring_type else {
             continue;
         };
 
-        let mut name = None;
-        for mut parser in iter {
-            let index = parser.get_unsigned()?;
-            let Some(va) = fixups.get_va_from_index(index) else {
-                continue;
-            };
+        let (type_def, type_name) = match type_handles.get(&declaring_va) {
+            Some(hdl) => {
+                let Ok(type_def) = hdl.to_data(metadata) else {
+                    continue;
+                };
+                let type_name = type_def.get_full_name_with_generics()?;
 
-            if va == entry_type_mt.view.va() {
-                let handle = BaseHandle::from_raw(parser.get_unsigned()?);
-                let Ok(type_def) = handle
-                    .to_handle::<TypeDefinitionHandle>()
-                    .and_then(|hdl| hdl.to_data(metadata))
+                (type_def, type_name)
+            }
+            // Code shared by the instantiations of a generic type is declared on its canonical form, e.g. List<__Canon>
+            None => {
+                let Some((mt, type_name)) = constructed_names.get(&declaring_va) else {
+                    continue;
+                };
+                let Some(type_def) = mt
+                    .generic_definition()
+                    .and_then(|definition| type_handles.get(&definition))
+                    .and_then(|hdl| hdl.to_data(metadata).ok())
                 else {
                     continue;
                 };
 
-                name = Some(type_def.get_full_name_with_generics()?);
-                break;
+                (type_def, type_name.clone())
             }
-        }
+        };
 
-        let Some(type_name) = name else {
+        if !include(Some(&type_def)) {
             continue;
+        }
+
+        // The overloads of a type are only looked for once, not for every one of its methods
+        let overloaded = match overload_groups.entry(type_def.handle()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(overloaded_names(&type_def, metadata)?),
         };
+        let name = overloaded_method_name(&method_def, overloaded, metadata)?;
+        let arguments = entry.is_generic_instantiation().then(|| {
+            get_type_names_from_mts(pe, &entry.generic_arguments, &type_handles, metadata)
+        });
+
+        definition.create_function(
+            entrypoint_va,
+            ida_function_name(&type_name, &name, arguments.as_deref()),
+        );
+        named_functions.insert(entrypoint_va);
+    }
+
+    // Interop thunks are named after the struct or delegate they marshal
+    for stub in pe
+        .struct_marshalling_stubs()?
+        .into_iter()
+        .chain(pe.delegate_marshalling_stubs()?)
+    {
+        if named_functions.contains(&stub.address) {
+            continue;
+        }
 
-        let Some(entrypoint_va) = fixups.get_va_from_index(parser.get_unsigned()?) else {
+        let Some(type_def) = type_handles
+            .get(&stub.declaring_type)
+            .and_then(|hdl| hdl.to_data(metadata).ok())
+            .filter(|typ| include(Some(typ)))
+        else {
             continue;
         };
 
-        let name = method_def.name.to_data(metadata)?.value;
+        definition.create_function(
+            stub.address,
+            format!(
+                "{}_{}",
+                type_def.get_full_name_with_generics()?,
+                stub.kind.suffix()
+            ),
+        );
+        named_functions.insert(stub.address);
+    }
+
+    // An anchor into the runtime's bookkeeping of the module, every MethodTable points to it
+    if let Some(type_manager) = pe.type_manager() {
+        definition.create_global(type_manager.indirection, TYPE_MANAGER_INDIRECTION_NAME);
+    }
+
+    if detailed_vtables {
+        let vtables = method_tables
+            .iter()
+            .map(|mt| (mt.view.va(), &*mt.vtable_addresses))
+            .collect::<HashMap<_, _>>();
 
-        definition.create_function(entrypoint_va, format!("{type_name}.{name}"));
+        definition.name_vtable_slots(&vtables);
     }
 
+    Ok(definition)
+}
+
+/// Writes the definition to `output`, or only reports its size for a dry run without one
+fn dump_ida(
+    pe: NativeAotBinary<'_>,
+    detailed_vtables: bool,
+    interned: bool,
+    filter: TypeFilter<'_>,
+    output: Option<&Path>,
+) -> Result<()> {
+    let definition = build_ida_definition(&pe, detailed_vtables, filter)?;
+
+    let Some(output) = output else {
+        println!(
+            "Would define {} MethodTable structs and {} functions",
+            definition.mt_struct_count(),
+            definition.function_count()
+        );
+
+        return Ok(());
+    };
+
     // Write definition to disk
-    std::fs::write("hytale_def.json", serde_json::to_string(&definition)?)?;
+    std::fs::write(output, definition.to_json(interned)?)?;
 
-    eprintln!("Definition written to 'hytale_def.json'");
+    info!(
+        "Definition of {} MethodTable structs and {} functions written to '{}'",
+        definition.mt_struct_count(),
+        definition.function_count(),
+        output.display()
+    );
 
     Ok(())
 }
 
-#[derive(Clone, Copy)]
-struct ParentInfo<'a> {
-    method: Option<&'a Method<'a>>,
-    typ: Option<&'a TypeDefinition<'a>>,
-}
+fn dump_ghidra(pe: NativeAotBinary<'_>, assembly: Option<&str>, output: &Path) -> Result<()> {
+    let Some(metadata) = load_metadata(&pe) else {
+        return Ok(());
+    };
 
-impl<'a> ParentInfo<'a> {
-    fn none() -> Self {
-        Self {
-            method: None,
-            typ: None,
+    let mut definition = ghidra::GhidraDefinition::default();
+
+    for scope in select_scopes(metadata, assembly)? {
+        for typ in scope.get_all_types()? {
+            // Auto layout leaves the size and field order to the runtime, which the metadata doesn't record
+            let Some(layout) = typ.declared_layout() else {
+                continue;
+            };
+
+            if !typ.is_value_type() {
+                continue;
+            }
+
+            let fields = model::build_field_models(metadata, &typ)?;
+            definition.create_struct(typ.get_full_name()?, layout, &fields);
         }
     }
 
-    fn typ(typ: &'a TypeDefinition<'a>) -> Self {
-        Self {
-            method: None,
-            typ: Some(typ),
+    std::fs::write(output, definition.to_json()?)?;
+
+    info!(
+        "Definition of {} structs written to '{}'",
+        definition.struct_count(),
+        output.display()
+    );
+
+    Ok(())
+}
+
+/// Runs [`dump_ida`] for every executable in `dir`, writing `<name>.json` files to `out_dir`
+fn dump_ida_batch(
+    dir: &Path,
+    out_dir: &Path,
+    interned: bool,
+    scan_sections: ScanSections,
+    strict_strings: bool,
+) -> Result<()> {
+    let mut files = std::fs::read_dir(dir)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+
+    files.retain(|path| {
+        path.is_file()
+            && path
+                .extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("exe"))
+    });
+    files.sort();
+
+    std::fs::create_dir_all(out_dir)?;
+
+    let dump = |path: &Path| -> Result<()> {
+        let data = std::fs::read(path)?;
+        let pe = NativeAotBinary::load_pe(PeFile::from_bytes(&data)?, scan_sections.clone())?
+            .with_strict_strings(strict_strings);
+
+        let definition = build_ida_definition(&pe, false, TypeFilter::default())?;
+
+        let name = path.file_stem().unwrap_or(path.as_os_str());
+        let output = out_dir.join(name).with_extension("json");
+        std::fs::write(&output, definition.to_json(interned)?)?;
+
+        info!(
+            "Definition of '{}' written to '{}'",
+            path.display(),
+            output.display()
+        );
+
+        Ok(())
+    };
+
+    // Every binary is parsed independently, so the files are simply handed out to a thread per core
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(files.len()));
+    let workers = std::thread::available_parallelism().map_or(1, |count| count.get());
+
+    std::thread::scope(|scope| {
+        for _ in 0..workers.min(files.len()) {
+            scope.spawn(|| {
+                while let Some(path) = files.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let result = dump(path);
+                    results.lock().unwrap().push((path, result));
+                }
+            });
         }
-    }
+    });
 
-    fn both(method: &'a Method<'a>, typ: &'a TypeDefinition<'a>) -> Self {
-        Self {
-            method: Some(method),
-            typ: Some(typ),
+    let (mut written, mut skipped, mut failed) = (0, 0, 0);
+
+    for (path, result) in results.into_inner().unwrap() {
+        match result {
+            Ok(()) => written += 1,
+            Err(why) if why.is::<NotHytaleBinary>() => {
+                warn!("Skipped '{}': {why}", path.display());
+                skipped += 1;
+            }
+            Err(why) => {
+                error!("Failed to dump '{}': {why}", path.display());
+                failed += 1;
+            }
         }
     }
 
-    fn has_none(&self) -> bool {
-        self.method.is_none() && self.typ.is_none()
-    }
+    info!(
+        "Processed {} binaries: {written} written, {skipped} skipped, {failed} failed",
+        files.len()
+    );
+
+    Ok(())
+}
+
+/// Maps every reflectable method with an entrypoint to its address using the InvokeMap
+fn get_method_pointers(pe: &NativeAotBinary<'_>) -> Result<HashMap<MethodHandle, Va>> {
+    let method_ptrs = pe.invoke_map()?.method_pointers()?;
+    debug!("InvokeMap has {} method pointers", method_ptrs.len());
+
+    Ok(method_ptrs)
+}
+
+enum MethodPointers<'a> {
+    Prebuilt(HashMap<MethodHandle, Va>),
+
+    /// Only keeps the MethodTable hashcode of every type, the InvokeMap is searched again for every method
+    OnDemand {
+        invoke_map: InvokeMap<'a>,
+        type_hashcodes: HashMap<TypeDefinitionHandle, u32>,
+    },
+}
+
+impl<'a> MethodPointers<'a> {
+    fn on_demand(pe: &NativeAotBinary<'a>) -> Result<Self> {
+        // The InvokeMap is keyed by the hashcode of the declaring type's MethodTable
+        let mut type_hashcodes = HashMap::new();
 
-    fn has_method(&self) -> bool {
-        self.method.is_some()
+        for (mt_va, handle) in pe.type_map()? {
+            let Ok(mt) = MethodTable::parse(&mut View::new(pe.pe(), mt_va)) else {
+                continue;
+            };
+
+            type_hashcodes.insert(handle, mt.hashcode);
+        }
+
+        Ok(Self::OnDemand {
+            invoke_map: pe.invoke_map()?,
+            type_hashcodes,
+        })
     }
 
-    fn has_type(&self) -> bool {
-        self.typ.is_some()
+    fn get(&self, method: MethodHandle, declaring_type: TypeDefinitionHandle) -> Option<Va> {
+        match self {
+            Self::Prebuilt(method_ptrs) => method_ptrs.get(&method).copied(),
+            Self::OnDemand {
+                invoke_map,
+                type_hashcodes,
+            } => invoke_map
+                .lookup(*type_hashcodes.get(&declaring_type)?, method)
+                .ok()
+                .flatten(),
+        }
     }
+}
 
-    fn get_method_generic(&self, reader: MetadataReader<'a>, index: usize) -> Option<String> {
-        Some(
-            self.method?
-                .generic_parameters
-                .iter()
-                .ok()?
-                .collect::<Vec<_>>()
-                .get(index)?
-                .as_ref()
-                .ok()?
-                .to_data(reader)
-                .ok()?
-                .name
-                .to_data(reader)
-                .ok()?
-                .value,
-        )
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum AddressFormat {
+    /// RVAs, falling back to VAs for addresses outside of the image
+    Rva,
+    FileOffset,
+}
+
+fn format_address(pe: &NativeAotBinary<'_>, va: Va, style: Style, format: AddressFormat) -> String {
+    if format == AddressFormat::FileOffset {
+        return match pe.va_to_file_offset(va) {
+            Some(offset) => format!(" File offset: {}", style.address(&format!("{offset:#x}"))),
+            None => format!(" {}", style.address("<no file offset>")),
+        };
     }
 
-    fn get_type_generic(&self, reader: MetadataReader<'a>, index: usize) -> Option<String> {
-        Some(
-            self.typ?
-                .generic_parameters
-                .iter()
-                .ok()?
-                .collect::<Vec<_>>()
-                .get(index)?
-                .as_ref()
-                .ok()?
-                .to_data(reader)
-                .ok()?
-                .name
-                .to_data(reader)
-                .ok()?
-                .value,
-        )
+    if let Ok(rva) = pe.pe().va_to_rva(va) {
+        format!(" RVA: {}", style.address(&format!("{rva:#x}")))
+    } else {
+        format!(" VA: {}", style.address(&format!("{va:#x}")))
     }
 }
 
-fn get_type_name_from_handle(
-    handle: BaseHandle,
-    parent: ParentInfo,
+/// Names constructed generic types, e.g. `System.Collections.Generic.List<System.Int32>`
+///
+/// Type arguments can be constructed generic types themselves, so names are resolved until no more progress is made.
+fn get_constructed_type_names(
+    types: &[ConstructedGenericType],
+    type_handles: &HashMap<Va, TypeDefinitionHandle>,
     reader: MetadataReader<'_>,
-) -> Result<String> {
-    let value = match handle.handle_type() {
-        Some(HandleType::TypeDefinition) => {
-            let typedef = handle
-                .to_handle::<TypeDefinitionHandle>()?
-                .to_data(reader)?;
-
-            format!(
-                "{}",
-                if parent.has_none() {
-                    typedef.get_full_name_with_generics()
-                } else {
-                    typedef.get_full_name()
-                }?
-            )
-        }
-        Some(HandleType::TypeSpecification) => {
-            let typespec = handle
-                .to_handle::<TypeSpecificationHandle>()?
-                .to_data(reader)?;
+) -> HashMap<Va, String> {
+    let mut names = type_handles
+        .iter()
+        .filter_map(|(&va, hdl)| Some((va, hdl.to_data(reader).ok()?.get_full_name().ok()?)))
+        .collect::<HashMap<_, _>>();
 
-            get_type_name_from_handle(typespec.signature, parent, reader)?
-        }
-        // Generic type
-        Some(HandleType::TypeInstantiationSignature) => {
-            let typeinst = handle
-                .to_handle::<TypeInstantiationSignatureHandle>()?
-                .to_data(reader)?;
-
-            let generic_type_name =
-                get_type_name_from_handle(typeinst.generic_type, parent, reader)?;
-            let mut generic_type_args = vec![];
+    loop {
+        let mut progress = false;
 
-            for typ in typeinst.generic_args.iter()?.flatten() {
-                generic_type_args.push(get_type_name_from_handle(typ, parent, reader)?);
+        for typ in types {
+            if names.contains_key(&typ.method_table) {
+                continue;
             }
 
-            format!("{generic_type_name}<{}>", generic_type_args.join(", "))
-        }
-        // ref Type
-        Some(HandleType::ByReferenceSignature) => {
-            let refsig = handle
-                .to_handle::<ByReferenceSignatureHandle>()?
-                .to_data(reader)?;
+            let Some(args) = typ
+                .generic_arguments
+                .iter()
+                .map(|va| names.get(va).cloned())
+                .collect::<Option<Vec<_>>>()
+            else {
+                continue;
+            };
 
-            let name = get_type_name_from_handle(refsig.type_handle, parent, reader)?;
+            let Ok(definition) = typ
+                .definition
+                .to_data(reader)
+                .and_then(|def| def.get_full_name())
+            else {
+                continue;
+            };
 
-            format!("ref {name}")
-        }
-        Some(HandleType::MethodTypeVariableSignature) if parent.has_method() => {
-            let mtvarsig = handle
-                .to_handle::<MethodTypeVariableSignatureHandle>()?
-                .to_data(reader)?;
-            let index = mtvarsig.number as usize;
+            // Strip the arity, e.g. List`1
+            let definition = definition
+                .split_once('`')
+                .map_or(definition.as_str(), |(name, _)| name);
 
-            format!(
-                "{}",
-                parent
-                    .get_method_generic(reader, index)
-                    .as_deref()
-                    .unwrap_or("Unknown")
-            )
+            names.insert(
+                typ.method_table,
+                format!("{definition}<{}>", args.join(", ")),
+            );
+            progress = true;
         }
-        Some(HandleType::TypeVariableSignature) if parent.has_type() => {
-            let mtvarsig = handle
-                .to_handle::<TypeVariableSignatureHandle>()?
-                .to_data(reader)?;
-            let index = mtvarsig.number as usize;
 
-            format!(
-                "{}",
-                parent
-                    .get_type_generic(reader, index)
-                    .as_deref()
-                    .unwrap_or("Unknown")
-            )
+        if !progress {
+            break;
         }
-        _ => format!("{:?}", handle.handle_type().unwrap_or(HandleType::Null)),
+    }
+
+    types
+        .iter()
+        .filter_map(|typ| Some((typ.method_table, names.get(&typ.method_table)?.clone())))
+        .collect()
+}
+
+/// Names the types of the given MethodTables, e.g. the type arguments of a generic instantiation
+///
+/// Arguments of shared code are named `__Canon`, as that code is used by every reference type.
+fn get_type_names_from_mts(
+    pe: &NativeAotBinary<'_>,
+    mts: &[Va],
+    type_handles: &HashMap<Va, TypeDefinitionHandle>,
+    reader: MetadataReader<'_>,
+) -> String {
+    mts.iter()
+        .map(|&va| {
+            type_handles
+                .get(&va)
+                .and_then(|hdl| hdl.to_data(reader).ok())
+                .and_then(|typ| typ.get_full_name().ok())
+                .or_else(|| {
+                    pe.is_canonical_type(va)
+                        .then(|| CANONICAL_TYPE_NAME.to_owned())
+                })
+                .unwrap_or_else(|| format!("{va:#x}"))
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The native calling convention of P/Invokes and unmanaged signatures, otherwise the managed calling convention
+fn calling_convention_name(method: &Method<'_>, signature: &MethodSignature<'_>) -> String {
+    if let Ok(Some(import)) = method.pinvoke_import() {
+        // `[DllImport]` defaults to the platform convention
+        return import
+            .calling_convention
+            .unwrap_or(UnmanagedCallingConvention::Platform)
+            .to_string();
+    }
+
+    match signature.calling_convention.unmanaged() {
+        Some(convention) => convention.to_string(),
+        None => signature.calling_convention.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pelite::pe64::PeFile;
+
+    use crate::{
+        TypeFilter,
+        binary::{NativeAotBinary, ScanSections},
+        build_ida_definition, ida_function_name,
+        test_utils::ida_fixture_image,
     };
 
-    Ok(value)
+    #[test]
+    fn ida_definition_matches_snapshot() {
+        let image = ida_fixture_image();
+        let pe = PeFile::from_bytes(&image).unwrap();
+        let binary = NativeAotBinary::load_pe(pe, ScanSections::default()).unwrap();
+
+        let definition = build_ida_definition(&binary, true, TypeFilter::default()).unwrap();
+        let json = serde_json::to_string_pretty(&definition).unwrap();
+
+        // The import scripts rely on these names and on the order of the fields, only update it on purpose
+        assert_eq!(
+            json,
+            include_str!("ida/snapshots/fixture_definition.json").trim_end()
+        );
+    }
+
+    #[test]
+    fn ida_function_names_are_identifiers() {
+        assert_eq!(
+            ida_function_name("System.Collections.Generic.List`1<T>", "Add", None),
+            "System.Collections.Generic.List_T.Add"
+        );
+        assert_eq!(
+            ida_function_name("System.Array", "Empty", Some("System.Int32")),
+            "System.Array.Empty__Int32"
+        );
+        assert_eq!(
+            ida_function_name("Game.Player", "<Jump>b__0_0", None),
+            "Game.Player._Jump_b__0_0"
+        );
+        assert_eq!(
+            ida_function_name(
+                "System.Collections.Generic.Dictionary<System.String, System.Int32>",
+                "TryGetValue_TValue_Ref",
+                None
+            ),
+            "System.Collections.Generic.Dictionary_String_Int32.TryGetValue_TValue_Ref"
+        );
+    }
 }
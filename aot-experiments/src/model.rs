@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use pelite::pe64::{Pe, Va};
+use serde::Serialize;
+
+use crate::{
+    binary::NativeAotBinary,
+    embedded_meta::{
+        MetadataReader, Method, TypeDefinition,
+        handles::{Handle, MethodHandle},
+    },
+    naming::{ParentInfo, get_type_name_from_handle, member_access_name},
+};
+
+/// Owned, printable description of a type, independent of the binary it was read from
+#[derive(Serialize, Clone, Debug)]
+pub struct TypeModel {
+    pub name: String,
+    /// The raw `BaseHandle` of the type, only filled in for `--with-tokens`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<u32>,
+    pub base_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub packing_size: Option<u16>,
+    pub fields: Vec<FieldModel>,
+    pub methods: Vec<MethodModel>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct FieldModel {
+    pub name: String,
+    pub type_name: String,
+    pub is_static: bool,
+    pub offset: u32,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct MethodModel {
+    pub name: String,
+    /// The raw `BaseHandle` of the method, only filled in for `--with-tokens`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<u32>,
+    pub access: String,
+    pub return_type: String,
+    pub generic_parameters: Vec<String>,
+    pub parameters: Vec<String>,
+    pub rva: Option<u32>,
+}
+
+impl MethodModel {
+    /// The name together with its signature, used to tell overloads apart
+    pub fn signature(&self) -> String {
+        let generics = if self.generic_parameters.is_empty() {
+            String::new()
+        } else {
+            format!("<{}>", self.generic_parameters.join(", "))
+        };
+
+        format!(
+            "{} {}{generics}({})",
+            self.return_type,
+            self.name,
+            self.parameters.join(", ")
+        )
+    }
+}
+
+/// Builds the models of every type in the image, ordered by name so dumps are deterministic
+pub fn build_type_models(
+    pe: &NativeAotBinary<'_>,
+    strict_signatures: bool,
+) -> Result<Vec<TypeModel>> {
+    let Some(metadata) = pe.rtr_header().metadata_result()? else {
+        anyhow::bail!("Image is missing a metadata section");
+    };
+
+    let method_ptrs = pe.invoke_map()?.method_pointers()?;
+    let mut models = Vec::new();
+
+    for def in metadata
+        .header()
+        .scope_definitions()
+        .iter()?
+        .flatten()
+        .flat_map(|hdl| hdl.to_data(metadata))
+    {
+        for typ in def.get_all_types()? {
+            models.push(build_type_model(
+                pe,
+                metadata,
+                &typ,
+                &method_ptrs,
+                strict_signatures,
+                false,
+            )?);
+        }
+    }
+
+    models.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(models)
+}
+
+/// Builds the model of a single type, for callers that pick the types themselves
+///
+/// With `strict_signatures`, names fail on handle types the namer doesn't model. With `with_tokens`, the type and its
+/// methods carry their handles.
+pub fn build_type_model(
+    pe: &NativeAotBinary<'_>,
+    metadata: MetadataReader<'_>,
+    typ: &TypeDefinition<'_>,
+    method_ptrs: &HashMap<MethodHandle, Va>,
+    strict_signatures: bool,
+    with_tokens: bool,
+) -> Result<TypeModel> {
+    let base_type = if typ.base_type.is_nil() {
+        None
+    } else {
+        Some(get_type_name_from_handle(
+            typ.base_type,
+            ParentInfo::typ(typ).strict(strict_signatures),
+            metadata,
+        )?)
+    };
+
+    let fields = build_field_models(metadata, typ)?;
+
+    let mut methods = Vec::new();
+    if let Ok(iter) = typ.methods.iter() {
+        for method in iter.flatten().flat_map(|hdl| hdl.to_data(metadata)) {
+            if let Some(model) = build_method_model(
+                pe,
+                metadata,
+                typ,
+                &method,
+                method_ptrs,
+                strict_signatures,
+                with_tokens,
+            )? {
+                methods.push(model);
+            }
+        }
+    }
+
+    let layout = typ.declared_layout();
+
+    Ok(TypeModel {
+        name: typ.get_full_name_with_generics()?,
+        token: with_tokens.then(|| typ.handle().to_value()),
+        base_type,
+        size: layout.and_then(|layout| layout.size),
+        packing_size: layout.and_then(|layout| layout.packing_size),
+        fields,
+        methods,
+    })
+}
+
+/// Builds the models of the fields of a type, in declaration order
+pub fn build_field_models(
+    metadata: MetadataReader<'_>,
+    typ: &TypeDefinition<'_>,
+) -> Result<Vec<FieldModel>> {
+    let mut fields = Vec::new();
+    let Ok(iter) = typ.fields.iter() else {
+        return Ok(fields);
+    };
+
+    for field in iter.flatten().flat_map(|hdl| hdl.to_data(metadata)) {
+        let signature = field.signature.to_data(metadata)?;
+
+        fields.push(FieldModel {
+            name: field.name.to_data(metadata)?.value,
+            type_name: get_type_name_from_handle(
+                signature.type_handle,
+                ParentInfo::typ(typ),
+                metadata,
+            )
+            .unwrap_or_else(|_| "Unknown TypeDefinition".to_string()),
+            is_static: field.flags.is_static(),
+            offset: field.offset,
+        });
+    }
+
+    Ok(fields)
+}
+
+fn build_method_model(
+    pe: &NativeAotBinary<'_>,
+    metadata: MetadataReader<'_>,
+    typ: &TypeDefinition<'_>,
+    method: &Method<'_>,
+    method_ptrs: &HashMap<MethodHandle, Va>,
+    strict_signatures: bool,
+    with_tokens: bool,
+) -> Result<Option<MethodModel>> {
+    let Ok(signature) = method.signature.to_data(metadata) else {
+        return Ok(None);
+    };
+
+    let parent = ParentInfo::both(method, typ).strict(strict_signatures);
+
+    let return_type = match signature.return_type {
+        t if t.is_nil() => "void".to_string(),
+        t => get_type_name_from_handle(t, parent, metadata)?,
+    };
+
+    let parameters = match signature.parameters.iter() {
+        Ok(iter) => iter
+            .flatten()
+            .map(|param| {
+                get_type_name_from_handle(param, parent, metadata)
+                    .unwrap_or_else(|_| "<unknown>".to_string())
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+
+    let generic_parameters = match method.generic_parameters.iter() {
+        Ok(iter) => iter
+            .flatten()
+            .flat_map(|hdl| hdl.to_data(metadata))
+            .flat_map(|param| param.name.to_data(metadata))
+            .map(|name| name.value)
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+
+    let rva = method_ptrs
+        .get(&method.handle())
+        .and_then(|&va| pe.pe().va_to_rva(va).ok());
+
+    Ok(Some(MethodModel {
+        name: method.name.to_data(metadata)?.value,
+        token: with_tokens.then(|| method.handle().to_value()),
+        access: member_access_name(method.flags.member_access()).to_string(),
+        return_type,
+        generic_parameters,
+        parameters,
+        rva,
+    }))
+}
@@ -0,0 +1,294 @@
+//! Naming the types and members of the embedded metadata in C# syntax, shared by the text and JSON output
+
+use anyhow::Result;
+
+use crate::{
+    embedded_meta::{
+        ByReferenceSignature, MetadataReader, Method, MethodSignature, MethodTypeVariableSignature,
+        ModifiedType, TypeDefinition, TypeInstantiationSignature, TypeSpecification,
+        TypeVariableSignature,
+        flags::MethodMemberAccess,
+        handles::{BaseHandle, ByReferenceSignatureHandle, Handle, HandleType},
+        utils::canonical_type_name,
+        visitor::HandleVisitor,
+    },
+    error,
+    style::Style,
+};
+
+pub fn member_access_name(access: MethodMemberAccess) -> &'static str {
+    match access {
+        MethodMemberAccess::Assembly => "internal",
+        MethodMemberAccess::FamAndAssem => "private protected",
+        MethodMemberAccess::FamOrAssem => "internal protected",
+        MethodMemberAccess::Family => "protected",
+        MethodMemberAccess::Private => "private",
+        MethodMemberAccess::PrivateScope => "",
+        MethodMemberAccess::Public => "public",
+    }
+}
+
+pub fn get_return_type_name(
+    signature: &MethodSignature<'_>,
+    parent: ParentInfo,
+    reader: MetadataReader<'_>,
+) -> Result<String> {
+    match signature.return_type {
+        t if t.is_nil() => Ok("void".to_string()),
+        t => get_type_name_from_handle(t, parent, reader),
+    }
+}
+
+pub fn get_parameter_list(
+    signature: &MethodSignature<'_>,
+    parent: ParentInfo,
+    reader: MetadataReader<'_>,
+    style: Style,
+) -> Result<String> {
+    let Ok(iter) = signature.parameters.iter() else {
+        return Ok(String::new());
+    };
+
+    let params = iter
+        .flatten()
+        .map(|param| {
+            // Turn this BaseHandle into a readable string
+            let name =
+                get_type_name_from_handle(param, parent, reader).map(|name| style.type_name(&name));
+
+            name_or(name, "<unknown>")
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(params.join(", "))
+}
+
+/// Falls back to `placeholder` for a name that failed to decode, unless `--strict-signatures` rejected it
+pub fn name_or(name: Result<String>, placeholder: &str) -> Result<String> {
+    match name {
+        Err(why)
+            if !matches!(
+                why.downcast_ref(),
+                Some(error::AotError::UnsupportedHandle { .. })
+            ) =>
+        {
+            Ok(placeholder.to_string())
+        }
+        name => name,
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct ParentInfo<'a> {
+    method: Option<&'a Method<'a>>,
+    typ: Option<&'a TypeDefinition<'a>>,
+    /// Set by `--strict-signatures`, failing on handle types the [`TypeNamer`] doesn't model
+    strict: bool,
+}
+
+impl<'a> ParentInfo<'a> {
+    pub fn none() -> Self {
+        Self {
+            method: None,
+            typ: None,
+            strict: false,
+        }
+    }
+
+    pub fn typ(typ: &'a TypeDefinition<'a>) -> Self {
+        Self {
+            method: None,
+            typ: Some(typ),
+            strict: false,
+        }
+    }
+
+    pub fn both(method: &'a Method<'a>, typ: &'a TypeDefinition<'a>) -> Self {
+        Self {
+            method: Some(method),
+            typ: Some(typ),
+            strict: false,
+        }
+    }
+
+    pub fn strict(self, strict: bool) -> Self {
+        Self { strict, ..self }
+    }
+
+    pub fn has_none(&self) -> bool {
+        self.method.is_none() && self.typ.is_none()
+    }
+
+    pub fn has_method(&self) -> bool {
+        self.method.is_some()
+    }
+
+    pub fn has_type(&self) -> bool {
+        self.typ.is_some()
+    }
+
+    pub fn get_method_generic(&self, reader: MetadataReader<'a>, index: usize) -> Option<String> {
+        Some(
+            self.method?
+                .generic_parameters
+                .iter()
+                .ok()?
+                .collect::<Vec<_>>()
+                .get(index)?
+                .as_ref()
+                .ok()?
+                .to_data(reader)
+                .ok()?
+                .name
+                .to_data(reader)
+                .ok()?
+                .value,
+        )
+    }
+
+    pub fn get_type_generic(&self, reader: MetadataReader<'a>, index: usize) -> Option<String> {
+        Some(
+            self.typ?
+                .generic_parameters
+                .iter()
+                .ok()?
+                .collect::<Vec<_>>()
+                .get(index)?
+                .as_ref()
+                .ok()?
+                .to_data(reader)
+                .ok()?
+                .name
+                .to_data(reader)
+                .ok()?
+                .value,
+        )
+    }
+}
+
+pub fn get_type_name_from_handle(
+    handle: BaseHandle,
+    parent: ParentInfo,
+    reader: MetadataReader<'_>,
+) -> Result<String> {
+    Ok(handle.visit(reader, &mut TypeNamer { parent, reader })?)
+}
+
+/// Names the type a handle refers to, resolving generic parameters through the parent
+pub struct TypeNamer<'a, 'p> {
+    parent: ParentInfo<'p>,
+    reader: MetadataReader<'a>,
+}
+
+impl<'a, 'p> TypeNamer<'a, 'p> {
+    fn name(&mut self, handle: BaseHandle) -> error::Result<String> {
+        handle.visit(self.reader, self)
+    }
+}
+
+impl<'a, 'p> HandleVisitor<'a> for TypeNamer<'a, 'p> {
+    type Output = String;
+
+    fn visit_other(&mut self, handle: BaseHandle) -> error::Result<String> {
+        if self.parent.strict {
+            return Err(error::AotError::UnsupportedHandle {
+                handle_type: handle.handle_type().unwrap_or(HandleType::Invalid),
+                handle: handle.to_value(),
+            });
+        }
+
+        Ok(format!(
+            "{:?}",
+            handle.handle_type().unwrap_or(HandleType::Null)
+        ))
+    }
+
+    fn visit_type_definition(&mut self, typedef: TypeDefinition<'a>) -> error::Result<String> {
+        if self.parent.has_none() {
+            typedef.get_full_name_with_generics()
+        } else {
+            typedef.get_full_name()
+        }
+    }
+
+    fn visit_type_specification(
+        &mut self,
+        typespec: TypeSpecification<'a>,
+    ) -> error::Result<String> {
+        self.name(typespec.signature)
+    }
+
+    // Generic type
+    fn visit_type_instantiation_signature(
+        &mut self,
+        typeinst: TypeInstantiationSignature<'a>,
+    ) -> error::Result<String> {
+        let generic_type_name = self.name(typeinst.generic_type)?;
+        let mut generic_type_args = vec![];
+
+        for typ in typeinst.generic_args.iter()?.flatten() {
+            generic_type_args.push(self.name(typ)?);
+        }
+
+        Ok(format!(
+            "{generic_type_name}<{}>",
+            generic_type_args.join(", ")
+        ))
+    }
+
+    // ref Type
+    fn visit_by_reference_signature(
+        &mut self,
+        refsig: ByReferenceSignature<'a>,
+    ) -> error::Result<String> {
+        Ok(format!("ref {}", self.name(refsig.type_handle)?))
+    }
+
+    // `ref readonly` (and `in`) is a byref with a `modreq(InAttribute)`, other modifiers are left out
+    fn visit_modified_type(&mut self, modified: ModifiedType<'a>) -> error::Result<String> {
+        let readonly_modifier = matches!(
+            canonical_type_name(self.reader, modified.modifier_type).as_deref(),
+            Ok("System.Runtime.InteropServices.InAttribute"
+                | "System.Runtime.CompilerServices.IsReadOnlyAttribute")
+        );
+
+        if readonly_modifier
+            && let Ok(refsig) = modified
+                .type_handle
+                .to_handle::<ByReferenceSignatureHandle>()
+                .and_then(|hdl| hdl.to_data(self.reader))
+        {
+            return Ok(format!("ref readonly {}", self.name(refsig.type_handle)?));
+        }
+
+        self.name(modified.type_handle)
+    }
+
+    fn visit_method_type_variable_signature(
+        &mut self,
+        mtvarsig: MethodTypeVariableSignature<'a>,
+    ) -> error::Result<String> {
+        if !self.parent.has_method() {
+            return Ok("MethodTypeVariableSignature".to_string());
+        }
+
+        Ok(self
+            .parent
+            .get_method_generic(self.reader, mtvarsig.number as usize)
+            .unwrap_or_else(|| "Unknown".to_string()))
+    }
+
+    fn visit_type_variable_signature(
+        &mut self,
+        tvarsig: TypeVariableSignature<'a>,
+    ) -> error::Result<String> {
+        if !self.parent.has_type() {
+            return Ok("TypeVariableSignature".to_string());
+        }
+
+        Ok(self
+            .parent
+            .get_type_generic(self.reader, tvarsig.number as usize)
+            .unwrap_or_else(|| "Unknown".to_string()))
+    }
+}
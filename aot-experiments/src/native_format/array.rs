@@ -0,0 +1,114 @@
+use crate::{
+    error::{AotError, Result},
+    native_format::{parser::NativeParser, reader::NativeReader},
+};
+
+#[derive(Clone, Copy)]
+pub struct NativeArray<'a> {
+    reader: NativeReader<'a>,
+    base_offset: usize,
+    number_of_elements: u32,
+    entry_index_size: u8,
+}
+
+impl<'a> NativeArray<'a> {
+    const BLOCK_SIZE: u32 = 16;
+
+    pub fn new(parser: NativeParser<'a>) -> Result<Self> {
+        let mut offset = parser.offset;
+        let header = parser.reader.decode_unsigned(&mut offset)?;
+
+        let entry_index_size = (header & 3) as u8;
+        if entry_index_size > 2 {
+            return Err(AotError::BadImage);
+        }
+
+        Ok(Self {
+            reader: parser.reader,
+            base_offset: offset,
+            number_of_elements: header >> 2,
+            entry_index_size,
+        })
+    }
+
+    pub fn len(&self) -> u32 {
+        self.number_of_elements
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.number_of_elements == 0
+    }
+
+    pub fn get(&self, index: u32) -> Result<Option<NativeParser<'a>>> {
+        if index >= self.number_of_elements {
+            return Ok(None);
+        }
+
+        let block = (index / Self::BLOCK_SIZE) as usize;
+        let mut offset = self.base_offset
+            + match self.entry_index_size {
+                0 => self.reader.read_u8(self.base_offset + block)? as usize,
+                1 => self.reader.read_u16(self.base_offset + 2 * block)? as usize,
+                _ => self.reader.read_u32(self.base_offset + 4 * block)? as usize,
+            };
+
+        // Each block is a small binary tree, walk it down to the leaf for this index
+        let mut bit = Self::BLOCK_SIZE >> 1;
+        while bit > 0 {
+            let mut next_offset = offset;
+            let val = self.reader.decode_unsigned(&mut next_offset)?;
+
+            if index & bit != 0 {
+                if val & 2 != 0 {
+                    offset += (val >> 2) as usize;
+                    bit >>= 1;
+                    continue;
+                }
+            } else if val & 1 != 0 {
+                offset = next_offset;
+                bit >>= 1;
+                continue;
+            }
+
+            // Special leaf node, only matches if it is the index we are looking for
+            if val & 3 == 0 && (val >> 2) == (index & (Self::BLOCK_SIZE - 1)) {
+                offset = next_offset;
+                break;
+            }
+
+            return Ok(None);
+        }
+
+        Ok(Some(NativeParser::new(self.reader, offset)))
+    }
+
+    pub fn iter(&self) -> NativeArrayIterator<'a> {
+        NativeArrayIterator {
+            array: *self,
+            index: 0,
+        }
+    }
+}
+
+pub struct NativeArrayIterator<'a> {
+    array: NativeArray<'a>,
+    index: u32,
+}
+
+impl<'a> Iterator for NativeArrayIterator<'a> {
+    type Item = (u32, NativeParser<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.index < self.array.len() {
+            let index = self.index;
+            self.index += 1;
+
+            // Arrays are sparse, not every index has an element
+            if let Some(parser) = self.array.get(index).ok()? {
+                return Some((index, parser));
+            }
+        }
+
+        None
+    }
+}
@@ -39,34 +39,25 @@ impl<'a> NativeHashtable<'a> {
         bucket: u32,
         offset: &mut usize,
     ) -> Result<NativeParser<'a>> {
-        let (start, end) = if self.entry_index_size == 0 {
-            let bucket_offset = self.base_offset + bucket as usize;
-
-            (
-                self.reader.read_u8(bucket_offset)? as u32,
-                self.reader.read_u8(bucket_offset + 1)? as u32,
-            )
-        } else if self.entry_index_size == 1 {
-            let bucket_offset = self.base_offset + 2 * bucket as usize;
-
-            (
-                self.reader.read_u16(bucket_offset)? as u32,
-                self.reader.read_u16(bucket_offset + 2)? as u32,
-            )
-        } else {
-            let bucket_offset = self.base_offset + 4 * bucket as usize;
-
-            (
-                self.reader.read_u32(bucket_offset)?,
-                self.reader.read_u32(bucket_offset + 4)?,
-            )
+        let start = self.bucket_bound(bucket)?;
+        *offset = self.bucket_bound(bucket + 1)?;
+
+        Ok(NativeParser::new(self.reader, start))
+    }
+
+    /// Offset at which a bucket starts, which is also where the previous bucket ends
+    fn bucket_bound(&self, bucket: u32) -> Result<usize> {
+        let bound = match self.entry_index_size {
+            0 => self.reader.read_u8(self.base_offset + bucket as usize)? as u32,
+            1 => self
+                .reader
+                .read_u16(self.base_offset + 2 * bucket as usize)? as u32,
+            _ => self
+                .reader
+                .read_u32(self.base_offset + 4 * bucket as usize)?,
         };
 
-        *offset = end as usize + self.base_offset;
-        Ok(NativeParser::new(
-            self.reader,
-            self.base_offset + start as usize,
-        ))
+        Ok(self.base_offset + bound as usize)
     }
 
     pub fn lookup(&self, hashcode: i32) -> Result<NativeHashtableIterator<'a>> {
@@ -122,22 +113,22 @@ impl<'a> Iterator for NativeHashtableIterator<'a> {
 }
 
 pub struct NativeHashtableAllEntries<'a> {
-    table: NativeHashtable<'a>,
     parser: NativeParser<'a>,
-    current_bucket: u32,
-    end_offset: usize,
+    current_bucket: usize,
+    /// The bounds of every bucket, read up front as large tables (like the InvokeMap) have thousands of buckets
+    bucket_bounds: Vec<usize>,
 }
 
 impl<'a> NativeHashtableAllEntries<'a> {
     pub fn new(table: NativeHashtable<'a>) -> Result<Self> {
-        let mut end_offset = 0;
-        let parser = table.get_parser_for_bucket(0, &mut end_offset)?;
+        let bucket_bounds = (0..=table.bucket_mask + 1)
+            .map(|bucket| table.bucket_bound(bucket))
+            .collect::<Result<Vec<_>>>()?;
 
         Ok(Self {
-            table,
-            parser,
+            parser: NativeParser::new(table.reader, bucket_bounds[0]),
             current_bucket: 0,
-            end_offset,
+            bucket_bounds,
         })
     }
 }
@@ -147,20 +138,121 @@ impl<'a> Iterator for NativeHashtableAllEntries<'a> {
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
-            while self.parser.offset < self.end_offset {
+            if self.parser.offset < self.bucket_bounds[self.current_bucket + 1] {
                 self.parser.get_u8().ok()?;
-                return Some(self.parser.get_parser_from_rel_offset().ok()?);
+                return self.parser.get_parser_from_rel_offset().ok();
             }
 
-            if self.current_bucket >= self.table.bucket_mask {
+            // The last bound only marks the end of the last bucket
+            if self.current_bucket + 2 >= self.bucket_bounds.len() {
                 return None;
             }
 
             self.current_bucket += 1;
-            self.parser = self
-                .table
-                .get_parser_for_bucket(self.current_bucket, &mut self.end_offset)
-                .ok()?;
+            self.parser.offset = self.bucket_bounds[self.current_bucket];
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        native_format::{hashtable::NativeHashtable, parser::NativeParser, reader::NativeReader},
+        test_utils::{encode_hashtable, encode_signed},
+    };
+
+    #[test]
+    fn enumerate_all_walks_every_bucket() {
+        // 4 buckets with 1 byte offsets, of which the second and the last are empty
+        let mut buf = vec![2 << 2, 5, 9, 9, 11, 11];
+        for hashcode in [0x10, 0x20, 0x30] {
+            buf.push(hashcode);
+            encode_signed(&mut buf, 0);
+        }
+
+        let reader = NativeReader::new(&buf).unwrap();
+        let table = NativeHashtable::new(NativeParser::new(reader, 0)).unwrap();
+
+        let offsets = table
+            .enumerate_all()
+            .unwrap()
+            .map(|parser| parser.offset)
+            .collect::<Vec<_>>();
+
+        // The same entries as when reading the bounds of every bucket separately
+        let mut expected = Vec::new();
+        for bucket in 0..4 {
+            let mut end = 0;
+            let mut parser = table.get_parser_for_bucket(bucket, &mut end).unwrap();
+
+            while parser.offset < end {
+                parser.get_u8().unwrap();
+                expected.push(parser.get_parser_from_rel_offset().unwrap().offset);
+            }
+        }
+
+        assert_eq!(offsets, vec![7, 9, 11]);
+        assert_eq!(offsets, expected);
+    }
+
+    #[test]
+    fn enumerate_all_reads_a_single_bucket() {
+        // A shift of 0 leaves a bucket mask of 0, which still has to read bucket 0
+        let buf = encode_hashtable(&[(0x10, vec![1]), (0x20, vec![2]), (0x30, vec![3])]);
+
+        let reader = NativeReader::new(&buf).unwrap();
+        let table = NativeHashtable::new(NativeParser::new(reader, 0)).unwrap();
+
+        let payloads = table
+            .enumerate_all()
+            .unwrap()
+            .map(|mut parser| parser.get_u8().unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(payloads, vec![1, 2, 3]);
+        assert_eq!(
+            table
+                .lookup(0x20)
+                .unwrap()
+                .next()
+                .unwrap()
+                .get_u8()
+                .unwrap(),
+            2
+        );
+    }
+
+    #[test]
+    fn enumerate_all_reads_the_last_bucket() {
+        // 8 buckets with 1 byte offsets, entries in the first and the last
+        let mut buf = vec![3 << 2, 9, 11, 11, 11, 11, 11, 11, 11, 15];
+        for hashcode in [0x10, 0x20, 0x30] {
+            buf.push(hashcode);
+            encode_signed(&mut buf, 0);
+        }
+
+        let reader = NativeReader::new(&buf).unwrap();
+        let table = NativeHashtable::new(NativeParser::new(reader, 0)).unwrap();
+
+        let offsets = table
+            .enumerate_all()
+            .unwrap()
+            .map(|parser| parser.offset)
+            .collect::<Vec<_>>();
+
+        assert_eq!(offsets, vec![11, 13, 15]);
+
+        // The bucket is taken from the upper bits of the hashcode
+        let lookup = |hashcode| {
+            table
+                .lookup(hashcode)
+                .unwrap()
+                .map(|parser| parser.offset)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(lookup(0x730), vec![15]);
+        assert_eq!(lookup(0x010), vec![11]);
+        assert!(lookup(0x710).is_empty());
+    }
+}
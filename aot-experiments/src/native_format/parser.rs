@@ -11,6 +11,10 @@ impl<'a> NativeParser<'a> {
         Self { reader, offset }
     }
 
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
     pub fn get_u8(&mut self) -> Result<u8> {
         let value = self.reader.read_u8(self.offset)?;
         self.offset += 1;
@@ -29,11 +33,15 @@ impl<'a> NativeParser<'a> {
         self.reader.decode_signed(&mut self.offset)
     }
 
+    pub fn get_string(&mut self) -> Result<String> {
+        self.reader.decode_string(&mut self.offset)
+    }
+
     pub fn get_relative_offset(&mut self) -> Result<u32> {
         let pos = self.offset;
         let delta = self.reader.decode_signed(&mut self.offset)?;
 
-        Ok(pos as u32 + delta as u32)
+        Ok((pos as u32).wrapping_add_signed(delta))
     }
 
     pub fn skip_integer(&mut self) -> Result<()> {
@@ -51,3 +59,22 @@ impl<'a> NativeParser<'a> {
         self.get_unsigned()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        native_format::{parser::NativeParser, reader::NativeReader},
+        test_utils::encode_signed,
+    };
+
+    #[test]
+    fn relative_offsets_can_point_backwards() {
+        let mut buf = vec![0; 4];
+        encode_signed(&mut buf, -4);
+
+        let reader = NativeReader::new(&buf).unwrap();
+        let mut parser = NativeParser::new(reader, 4);
+
+        assert_eq!(parser.get_relative_offset().unwrap(), 0);
+    }
+}
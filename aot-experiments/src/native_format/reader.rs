@@ -1,6 +1,9 @@
-use std::fmt::Debug;
+use std::{fmt::Debug, marker::PhantomData};
 
-use crate::error::{AotError, Result};
+use crate::{
+    embedded_meta::handles::Handle,
+    error::{AotError, Result},
+};
 
 macro_rules! impl_read_primitives {
     ($($fn:ident: $primitive:ident $(,)?)*) => {
@@ -19,6 +22,8 @@ macro_rules! impl_read_primitives {
 #[derive(Clone, Copy)]
 pub struct NativeReader<'a> {
     data: &'a [u8],
+    /// See [`Self::with_strict_strings`]
+    strict_strings: bool,
 }
 
 impl<'a> NativeReader<'a> {
@@ -27,7 +32,27 @@ impl<'a> NativeReader<'a> {
             return Err(AotError::BadImage);
         }
 
-        Ok(Self { data })
+        Ok(Self {
+            data,
+            strict_strings: false,
+        })
+    }
+
+    /// Makes [`Self::decode_string`] reject invalid UTF-8 instead of replacing it, as set by `--strict-strings`
+    pub fn with_strict_strings(self, strict_strings: bool) -> Self {
+        Self {
+            strict_strings,
+            ..self
+        }
+    }
+
+    /// Size of the underlying blob
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
     }
 
     pub fn ensure_offset_in_range(&self, offset: usize, look_ahead: usize) -> Result<usize> {
@@ -47,10 +72,6 @@ impl<'a> NativeReader<'a> {
         read_f64: f64,
     }
 
-    pub fn read<R: NativeReadable<'a>>(&self, offset: &mut usize) -> Result<R> {
-        R::read(self, offset)
-    }
-
     pub fn decode_unsigned(&self, offset: &mut usize) -> Result<u32> {
         self.ensure_offset_in_range(*offset, 0)?;
 
@@ -91,8 +112,8 @@ impl<'a> NativeReader<'a> {
                 | ((*self.data.get(*offset + 3).ok_or(AotError::BadImage)? as u32) << 20);
             *offset += 4;
         } else if val & 16 == 0 {
-            *offset += 1;
-            value = self.read_u32(*offset)?;
+            value = self.read_u32(*offset + 1)?;
+            *offset += 5;
         } else {
             return Err(AotError::BadImage);
         }
@@ -117,7 +138,7 @@ impl<'a> NativeReader<'a> {
                 | ((self
                     .data
                     .get(*offset + 1)
-                    .map(|v| *v as i32)
+                    .map(|v| *v as i8 as i32)
                     .ok_or(AotError::BadImage)? as i32)
                     << 6);
             *offset += 2;
@@ -136,7 +157,7 @@ impl<'a> NativeReader<'a> {
                 | ((self
                     .data
                     .get(*offset + 2)
-                    .map(|v| *v as i32)
+                    .map(|v| *v as i8 as i32)
                     .ok_or(AotError::BadImage)? as i32)
                     << 13);
             *offset += 3;
@@ -161,13 +182,13 @@ impl<'a> NativeReader<'a> {
                 | ((self
                     .data
                     .get(*offset + 3)
-                    .map(|v| *v as i32)
+                    .map(|v| *v as i8 as i32)
                     .ok_or(AotError::BadImage)? as i32)
                     << 20);
             *offset += 4;
         } else if val & 16 == 0 {
-            *offset += 1;
-            value = self.read_u32(*offset)? as i32;
+            value = self.read_u32(*offset + 1)? as i32;
+            *offset += 5;
         } else {
             return Err(AotError::BadImage);
         }
@@ -181,8 +202,9 @@ impl<'a> NativeReader<'a> {
         Ok(if val & 31 != 31 {
             self.decode_unsigned(offset)? as u64
         } else if val & 32 == 0 {
-            *offset += 1;
-            self.read_u64(*offset)?
+            let value = self.read_u64(*offset + 1)?;
+            *offset += 9;
+            value
         } else {
             return Err(AotError::BadImage);
         })
@@ -194,29 +216,43 @@ impl<'a> NativeReader<'a> {
         Ok(if val & 31 != 31 {
             self.decode_signed(offset)? as i64
         } else if val & 32 == 0 {
-            *offset += 1;
-            self.read_u64(*offset)? as i64
+            let value = self.read_u64(*offset + 1)? as i64;
+            *offset += 9;
+            value
         } else {
             return Err(AotError::BadImage);
         })
     }
 
+    /// Decodes a length prefixed UTF-8 string, invalid bytes are replaced by U+FFFD unless the reader is strict
     pub fn decode_string(&self, offset: &mut usize) -> Result<String> {
+        if self.strict_strings {
+            return self.decode_string_strict(offset);
+        }
+
+        Ok(String::from_utf8_lossy(self.decode_string_bytes(offset)?).into_owned())
+    }
+
+    /// Like [`Self::decode_string`], but fails on invalid UTF-8, which is a sign of a string read at the wrong offset
+    pub fn decode_string_strict(&self, offset: &mut usize) -> Result<String> {
+        let bytes = self.decode_string_bytes(offset)?;
+
+        String::from_utf8(bytes.to_vec()).map_err(|_| AotError::BadImage)
+    }
+
+    fn decode_string_bytes(&self, offset: &mut usize) -> Result<&'a [u8]> {
         let length = self.decode_unsigned(offset)?;
 
         if length == 0 {
-            return Ok(String::new());
+            return Ok(&[]);
         }
 
         let end_offset = *offset + length as usize;
-        if end_offset < length as usize || *offset > self.data.len() {
+        if end_offset < length as usize || end_offset > self.data.len() {
             return Err(AotError::BadImage);
         }
 
-        Ok(
-            String::from_utf8_lossy(&self.data[*offset as usize..*offset + length as usize])
-                .into_owned(),
-        )
+        Ok(&self.data[*offset..end_offset])
     }
 
     pub fn skip_integer(&self, offset: &mut usize) -> Result<()> {
@@ -258,81 +294,388 @@ impl<'a> Debug for NativeReader<'a> {
     }
 }
 
+/// A position in a [`NativeReader`], which advances past every value read through it
+#[derive(Clone, Copy)]
+pub struct Cursor<'a> {
+    reader: NativeReader<'a>,
+    offset: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(reader: NativeReader<'a>, offset: usize) -> Self {
+        Self { reader, offset }
+    }
+
+    pub fn reader(&self) -> NativeReader<'a> {
+        self.reader
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn read<R: NativeReadable<'a>>(&mut self) -> Result<R> {
+        let start = self.offset;
+
+        R::read(self).map_err(|err| err.at(start, std::any::type_name::<R>()))
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8> {
+        let value = self.reader.read_u8(self.offset)?;
+        self.offset += size_of::<u8>();
+        Ok(value)
+    }
+
+    pub fn read_f32(&mut self) -> Result<f32> {
+        let value = self.reader.read_f32(self.offset)?;
+        self.offset += size_of::<f32>();
+        Ok(value)
+    }
+
+    pub fn read_f64(&mut self) -> Result<f64> {
+        let value = self.reader.read_f64(self.offset)?;
+        self.offset += size_of::<f64>();
+        Ok(value)
+    }
+
+    pub fn read_unsigned(&mut self) -> Result<u32> {
+        self.reader.decode_unsigned(&mut self.offset)
+    }
+
+    pub fn read_signed(&mut self) -> Result<i32> {
+        self.reader.decode_signed(&mut self.offset)
+    }
+
+    pub fn read_unsigned_long(&mut self) -> Result<u64> {
+        self.reader.decode_unsigned_long(&mut self.offset)
+    }
+
+    pub fn read_signed_long(&mut self) -> Result<i64> {
+        self.reader.decode_signed_long(&mut self.offset)
+    }
+
+    pub fn read_string(&mut self) -> Result<String> {
+        self.reader.decode_string(&mut self.offset)
+    }
+
+    pub fn read_handle<H: Handle>(&mut self) -> Result<H> {
+        H::from_value(self.read_unsigned()?)
+    }
+
+    pub fn skip_integer(&mut self) -> Result<()> {
+        self.reader.skip_integer(&mut self.offset)
+    }
+
+    pub fn skip(&mut self, length: usize) {
+        self.offset += length;
+    }
+}
+
 pub trait NativeReadable<'a>
 where
     Self: Sized,
 {
-    fn read(reader: &NativeReader<'a>, offset: &mut usize) -> Result<Self>;
+    fn read(cursor: &mut Cursor<'a>) -> Result<Self>;
+}
+
+/// Reads a count followed by that many values, advancing the cursor past all of them
+pub fn read_sequence<'a, T: NativeReadable<'a>>(cursor: &mut Cursor<'a>) -> Result<Vec<T>> {
+    let mut sequence = SequenceIter::new(*cursor)?;
+    let values = sequence.by_ref().collect::<Result<Vec<_>>>()?;
+
+    *cursor = sequence.cursor;
+    Ok(values)
+}
+
+/// Lazily reads a count followed by that many values, see [`read_sequence`]
+pub struct SequenceIter<'a, T> {
+    cursor: Cursor<'a>,
+    remaining: u32,
+    _marker: PhantomData<T>,
+}
+
+impl<'a, T: NativeReadable<'a>> SequenceIter<'a, T> {
+    /// Reads the count at the cursor, the values are read while iterating
+    pub fn new(mut cursor: Cursor<'a>) -> Result<Self> {
+        let remaining = cursor.read_unsigned()?;
+
+        Ok(Self {
+            cursor,
+            remaining,
+            _marker: PhantomData,
+        })
+    }
+}
+
+impl<'a, T: NativeReadable<'a>> Iterator for SequenceIter<'a, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        self.remaining -= 1;
+
+        Some(self.cursor.read::<T>())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining as usize))
+    }
 }
 
 mod native_reader_impls {
     use crate::{
-        embedded_meta::flags::{MethodAttributes, SignatureCallingConvention},
-        native_format::reader::NativeReadable,
+        embedded_meta::flags::{
+            CallingConventionFlags, FieldAttributes, MethodAttributes, MethodImplAttributes,
+            MethodSemanticsAttributes, TypeAttributes,
+        },
+        error::Result,
+        native_format::reader::{Cursor, NativeReadable},
     };
 
+    impl<'a> NativeReadable<'a> for bool {
+        fn read(cursor: &mut Cursor<'a>) -> Result<Self> {
+            cursor.read_u8().map(|value| value != 0)
+        }
+    }
+
     impl<'a> NativeReadable<'a> for String {
-        fn read(
-            reader: &super::NativeReader<'_>,
-            offset: &mut usize,
-        ) -> crate::error::Result<Self> {
-            reader.decode_string(offset)
+        fn read(cursor: &mut Cursor<'a>) -> Result<Self> {
+            cursor.read_string()
         }
     }
 
     impl<'a> NativeReadable<'a> for u8 {
-        fn read(
-            reader: &super::NativeReader<'_>,
-            offset: &mut usize,
-        ) -> crate::error::Result<Self> {
-            let value = reader.read_u8(*offset)?;
-            *offset += 1;
-            Ok(value)
+        fn read(cursor: &mut Cursor<'a>) -> Result<Self> {
+            cursor.read_u8()
         }
     }
 
     impl<'a> NativeReadable<'a> for u16 {
-        fn read(
-            reader: &super::NativeReader<'_>,
-            offset: &mut usize,
-        ) -> crate::error::Result<Self> {
-            Ok(reader.decode_unsigned(offset)? as u16)
+        fn read(cursor: &mut Cursor<'a>) -> Result<Self> {
+            Ok(cursor.read_unsigned()? as u16)
         }
     }
 
     impl<'a> NativeReadable<'a> for u32 {
-        fn read(
-            reader: &super::NativeReader<'_>,
-            offset: &mut usize,
-        ) -> crate::error::Result<Self> {
-            reader.decode_unsigned(offset)
+        fn read(cursor: &mut Cursor<'a>) -> Result<Self> {
+            cursor.read_unsigned()
         }
     }
 
     impl<'a> NativeReadable<'a> for i32 {
-        fn read(
-            reader: &super::NativeReader<'a>,
-            offset: &mut usize,
-        ) -> crate::error::Result<Self> {
-            reader.decode_signed(offset)
+        fn read(cursor: &mut Cursor<'a>) -> Result<Self> {
+            cursor.read_signed()
         }
     }
 
     impl<'a> NativeReadable<'a> for MethodAttributes {
-        fn read(
-            reader: &super::NativeReader<'a>,
-            offset: &mut usize,
-        ) -> crate::error::Result<Self> {
-            <u32 as NativeReadable>::read(reader, offset).map(MethodAttributes::new)
+        fn read(cursor: &mut Cursor<'a>) -> Result<Self> {
+            cursor.read_unsigned().map(MethodAttributes::new)
+        }
+    }
+
+    impl<'a> NativeReadable<'a> for TypeAttributes {
+        fn read(cursor: &mut Cursor<'a>) -> Result<Self> {
+            cursor.read_unsigned().map(TypeAttributes::new)
+        }
+    }
+
+    impl<'a> NativeReadable<'a> for FieldAttributes {
+        fn read(cursor: &mut Cursor<'a>) -> Result<Self> {
+            cursor.read_unsigned().map(FieldAttributes::new)
         }
     }
 
-    impl<'a> NativeReadable<'a> for SignatureCallingConvention {
-        fn read(
-            reader: &super::NativeReader<'a>,
-            offset: &mut usize,
-        ) -> crate::error::Result<Self> {
-            <u8 as NativeReadable>::read(reader, offset).map(SignatureCallingConvention::from)
+    impl<'a> NativeReadable<'a> for MethodImplAttributes {
+        fn read(cursor: &mut Cursor<'a>) -> Result<Self> {
+            cursor.read_unsigned().map(MethodImplAttributes::new)
         }
     }
+
+    impl<'a> NativeReadable<'a> for MethodSemanticsAttributes {
+        fn read(cursor: &mut Cursor<'a>) -> Result<Self> {
+            cursor.read_unsigned().map(MethodSemanticsAttributes::new)
+        }
+    }
+
+    impl<'a> NativeReadable<'a> for CallingConventionFlags {
+        fn read(cursor: &mut Cursor<'a>) -> Result<Self> {
+            cursor.read_u8().map(CallingConventionFlags::new)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::Result,
+        native_format::reader::{Cursor, NativeReader, SequenceIter, read_sequence},
+        test_utils::{encode_signed, encode_signed_long, encode_unsigned, encode_unsigned_long},
+    };
+
+    // Every value is followed by a marker byte, to make sure the decoder advanced past the whole value
+    const MARKER: u8 = 0xAA;
+
+    #[test]
+    fn unsigned_round_trip() {
+        for value in [
+            0,
+            1,
+            127,
+            128,
+            0x3FFF,
+            0x4000,
+            0x1F_FFFF,
+            0x20_0000,
+            0xFFF_FFFF,
+            u32::MAX,
+        ] {
+            let mut buf = Vec::new();
+            encode_unsigned(&mut buf, value);
+            buf.push(MARKER);
+
+            let reader = NativeReader::new(&buf).unwrap();
+            let mut offset = 0;
+
+            assert_eq!(reader.decode_unsigned(&mut offset).unwrap(), value);
+            assert_eq!(reader.read_u8(offset).unwrap(), MARKER, "value {value:#x}");
+        }
+    }
+
+    #[test]
+    fn signed_round_trip() {
+        for value in [
+            0,
+            1,
+            -1,
+            63,
+            -64,
+            64,
+            -65,
+            0x1FFF,
+            -0x2000,
+            -0x10_0000,
+            0x7FF_FFFF,
+            -0x800_0000,
+            i32::MIN,
+            i32::MAX,
+        ] {
+            let mut buf = Vec::new();
+            encode_signed(&mut buf, value);
+            buf.push(MARKER);
+
+            let reader = NativeReader::new(&buf).unwrap();
+            let mut offset = 0;
+
+            assert_eq!(reader.decode_signed(&mut offset).unwrap(), value);
+            assert_eq!(reader.read_u8(offset).unwrap(), MARKER, "value {value:#x}");
+        }
+    }
+
+    #[test]
+    fn unsigned_long_round_trip() {
+        for value in [
+            0,
+            0x4000,
+            u32::MAX as u64,
+            u32::MAX as u64 + 1,
+            0x1234_5678_9ABC_DEF0,
+            u64::MAX,
+        ] {
+            let mut buf = Vec::new();
+            encode_unsigned_long(&mut buf, value);
+            buf.push(MARKER);
+
+            let reader = NativeReader::new(&buf).unwrap();
+            let mut offset = 0;
+
+            assert_eq!(reader.decode_unsigned_long(&mut offset).unwrap(), value);
+            assert_eq!(reader.read_u8(offset).unwrap(), MARKER, "value {value:#x}");
+        }
+    }
+
+    #[test]
+    fn signed_long_round_trip() {
+        for value in [
+            0,
+            -1,
+            i32::MIN as i64,
+            i32::MAX as i64 + 1,
+            i32::MIN as i64 - 1,
+            i64::MIN,
+            i64::MAX,
+        ] {
+            let mut buf = Vec::new();
+            encode_signed_long(&mut buf, value);
+            buf.push(MARKER);
+
+            let reader = NativeReader::new(&buf).unwrap();
+            let mut offset = 0;
+
+            assert_eq!(reader.decode_signed_long(&mut offset).unwrap(), value);
+            assert_eq!(reader.read_u8(offset).unwrap(), MARKER, "value {value:#x}");
+        }
+    }
+
+    #[test]
+    fn skip_integer_matches_decoded_length() {
+        for value in [0, 0x4000, u32::MAX as u64, u64::MAX] {
+            let mut buf = Vec::new();
+            encode_unsigned_long(&mut buf, value);
+            buf.push(MARKER);
+
+            let reader = NativeReader::new(&buf).unwrap();
+            let mut decoded = 0;
+            let mut skipped = 0;
+
+            reader.decode_unsigned_long(&mut decoded).unwrap();
+            reader.skip_integer(&mut skipped).unwrap();
+
+            assert_eq!(decoded, skipped, "value {value:#x}");
+        }
+    }
+
+    #[test]
+    fn read_sequence_advances_past_every_value() {
+        let mut buf = Vec::new();
+        encode_unsigned(&mut buf, 3);
+        for value in [1, 0x4000, u32::MAX] {
+            encode_unsigned(&mut buf, value);
+        }
+        buf.push(MARKER);
+
+        let reader = NativeReader::new(&buf).unwrap();
+        let mut cursor = Cursor::new(reader, 0);
+
+        let lazy = SequenceIter::<u32>::new(cursor)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        let values = read_sequence::<u32>(&mut cursor).unwrap();
+
+        assert_eq!(values, vec![1, 0x4000, u32::MAX]);
+        assert_eq!(lazy, values);
+        assert_eq!(cursor.read_u8().unwrap(), MARKER);
+    }
+
+    #[test]
+    fn strict_strings_reject_invalid_utf8() {
+        let mut buf = Vec::new();
+        encode_unsigned(&mut buf, 3);
+        buf.extend_from_slice(&[b'a', 0xFF, b'b']);
+
+        let reader = NativeReader::new(&buf).unwrap();
+
+        assert_eq!(reader.decode_string(&mut 0).unwrap(), "a\u{FFFD}b");
+        assert!(reader.decode_string_strict(&mut 0).is_err());
+        assert!(
+            reader
+                .with_strict_strings(true)
+                .decode_string(&mut 0)
+                .is_err()
+        );
+    }
 }
@@ -1,6 +1,6 @@
 use std::io::Read;
 
-use pelite::pe64::Va;
+use pelite::pe64::{Pe, Va};
 
 use crate::native_format::View;
 
@@ -19,8 +19,17 @@ impl<'a> ExternalReferencesTable<'a> {
         }
     }
 
+    /// Number of entries in the table
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
     pub fn get_va_from_index(&self, index: u32) -> Option<Va> {
-        if index as usize > self.count {
+        if index as usize >= self.count {
             return None;
         }
 
@@ -30,4 +39,52 @@ impl<'a> ExternalReferencesTable<'a> {
 
         Some((view.base as i64 + i32::from_le_bytes(bytes) as i64) as Va)
     }
+
+    /// The entries whose relative pointer resolves to an address outside of the sections of the image
+    ///
+    /// Lookups through [`Self::get_va_from_index`] still return these addresses, this tells a table that was parsed
+    /// from the wrong place (most entries are bad) apart from the odd entry that points outside of mapped memory.
+    pub fn validate(&self) -> Vec<(u32, Va)> {
+        let pe = self.view.pe;
+
+        (0..self.count as u32)
+            .filter_map(|index| Some((index, self.get_va_from_index(index)?)))
+            .filter(|&(_, va)| {
+                pe.va_to_rva(va)
+                    .ok()
+                    .and_then(|rva| pe.section_headers().by_rva(rva))
+                    .is_none()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pelite::pe64::PeFile;
+
+    use crate::{
+        native_format::{View, ref_table::ExternalReferencesTable},
+        test_utils::{IMAGE_BASE, PeBuilder},
+    };
+
+    #[test]
+    fn validate_reports_entries_outside_the_image() {
+        let mut builder = PeBuilder::new();
+        let table_va = IMAGE_BASE + builder.next_rva() as u64;
+
+        // The first entry points to itself, the second far past the end of the image
+        let mut table = Vec::new();
+        table.extend_from_slice(&0i32.to_le_bytes());
+        table.extend_from_slice(&0x4000_0000i32.to_le_bytes());
+        builder.section(".rdata", table);
+
+        let image = builder.build();
+        let pe = PeFile::from_bytes(&image).unwrap();
+        let table = ExternalReferencesTable::new(View::new(pe, table_va), 8);
+
+        assert_eq!(table.get_va_from_index(0), Some(table_va));
+        assert_eq!(table.get_va_from_index(2), None);
+        assert_eq!(table.validate(), [(1, table_va + 4 + 0x4000_0000)]);
+    }
 }
@@ -0,0 +1,120 @@
+use std::fmt::Debug;
+
+use binary_rw::{ReadStream, SeekStream};
+use pelite::pe64::{Pe, PeFile, PeObject, Rva, Va};
+
+#[derive(Clone, Copy)]
+pub struct View<'a> {
+    pub pe: PeFile<'a>,
+
+    pub(super) base: Va,
+    offset: Va,
+}
+
+impl<'a> View<'a> {
+    pub fn new(pe: PeFile<'a>, va: Va) -> Self {
+        Self {
+            pe,
+            base: va,
+            offset: 0,
+        }
+    }
+
+    pub fn from_rva(pe: PeFile<'a>, rva: Rva) -> pelite::Result<Self> {
+        Ok(Self::new(pe, pe.rva_to_va(rva)?))
+    }
+
+    pub fn from_file_offset(pe: PeFile<'a>, file_offset: usize) -> pelite::Result<Self> {
+        Self::from_rva(pe, pe.file_offset_to_rva(file_offset)?)
+    }
+
+    pub fn va(self) -> Va {
+        self.base + self.offset
+    }
+
+    pub fn bytes(self) -> pelite::Result<&'a [u8]> {
+        self.pe
+            .va_to_rva(self.va())
+            .and_then(|rva| self.pe.rva_to_file_offset(rva))
+            .map(|fo| &self.pe.image()[fo..])
+    }
+
+    pub fn with_offset(self, offset: Va) -> Self {
+        Self::new(self.pe, self.base + offset)
+    }
+}
+
+impl<'a> SeekStream for View<'a> {
+    fn len(&self) -> binary_rw::Result<usize> {
+        Ok(self
+            .bytes()
+            .map_err(|_| binary_rw::BinaryError::ReadPastEof)?
+            .len())
+    }
+
+    fn tell(&mut self) -> binary_rw::Result<usize> {
+        Ok(self.offset as usize)
+    }
+
+    fn seek(&mut self, to: usize) -> binary_rw::Result<usize> {
+        self.offset = to as u64;
+
+        Ok(to)
+    }
+}
+
+impl<'a> std::io::Read for View<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let slice = View::bytes(*self).map_err(|e| std::io::Error::other(e))?;
+        let len = std::cmp::min(slice.len(), buf.len());
+
+        buf[..len].copy_from_slice(&slice[..len]);
+
+        self.offset += len as u64;
+
+        Ok(len)
+    }
+}
+
+impl<'a> ReadStream for View<'a> {}
+
+impl<'a> Debug for View<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("View")
+            .field(&format_args!("{:#x}", self.va()))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use binary_rw::{BinaryReader, Endian, SeekStream};
+    use pelite::pe64::PeFile;
+
+    use crate::{
+        native_format::View,
+        test_utils::{IMAGE_BASE, PeBuilder},
+    };
+
+    #[test]
+    fn view_tell_round_trips_through_seek() {
+        let mut builder = PeBuilder::new();
+        let rva = builder.section(".rdata", (0u8..32).collect());
+        let image = builder.build();
+        let pe = PeFile::from_bytes(&image).unwrap();
+
+        let mut view = View::new(pe, IMAGE_BASE + rva as u64);
+        let mut reader = BinaryReader::new(&mut view, Endian::Little);
+
+        reader.seek(4).unwrap();
+        let position = reader.tell().unwrap();
+        assert_eq!(position, 4);
+
+        let first = reader.read_u32().unwrap();
+        assert_eq!(reader.tell().unwrap(), 8);
+
+        reader.seek(position).unwrap();
+        assert_eq!(reader.read_u32().unwrap(), first);
+        assert_eq!(first, u32::from_le_bytes([4, 5, 6, 7]));
+    }
+}
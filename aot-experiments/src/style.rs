@@ -0,0 +1,54 @@
+use std::io::IsTerminal;
+
+use owo_colors::OwoColorize;
+
+/// Decides how the text dumps are colorized
+#[derive(Clone, Copy, Debug)]
+pub struct Style {
+    colored: bool,
+}
+
+impl Style {
+    pub fn new(colored: bool) -> Self {
+        Self { colored }
+    }
+
+    /// Colorizes only when writing to a terminal, unless explicitly forced on or off
+    pub fn from_flags(color: bool, no_color: bool) -> Self {
+        let colored = if no_color {
+            false
+        } else {
+            color || std::io::stdout().is_terminal()
+        };
+
+        Self::new(colored)
+    }
+
+    pub fn access(&self, text: &str) -> String {
+        self.paint(text, |text| text.magenta().to_string())
+    }
+
+    pub fn type_name(&self, text: &str) -> String {
+        self.paint(text, |text| text.cyan().to_string())
+    }
+
+    pub fn declaration(&self, text: &str) -> String {
+        self.paint(text, |text| text.bold().cyan().to_string())
+    }
+
+    pub fn address(&self, text: &str) -> String {
+        self.paint(text, |text| text.yellow().to_string())
+    }
+
+    pub fn comment(&self, text: &str) -> String {
+        self.paint(text, |text| text.dimmed().to_string())
+    }
+
+    fn paint(&self, text: &str, paint: impl FnOnce(&str) -> String) -> String {
+        if self.colored {
+            paint(text)
+        } else {
+            text.to_string()
+        }
+    }
+}
@@ -0,0 +1,755 @@
+//! Helpers for building tiny in-memory PE images, so parsers can be tested without a real client binary
+
+use crate::embedded_meta::{
+    handles::{BaseHandle, Handle, HandleType},
+    hashing::compute_name_hashcode,
+};
+
+pub const IMAGE_BASE: u64 = 0x1_4000_0000;
+
+const FILE_ALIGNMENT: u32 = 0x200;
+const SECTION_ALIGNMENT: u32 = 0x1000;
+const HEADERS_SIZE: u32 = 0x400;
+
+struct Section {
+    name: [u8; 8],
+    characteristics: u32,
+    data: Vec<u8>,
+}
+
+/// Builds a PE32+ image where every section is laid out back to back, starting at RVA `0x1000`
+#[derive(Default)]
+pub struct PeBuilder {
+    sections: Vec<Section>,
+}
+
+impl PeBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a section and returns its RVA
+    pub fn section(&mut self, name: &str, data: Vec<u8>) -> u32 {
+        const CNT_INITIALIZED_DATA: u32 = 0x40;
+        const MEM_READ: u32 = 0x4000_0000;
+
+        self.section_with_characteristics(name, data, CNT_INITIALIZED_DATA | MEM_READ)
+    }
+
+    pub fn section_with_characteristics(
+        &mut self,
+        name: &str,
+        data: Vec<u8>,
+        characteristics: u32,
+    ) -> u32 {
+        let rva = self.next_rva();
+
+        let mut raw_name = [0; 8];
+        raw_name[..name.len()].copy_from_slice(name.as_bytes());
+
+        self.sections.push(Section {
+            name: raw_name,
+            characteristics,
+            data,
+        });
+
+        rva
+    }
+
+    /// The RVA the next section will be placed at
+    pub fn next_rva(&self) -> u32 {
+        SECTION_ALIGNMENT
+            + self
+                .sections
+                .iter()
+                .map(|sect| align(sect.data.len().max(1) as u32, SECTION_ALIGNMENT))
+                .sum::<u32>()
+    }
+
+    pub fn build(&self) -> Vec<u8> {
+        let mut image = vec![0; HEADERS_SIZE as usize];
+
+        // DOS header
+        image[0..2].copy_from_slice(b"MZ");
+        image[0x3C..0x40].copy_from_slice(&0x40u32.to_le_bytes());
+
+        // NT headers
+        let mut nt = Vec::new();
+        nt.extend_from_slice(b"PE\0\0");
+        nt.extend_from_slice(&0x8664u16.to_le_bytes()); // Machine
+        nt.extend_from_slice(&(self.sections.len() as u16).to_le_bytes());
+        nt.extend_from_slice(&[0; 12]); // TimeDateStamp, PointerToSymbolTable, NumberOfSymbols
+        nt.extend_from_slice(&0xF0u16.to_le_bytes()); // SizeOfOptionalHeader
+        nt.extend_from_slice(&0x22u16.to_le_bytes()); // Characteristics
+
+        // Optional header
+        nt.extend_from_slice(&0x20Bu16.to_le_bytes()); // Magic
+        nt.extend_from_slice(&[0; 2]); // Linker version
+        nt.extend_from_slice(&[0; 12]); // SizeOfCode, SizeOfInitializedData, SizeOfUninitializedData
+        nt.extend_from_slice(&0u32.to_le_bytes()); // AddressOfEntryPoint
+        nt.extend_from_slice(&SECTION_ALIGNMENT.to_le_bytes()); // BaseOfCode
+        nt.extend_from_slice(&IMAGE_BASE.to_le_bytes());
+        nt.extend_from_slice(&SECTION_ALIGNMENT.to_le_bytes());
+        nt.extend_from_slice(&FILE_ALIGNMENT.to_le_bytes());
+        nt.extend_from_slice(&6u16.to_le_bytes()); // MajorOperatingSystemVersion
+        nt.extend_from_slice(&[0; 6]); // Minor OS version, image version
+        nt.extend_from_slice(&6u16.to_le_bytes()); // MajorSubsystemVersion
+        nt.extend_from_slice(&[0; 6]); // MinorSubsystemVersion, Win32VersionValue
+        nt.extend_from_slice(&self.next_rva().to_le_bytes()); // SizeOfImage
+        nt.extend_from_slice(&HEADERS_SIZE.to_le_bytes());
+        nt.extend_from_slice(&0u32.to_le_bytes()); // CheckSum
+        nt.extend_from_slice(&3u16.to_le_bytes()); // Subsystem (console)
+        nt.extend_from_slice(&0x8160u16.to_le_bytes()); // DllCharacteristics
+        for size in [0x100000u64, 0x1000, 0x100000, 0x1000] {
+            nt.extend_from_slice(&size.to_le_bytes());
+        }
+        nt.extend_from_slice(&0u32.to_le_bytes()); // LoaderFlags
+        nt.extend_from_slice(&16u32.to_le_bytes()); // NumberOfRvaAndSizes
+        nt.extend_from_slice(&[0; 16 * 8]);
+
+        // Section headers
+        let mut raw_offset = HEADERS_SIZE;
+        let mut rva = SECTION_ALIGNMENT;
+        for sect in &self.sections {
+            let raw_size = align(sect.data.len() as u32, FILE_ALIGNMENT);
+
+            nt.extend_from_slice(&sect.name);
+            nt.extend_from_slice(&(sect.data.len() as u32).to_le_bytes()); // VirtualSize
+            nt.extend_from_slice(&rva.to_le_bytes());
+            nt.extend_from_slice(&raw_size.to_le_bytes());
+            nt.extend_from_slice(&raw_offset.to_le_bytes());
+            nt.extend_from_slice(&[0; 12]); // Relocations, line numbers
+            nt.extend_from_slice(&sect.characteristics.to_le_bytes());
+
+            raw_offset += raw_size;
+            rva += align(sect.data.len().max(1) as u32, SECTION_ALIGNMENT);
+        }
+
+        image[0x40..0x40 + nt.len()].copy_from_slice(&nt);
+
+        for sect in &self.sections {
+            let mut data = sect.data.clone();
+            data.resize(align(data.len() as u32, FILE_ALIGNMENT) as usize, 0);
+            image.extend_from_slice(&data);
+        }
+
+        image
+    }
+}
+
+// Encoders matching the NativeAOT `NativeWriter`, used to produce input for the `NativeReader` decoders
+
+pub fn encode_unsigned(buf: &mut Vec<u8>, value: u32) {
+    if value < 128 {
+        buf.push((value << 1) as u8);
+    } else if value < 128 * 128 {
+        buf.push(((value << 2) | 1) as u8);
+        buf.push((value >> 6) as u8);
+    } else if value < 128 * 128 * 128 {
+        buf.push(((value << 3) | 3) as u8);
+        buf.push((value >> 5) as u8);
+        buf.push((value >> 13) as u8);
+    } else if value < 128 * 128 * 128 * 128 {
+        buf.push(((value << 4) | 7) as u8);
+        buf.push((value >> 4) as u8);
+        buf.push((value >> 12) as u8);
+        buf.push((value >> 20) as u8);
+    } else {
+        buf.push(15);
+        buf.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+pub fn encode_signed(buf: &mut Vec<u8>, value: i32) {
+    let bits = value as u32;
+
+    if bits.wrapping_add(64) < 128 {
+        buf.push((bits << 1) as u8);
+    } else if bits.wrapping_add(64 * 128) < 128 * 128 {
+        buf.push(((bits << 2) | 1) as u8);
+        buf.push((bits >> 6) as u8);
+    } else if bits.wrapping_add(64 * 128 * 128) < 128 * 128 * 128 {
+        buf.push(((bits << 3) | 3) as u8);
+        buf.push((bits >> 5) as u8);
+        buf.push((bits >> 13) as u8);
+    } else if bits.wrapping_add(64 * 128 * 128 * 128) < 128 * 128 * 128 * 128 {
+        buf.push(((bits << 4) | 7) as u8);
+        buf.push((bits >> 4) as u8);
+        buf.push((bits >> 12) as u8);
+        buf.push((bits >> 20) as u8);
+    } else {
+        buf.push(15);
+        buf.extend_from_slice(&bits.to_le_bytes());
+    }
+}
+
+pub fn encode_unsigned_long(buf: &mut Vec<u8>, value: u64) {
+    if let Ok(value) = u32::try_from(value) {
+        encode_unsigned(buf, value);
+    } else {
+        buf.push(31);
+        buf.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+pub fn encode_signed_long(buf: &mut Vec<u8>, value: i64) {
+    if let Ok(value) = i32::try_from(value) {
+        encode_signed(buf, value);
+    } else {
+        buf.push(31);
+        buf.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+/// Encodes a `NativeHashtable` with a single bucket, where every entry is a `(hashcode, payload)` pair
+pub fn encode_hashtable(entries: &[(u32, Vec<u8>)]) -> Vec<u8> {
+    let mut entries = entries.iter().collect::<Vec<_>>();
+    entries.sort_by_key(|(hashcode, _)| *hashcode as u8);
+
+    // Payloads come first, so the (negative) relative offsets to them are known while writing the bucket
+    let mut payloads = Vec::new();
+    let mut payload_offsets = Vec::new();
+    for (_, payload) in &entries {
+        payload_offsets.push(3 + payloads.len());
+        payloads.extend_from_slice(payload);
+    }
+
+    // Bucket offsets are relative to the end of the header byte
+    let mut bucket = Vec::new();
+    let bucket_start = 2 + payloads.len();
+    for ((hashcode, _), payload_offset) in entries.iter().zip(payload_offsets) {
+        bucket.push(*hashcode as u8);
+
+        let position = 1 + bucket_start + bucket.len();
+        encode_signed(&mut bucket, payload_offset as i32 - position as i32);
+    }
+
+    let bucket_end = bucket_start + bucket.len();
+    assert!(bucket_end <= u8::MAX as usize, "hashtable too large");
+
+    // Header: 1 bucket, 1 byte bucket offsets
+    let mut buf = vec![0, bucket_start as u8, bucket_end as u8];
+    buf.extend_from_slice(&payloads);
+    buf.extend_from_slice(&bucket);
+
+    buf
+}
+
+/// Writes an unsigned integer in the 5 byte form, which decodes to the same value but has a fixed size
+pub fn encode_unsigned_fixed(buf: &mut Vec<u8>, value: u32) {
+    buf.push(15);
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+/// The raw value of a `BaseHandle` as it is stored in the metadata stream
+pub fn encode_base_handle(handle_type: HandleType, offset: u32) -> u32 {
+    (offset << 7) | handle_type as u32
+}
+
+/// A reference that is written before the record it points to, see [`MetadataBuilder::placeholder`]
+#[derive(Clone, Copy, Debug)]
+pub struct Slot(usize);
+
+/// Builds an `EmbeddedMetadata` blob one record field at a time
+///
+/// Records are written in the order `impl_handle!` declares their fields. Handles are always written in the 5 byte
+/// form, so references to records that haven't been written yet can be patched afterwards.
+pub struct MetadataBuilder {
+    buf: Vec<u8>,
+}
+
+impl Default for MetadataBuilder {
+    fn default() -> Self {
+        Self {
+            buf: 0xDEADDFFDu32.to_le_bytes().to_vec(),
+        }
+    }
+}
+
+impl MetadataBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The offset of the next record, which is what handles to it refer to
+    pub fn offset(&self) -> u32 {
+        self.buf.len() as u32
+    }
+
+    pub fn unsigned(&mut self, value: u32) -> &mut Self {
+        encode_unsigned(&mut self.buf, value);
+        self
+    }
+
+    pub fn signed(&mut self, value: i32) -> &mut Self {
+        encode_signed(&mut self.buf, value);
+        self
+    }
+
+    pub fn byte(&mut self, value: u8) -> &mut Self {
+        self.buf.push(value);
+        self
+    }
+
+    pub fn string(&mut self, value: &str) -> &mut Self {
+        self.unsigned(value.len() as u32);
+        self.buf.extend_from_slice(value.as_bytes());
+        self
+    }
+
+    /// Writes a string record, returning its offset for the handles referring to it
+    pub fn add_string(&mut self, value: &str) -> u32 {
+        let offset = self.offset();
+        self.string(value);
+        offset
+    }
+
+    /// Writes a NamespaceReference for every part of the dotted `name`, below a root namespace without a name
+    ///
+    /// Returns the offset of the innermost namespace, which is what type references are parented to.
+    pub fn namespace_reference(&mut self, name: &str) -> u32 {
+        let mut namespace = self.offset();
+        self.base_handle(HandleType::Null, 0).handle(0);
+
+        for part in name.split('.').filter(|part| !part.is_empty()) {
+            let name = self.add_string(part);
+            let parent = namespace;
+
+            namespace = self.offset();
+            self.base_handle(HandleType::NamespaceReference, parent)
+                .handle(name);
+        }
+
+        namespace
+    }
+
+    /// Writes a TypeReference, parented to a NamespaceReference or, for nested types, to another TypeReference
+    pub fn type_reference(&mut self, parent_type: HandleType, parent: u32, name: &str) -> u32 {
+        let name = self.add_string(name);
+
+        let offset = self.offset();
+        self.base_handle(parent_type, parent).handle(name);
+        offset
+    }
+
+    /// A typed handle (e.g. `TypeDefinitionHandle`), which is stored as a bare offset
+    pub fn handle(&mut self, offset: u32) -> &mut Self {
+        encode_unsigned_fixed(&mut self.buf, offset);
+        self
+    }
+
+    pub fn base_handle(&mut self, handle_type: HandleType, offset: u32) -> &mut Self {
+        encode_unsigned_fixed(&mut self.buf, encode_base_handle(handle_type, offset));
+        self
+    }
+
+    /// A collection of typed handles
+    pub fn handles(&mut self, offsets: &[u32]) -> &mut Self {
+        self.unsigned(offsets.len() as u32);
+        for &offset in offsets {
+            self.handle(offset);
+        }
+        self
+    }
+
+    pub fn empty_collection(&mut self) -> &mut Self {
+        self.unsigned(0)
+    }
+
+    /// Reserves room for a handle, to be filled in with [`Self::patch`] or [`Self::patch_base`]
+    pub fn placeholder(&mut self) -> Slot {
+        let slot = Slot(self.buf.len());
+        encode_unsigned_fixed(&mut self.buf, 0);
+        slot
+    }
+
+    pub fn patch(&mut self, slot: Slot, offset: u32) {
+        self.buf[slot.0 + 1..slot.0 + 5].copy_from_slice(&offset.to_le_bytes());
+    }
+
+    pub fn patch_base(&mut self, slot: Slot, handle_type: HandleType, offset: u32) {
+        self.patch(slot, encode_base_handle(handle_type, offset));
+    }
+
+    pub fn build(&self) -> Vec<u8> {
+        let mut buf = self.buf.clone();
+
+        // The reader refuses to decode the very last byte of a stream
+        buf.extend_from_slice(&[0; 8]);
+        buf
+    }
+}
+
+/// Offsets of the records in [`minimal_metadata`]
+#[derive(Clone, Copy, Debug)]
+pub struct MinimalMetadata {
+    pub scope: u32,
+    pub root_namespace: u32,
+    pub namespace: u32,
+    pub typ: u32,
+    pub method: u32,
+}
+
+/// Metadata with a single scope `Hytale.Test` (1.2.3.4), defining `Game.Player` with a single method `void Jump()`
+pub fn minimal_metadata() -> (Vec<u8>, MinimalMetadata) {
+    minimal_metadata_with_scopes(&[])
+}
+
+/// Like [`minimal_metadata`], followed by a scope without any types for every name in `extra_scopes`
+pub fn minimal_metadata_with_scopes(extra_scopes: &[&str]) -> (Vec<u8>, MinimalMetadata) {
+    let mut builder = MetadataBuilder::new();
+
+    // Header: the scope definitions
+    builder.unsigned(1 + extra_scopes.len() as u32);
+    let scope_slots = (0..=extra_scopes.len())
+        .map(|_| builder.placeholder())
+        .collect::<Vec<_>>();
+
+    let scope_name = builder.add_string("Hytale.Test");
+    let namespace_name = builder.add_string("Game");
+    let type_name = builder.add_string("Player");
+    let method_name = builder.add_string("Jump");
+
+    // MethodSignature: `void ()`
+    let signature = builder.offset();
+    builder
+        .byte(0x20) // HasThis
+        .signed(0)
+        .base_handle(HandleType::Null, 0)
+        .empty_collection()
+        .empty_collection();
+
+    let method = builder.offset();
+    builder
+        .unsigned(0x0006) // Public
+        .unsigned(0)
+        .handle(method_name)
+        .handle(signature)
+        .empty_collection()
+        .empty_collection()
+        .empty_collection();
+
+    let typ = builder.offset();
+    builder.unsigned(0x0001).base_handle(HandleType::Null, 0); // Public
+    let type_namespace_slot = builder.placeholder();
+    builder
+        .handle(type_name)
+        .unsigned(0)
+        .unsigned(0)
+        .handle(0)
+        .empty_collection()
+        .handles(&[method])
+        .empty_collection()
+        .empty_collection()
+        .empty_collection()
+        .empty_collection()
+        .empty_collection()
+        .empty_collection();
+
+    let namespace = builder.offset();
+    let namespace_parent_slot = builder.placeholder();
+    builder
+        .handle(namespace_name)
+        .handles(&[typ])
+        .empty_collection()
+        .empty_collection();
+
+    // The root namespace has no name
+    let root_namespace = builder.offset();
+    let root_parent_slot = builder.placeholder();
+    builder
+        .handle(0)
+        .empty_collection()
+        .empty_collection()
+        .handles(&[namespace]);
+
+    fn scope_definition(builder: &mut MetadataBuilder, name: u32, root_namespace: u32) -> u32 {
+        let scope = builder.offset();
+        builder
+            .unsigned(0)
+            .handle(name)
+            .unsigned(0)
+            .unsigned(1)
+            .unsigned(2)
+            .unsigned(3)
+            .unsigned(4)
+            .empty_collection()
+            .handle(0)
+            .handle(root_namespace)
+            .handle(0)
+            .handle(0)
+            .empty_collection()
+            .handle(0)
+            .empty_collection()
+            .empty_collection();
+        scope
+    }
+
+    let scope = scope_definition(&mut builder, scope_name, root_namespace);
+
+    for (&name, &slot) in extra_scopes.iter().zip(&scope_slots[1..]) {
+        let name = builder.add_string(name);
+
+        let root_namespace = builder.offset();
+        let parent_slot = builder.placeholder();
+        builder
+            .handle(0)
+            .empty_collection()
+            .empty_collection()
+            .empty_collection();
+
+        let extra_scope = scope_definition(&mut builder, name, root_namespace);
+        builder.patch(slot, extra_scope);
+        builder.patch_base(parent_slot, HandleType::ScopeDefinition, extra_scope);
+    }
+
+    builder.patch(scope_slots[0], scope);
+    builder.patch(type_namespace_slot, namespace);
+    builder.patch_base(
+        namespace_parent_slot,
+        HandleType::NamespaceDefinition,
+        root_namespace,
+    );
+    builder.patch_base(root_parent_slot, HandleType::ScopeDefinition, scope);
+
+    let offsets = MinimalMetadata {
+        scope,
+        root_namespace,
+        namespace,
+        typ,
+        method,
+    };
+
+    (builder.build(), offsets)
+}
+
+/// Builds a ReadyToRun header, where every section is a `(section type, start VA, end VA)` tuple
+pub fn encode_rtr_header(sections: &[(u32, u64, u64)]) -> Vec<u8> {
+    let mut buf = Vec::new();
+
+    buf.extend_from_slice(b"RTR\0");
+    buf.extend_from_slice(&16u16.to_le_bytes()); // MajorVersion
+    buf.extend_from_slice(&0u16.to_le_bytes()); // MinorVersion
+    buf.extend_from_slice(&0u32.to_le_bytes()); // Flags
+    buf.extend_from_slice(&(sections.len() as u16).to_le_bytes());
+    buf.push(24); // EntrySize
+    buf.push(1); // EntryType
+
+    for &(section_type, start, end) in sections {
+        buf.extend_from_slice(&section_type.to_le_bytes());
+        buf.extend_from_slice(&0u32.to_le_bytes()); // Flags
+        buf.extend_from_slice(&start.to_le_bytes());
+        buf.extend_from_slice(&end.to_le_bytes());
+    }
+
+    buf
+}
+
+/// The size of a ReadyToRun header with `sections` sections
+pub const fn rtr_header_size(sections: usize) -> usize {
+    16 + 24 * sections
+}
+
+/// A PE image with a ReadyToRun header at the start of `.rdata`, whose only section is the [`minimal_metadata`]
+pub fn minimal_image() -> (Vec<u8>, MinimalMetadata) {
+    minimal_image_with_sections(&[])
+}
+
+/// Like [`minimal_image`], with `extra_sections` listed after the metadata in the ReadyToRun header
+pub fn minimal_image_with_sections(
+    extra_sections: &[(u32, u64, u64)],
+) -> (Vec<u8>, MinimalMetadata) {
+    const EMBEDDED_METADATA: u32 = 300 + 13;
+
+    let (metadata, offsets) = minimal_metadata();
+
+    let mut builder = PeBuilder::new();
+    let rdata_va = IMAGE_BASE + builder.next_rva() as u64;
+
+    let metadata_va = rdata_va + rtr_header_size(1 + extra_sections.len()) as u64;
+    let mut sections = vec![(
+        EMBEDDED_METADATA,
+        metadata_va,
+        metadata_va + metadata.len() as u64,
+    )];
+    sections.extend_from_slice(extra_sections);
+
+    let mut rdata = encode_rtr_header(&sections);
+    rdata.extend_from_slice(&metadata);
+
+    builder.section(".rdata", rdata);
+
+    (builder.build(), offsets)
+}
+
+/// Encodes the header of a MethodTable followed by its vtable and interface map, the optional fields are left to the
+/// caller
+pub fn encode_method_table(
+    flags: u32,
+    base_size: u32,
+    related_type: u64,
+    vtable: &[u64],
+    ifaces: &[u64],
+    hashcode: u32,
+) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&flags.to_le_bytes());
+    data.extend_from_slice(&base_size.to_le_bytes());
+    data.extend_from_slice(&related_type.to_le_bytes());
+    data.extend_from_slice(&(vtable.len() as u16).to_le_bytes());
+    data.extend_from_slice(&(ifaces.len() as u16).to_le_bytes());
+    data.extend_from_slice(&hashcode.to_le_bytes());
+
+    for &va in vtable.iter().chain(ifaces) {
+        data.extend_from_slice(&va.to_le_bytes());
+    }
+
+    data
+}
+
+/// A relative pointer stored at `location`, like the optional fields of a MethodTable
+pub fn encode_relative_pointer(location: u64, target: u64) -> [u8; 4] {
+    ((target as i64 - location as i64) as i32).to_le_bytes()
+}
+
+/// A PE image with everything `DumpIDA` needs to name a type and a function
+///
+/// The metadata is the [`minimal_metadata`] together with the assemblies of the Hytale client. `.data` holds the
+/// MethodTables of `System.Object`, which has no metadata, `System.__Canon` and `Game.Player`, which the TypeMap
+/// names. The InvokeMap points `Game.Player.Jump` to a function in `.text`.
+pub fn ida_fixture_image() -> Vec<u8> {
+    ida_fixture_image_with_sections(|_| Vec::new())
+}
+
+/// Like [`ida_fixture_image`], with the `(section type, contents)` pairs built from the metadata offsets listed after
+/// the InvokeMap
+///
+/// The common fixups table has the MethodTable of `Game.Player` at index 0 and `Game.Player.Jump` at index 1.
+pub fn ida_fixture_image_with_sections(
+    extra_sections: impl FnOnce(&MinimalMetadata) -> Vec<(u32, Vec<u8>)>,
+) -> Vec<u8> {
+    const CLASS: u32 = 0x14 << 26;
+    const EMBEDDED_METADATA: u32 = 300 + 13;
+    const TYPE_MAP: u32 = 300 + 1;
+    const INVOKE_MAP: u32 = 300 + 6;
+    const COMMON_FIXUPS_TABLE: u32 = 300 + 8;
+
+    let (metadata, offsets) = minimal_metadata_with_scopes(&[
+        "Hytale.Nat",
+        "Hytale.Protocol",
+        "Hytale.Protocol.Runtime",
+        "HytaleClient",
+        "Noesis.GUI",
+        "HytaleClient.Interop",
+    ]);
+
+    let mut builder = PeBuilder::new();
+
+    // Every function is a lone `ret`
+    let text_va = IMAGE_BASE + builder.section(".text", vec![0xC3; 0x40]) as u64;
+    let vtable = [text_va, text_va + 0x10, text_va + 0x20];
+    let jump_va = text_va + 0x30;
+
+    // Every MethodTable shares the vtable of System.Object
+    let data_va = IMAGE_BASE + builder.next_rva() as u64;
+    let object_mt = data_va;
+    let player_hashcode = compute_name_hashcode("Game.Player") as u32;
+
+    let mut data = Vec::new();
+    let mut player_mt = 0;
+    for (related_type, hashcode) in [
+        (0, compute_name_hashcode("System.Object") as u32),
+        (object_mt, compute_name_hashcode("System.__Canon") as u32),
+        (object_mt, player_hashcode),
+    ] {
+        player_mt = data_va + data.len() as u64;
+
+        data.extend(encode_method_table(
+            CLASS,
+            0x18,
+            related_type,
+            &vtable,
+            &[],
+            hashcode,
+        ));
+    }
+    builder.section(".data", data);
+
+    // TypeMap: MethodTable + TypeDefinition
+    let mut type_entry = Vec::new();
+    encode_unsigned(&mut type_entry, 0);
+    encode_unsigned(
+        &mut type_entry,
+        BaseHandle::from_offset(HandleType::TypeDefinition, offsets.typ).to_value(),
+    );
+    let type_map = encode_hashtable(&[(player_hashcode, type_entry)]);
+
+    // InvokeMap: Flags + MethodHandle + DeclaringType + Entrypoint
+    let mut method_entry = Vec::new();
+    encode_unsigned(&mut method_entry, 0x04 | 0x20); // HasMetadataHandle | HasEntrypoint
+    encode_unsigned(&mut method_entry, offsets.method);
+    encode_unsigned(&mut method_entry, 0);
+    encode_unsigned(&mut method_entry, 1);
+    let invoke_map = encode_hashtable(&[(player_hashcode, method_entry)]);
+
+    let extra_sections = extra_sections(&offsets);
+
+    let rdata_va = IMAGE_BASE + builder.next_rva() as u64;
+    let metadata_va = rdata_va + rtr_header_size(4 + extra_sections.len()) as u64;
+    let fixups_va = metadata_va + metadata.len() as u64;
+    let fixups = [player_mt, jump_va]
+        .iter()
+        .enumerate()
+        .flat_map(|(index, &target)| {
+            ((target as i64 - (fixups_va + 4 * index as u64) as i64) as i32).to_le_bytes()
+        })
+        .collect::<Vec<_>>();
+    let type_map_va = fixups_va + fixups.len() as u64;
+    let invoke_map_va = type_map_va + type_map.len() as u64;
+
+    let mut sections = vec![
+        (
+            EMBEDDED_METADATA,
+            metadata_va,
+            metadata_va + metadata.len() as u64,
+        ),
+        (
+            COMMON_FIXUPS_TABLE,
+            fixups_va,
+            fixups_va + fixups.len() as u64,
+        ),
+        (TYPE_MAP, type_map_va, invoke_map_va),
+        (
+            INVOKE_MAP,
+            invoke_map_va,
+            invoke_map_va + invoke_map.len() as u64,
+        ),
+    ];
+
+    // The extra sections are 4 byte aligned, so tables of structs like the RuntimeFunctions can be read in place
+    let mut position = invoke_map_va + invoke_map.len() as u64;
+    let mut padding = Vec::new();
+    for (section_type, contents) in &extra_sections {
+        let start = position.next_multiple_of(4);
+        padding.push((start - position) as usize);
+        sections.push((*section_type, start, start + contents.len() as u64));
+        position = start + contents.len() as u64;
+    }
+
+    let mut rdata = encode_rtr_header(&sections);
+    rdata.extend_from_slice(&metadata);
+    rdata.extend_from_slice(&fixups);
+    rdata.extend_from_slice(&type_map);
+    rdata.extend_from_slice(&invoke_map);
+    for ((_, contents), padding) in extra_sections.iter().zip(padding) {
+        rdata.resize(rdata.len() + padding, 0);
+        rdata.extend_from_slice(contents);
+    }
+    builder.section(".rdata", rdata);
+
+    builder.build()
+}
+
+fn align(value: u32, alignment: u32) -> u32 {
+    value.div_ceil(alignment) * alignment
+}
@@ -0,0 +1,100 @@
+//! Bindings for listing the assemblies and types of a raw metadata blob from JavaScript
+//!
+//! The blob is the EmbeddedMetadata section of a binary, which `MetadataReader::new` parses without the PE image
+//! around it. Results are returned as JSON.
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::embedded_meta::MetadataReader;
+
+#[derive(Serialize)]
+struct AssemblyEntry {
+    name: String,
+    version: String,
+}
+
+#[derive(Serialize)]
+struct TypeEntry {
+    name: String,
+    assembly: String,
+}
+
+/// The assemblies defined in the metadata, as a JSON array of names and versions
+#[wasm_bindgen(js_name = getAssemblies)]
+pub fn get_assemblies(metadata: &[u8]) -> Result<String, JsError> {
+    let metadata = MetadataReader::new(metadata)?;
+    let mut assemblies = Vec::new();
+
+    for scope in metadata
+        .header()
+        .scope_definitions()
+        .iter()?
+        .flatten()
+        .flat_map(|hdl| hdl.to_data(metadata))
+    {
+        let Ok(name) = scope.assembly_name() else {
+            continue;
+        };
+
+        let (major, minor, build, revision) = name.version;
+        assemblies.push(AssemblyEntry {
+            name: name.name,
+            version: format!("{major}.{minor}.{build}.{revision}"),
+        });
+    }
+
+    Ok(serde_json::to_string(&assemblies)?)
+}
+
+/// The types defined in the metadata, or only those of `assembly`, as a JSON array of full names
+#[wasm_bindgen(js_name = getTypes)]
+pub fn get_types(metadata: &[u8], assembly: Option<String>) -> Result<String, JsError> {
+    let metadata = MetadataReader::new(metadata)?;
+    let mut types = Vec::new();
+
+    for scope in metadata
+        .header()
+        .scope_definitions()
+        .iter()?
+        .flatten()
+        .flat_map(|hdl| hdl.to_data(metadata))
+    {
+        let scope_name = scope.name.to_data(metadata)?.value;
+        if assembly.as_ref().is_some_and(|name| *name != scope_name) {
+            continue;
+        }
+
+        for typ in scope.get_all_types()? {
+            types.push(TypeEntry {
+                name: typ.get_full_name_with_generics()?,
+                assembly: scope_name.clone(),
+            });
+        }
+    }
+
+    Ok(serde_json::to_string(&types)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_utils::minimal_metadata,
+        wasm::{get_assemblies, get_types},
+    };
+
+    #[test]
+    fn assemblies_and_types_are_listed_from_the_blob() {
+        let (blob, _) = minimal_metadata();
+
+        assert_eq!(
+            get_assemblies(&blob).unwrap(),
+            r#"[{"name":"Hytale.Test","version":"1.2.3.4"}]"#
+        );
+        assert_eq!(
+            get_types(&blob, Some("Hytale.Test".to_string())).unwrap(),
+            r#"[{"name":"Game.Player","assembly":"Hytale.Test"}]"#
+        );
+        assert_eq!(get_types(&blob, Some("Other".to_string())).unwrap(), "[]");
+    }
+}